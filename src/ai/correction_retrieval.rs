@@ -0,0 +1,218 @@
+//! Picks the past corrections most like a new input, for the parsing
+//! prompt's few-shot examples. Corrections are ranked by BM25 over their
+//! original text, near-duplicates are dropped, and examples are added until
+//! a token budget is spent.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::ai::llm_service::CorrectionExample;
+use crate::db::correction_repo::CorrectionRecord;
+use crate::matching;
+
+/// Roughly how many prompt tokens past corrections may take up.
+pub const TOKEN_BUDGET: usize = 800;
+
+/// Corrections whose inputs share this much of their words (Jaccard) are
+/// treated as the same mistake.
+const DUPLICATE_SIMILARITY: f64 = 0.8;
+
+// Standard BM25 parameters
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+/// Words too common in descriptions to say anything about similarity.
+const STOPWORDS: &[&str] = &[
+    "a", "about", "an", "and", "at", "for", "her", "his", "i", "in", "is", "it", "me", "my", "of", "on", "the",
+    "their", "to", "was", "we", "with",
+];
+
+/// The corrections to show for `input`: pinned ones first, whatever their
+/// cost, then the rest most relevant first. `records` are newest first;
+/// ties and corrections sharing no words with the input keep that order,
+/// after the matching ones.
+pub fn select(input: &str, records: Vec<CorrectionRecord>, budget: usize) -> Vec<CorrectionExample> {
+    let (pinned, others): (Vec<_>, Vec<_>) = records.into_iter().partition(|r| r.pinned);
+    let mut spent = pinned.iter().map(estimate_tokens).sum::<usize>();
+    let seen: Vec<HashSet<String>> = pinned.iter().map(|r| word_set(&r.original_text)).collect();
+    let mut selected: Vec<CorrectionExample> = pinned.into_iter().map(example).collect();
+
+    let records = dedupe(seen, others);
+    let docs: Vec<Vec<String>> = records.iter().map(|r| tokens(&r.original_text)).collect();
+    let scores = bm25(&tokens(input), &docs);
+
+    let mut order: Vec<usize> = (0..records.len()).collect();
+    order.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]).then(a.cmp(&b)));
+
+    let mut records: Vec<Option<CorrectionRecord>> = records.into_iter().map(Some).collect();
+    for i in order {
+        let Some(r) = records[i].take() else { continue };
+        let cost = estimate_tokens(&r);
+        if spent + cost > budget {
+            continue;
+        }
+        spent += cost;
+        selected.push(example(r));
+    }
+    selected
+}
+
+fn example(r: CorrectionRecord) -> CorrectionExample {
+    CorrectionExample {
+        original_text: r.original_text,
+        ai_output: r.ai_output,
+        user_output: r.user_output,
+    }
+}
+
+/// Drops corrections whose input nearly repeats a newer one's, or one of
+/// the `seen` word sets.
+fn dedupe(mut seen: Vec<HashSet<String>>, records: Vec<CorrectionRecord>) -> Vec<CorrectionRecord> {
+    let mut kept = Vec::new();
+    for record in records {
+        let words = word_set(&record.original_text);
+        if seen.iter().all(|other| jaccard(&words, other) < DUPLICATE_SIMILARITY) {
+            seen.push(words);
+            kept.push(record);
+        }
+    }
+    kept
+}
+
+/// BM25 score of each document for the query.
+fn bm25(query: &[String], docs: &[Vec<String>]) -> Vec<f64> {
+    if docs.is_empty() {
+        return Vec::new();
+    }
+    let n = docs.len() as f64;
+    let avg_len = docs.iter().map(|d| d.len()).sum::<usize>() as f64 / n;
+    let mut doc_freq: HashMap<&str, usize> = HashMap::new();
+    for doc in docs {
+        for term in doc.iter().map(String::as_str).collect::<HashSet<_>>() {
+            *doc_freq.entry(term).or_default() += 1;
+        }
+    }
+    let query: HashSet<&str> = query.iter().map(String::as_str).collect();
+
+    docs.iter()
+        .map(|doc| {
+            let len = doc.len() as f64;
+            query
+                .iter()
+                .filter_map(|term| {
+                    let df = *doc_freq.get(term)? as f64;
+                    let tf = doc.iter().filter(|t| t == term).count() as f64;
+                    let idf = ((n - df + 0.5) / (df + 0.5) + 1.0).ln();
+                    Some(idf * tf * (K1 + 1.0) / (tf + K1 * (1.0 - B + B * len / avg_len.max(1.0))))
+                })
+                .sum()
+        })
+        .collect()
+}
+
+fn tokens(text: &str) -> Vec<String> {
+    matching::fold(text)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty() && !STOPWORDS.contains(w))
+        .map(|w| w.to_string())
+        .collect()
+}
+
+fn word_set(text: &str) -> HashSet<String> {
+    tokens(text).into_iter().collect()
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 1.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+/// About four characters per token, plus the example's framing.
+fn estimate_tokens(record: &CorrectionRecord) -> usize {
+    let chars = record.original_text.chars().count() + record.ai_output.chars().count() + record.user_output.chars().count();
+    chars.div_ceil(4) + 12
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::correction_repo::CorrectionKind;
+
+    fn record(text: &str) -> CorrectionRecord {
+        CorrectionRecord {
+            id: uuid::Uuid::new_v4(),
+            original_text: text.into(),
+            ai_output: r#"{"medium":"InPerson"}"#.into(),
+            user_output: r#"{"medium":"PhoneCall"}"#.into(),
+            created_at: "2024-06-01 12:00:00".into(),
+            pinned: false,
+            kind: CorrectionKind::Interaction,
+        }
+    }
+
+    fn inputs(selected: &[CorrectionExample]) -> Vec<&str> {
+        selected.iter().map(|c| c.original_text.as_str()).collect()
+    }
+
+    #[test]
+    fn ranks_by_similarity_then_recency() {
+        let records = vec![
+            record("Lunch with Bo at the office"),
+            record("Facetimed Ann about her new job"),
+            record("Drinks with Cy downtown"),
+            record("Facetimed Dee from Lisbon"),
+        ];
+        let selected = select("Facetimed Ann last night", records, TOKEN_BUDGET);
+        assert_eq!(
+            inputs(&selected),
+            vec![
+                "Facetimed Ann about her new job",
+                "Facetimed Dee from Lisbon",
+                "Lunch with Bo at the office",
+                "Drinks with Cy downtown",
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_the_newest_of_near_duplicates() {
+        let mut older = record("Coffee with Ann at Blue Bottle");
+        older.user_output = "older".into();
+        let records = vec![record("coffee with Ann at Blue Bottle!"), older, record("Coffee with Bo")];
+        let selected = select("coffee with Ann", records, TOKEN_BUDGET);
+        assert_eq!(inputs(&selected), vec!["coffee with Ann at Blue Bottle!", "Coffee with Bo"]);
+        assert!(selected.iter().all(|c| c.user_output != "older"));
+    }
+
+    #[test]
+    fn stops_at_the_token_budget() {
+        let long = format!("Called Ann {}", "and talked at length ".repeat(40));
+        let records = vec![record(&long), record("Called Ann"), record("Called Bo")];
+        let budget = estimate_tokens(&records[1]) * 2;
+        // The long one matches better than "Called Bo" but doesn't fit
+        let selected = select("Called Ann", records, budget);
+        assert_eq!(inputs(&selected), vec!["Called Ann", "Called Bo"]);
+        assert!(select("Called Ann", vec![record("Called Ann")], 5).is_empty());
+    }
+
+    #[test]
+    fn pinned_corrections_always_come_first() {
+        let mut pinned = record("Texted Bo a long story about nothing in particular");
+        pinned.pinned = true;
+        let mut duplicate_of_pinned = record("texted Bo a long story about nothing in particular");
+        duplicate_of_pinned.user_output = "dup".into();
+        let records = vec![record("Coffee with Ann"), duplicate_of_pinned, pinned];
+
+        let selected = select("Coffee with Ann", records, 1);
+        assert_eq!(inputs(&selected), vec!["Texted Bo a long story about nothing in particular"]);
+
+        let records = vec![record("Coffee with Ann"), {
+            let mut p = record("Texted Bo");
+            p.pinned = true;
+            p
+        }];
+        assert_eq!(inputs(&select("Coffee with Ann", records, TOKEN_BUDGET)), vec!["Texted Bo", "Coffee with Ann"]);
+    }
+}
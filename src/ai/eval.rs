@@ -0,0 +1,263 @@
+//! Scores the interaction parser against labeled examples, so prompt and
+//! model changes can be compared on numbers instead of impressions.
+//!
+//! A dataset is a JSONL file, one case per line:
+//!
+//! ```text
+//! {"input": "Lunch with Ann at Tartine", "expected": {"person_names": ["Ann"], "medium": "InPerson", ...}}
+//! ```
+//!
+//! `expected` uses the same shape as the saved AI corrections, so a dataset
+//! can be built from them with `cases_from_corrections`.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::ai::llm_backend::LlmBackend;
+use crate::ai::llm_service::{self, LlmError, ParsedInteraction};
+use crate::db::correction_repo::CorrectionRecord;
+
+/// One labeled example.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalCase {
+    pub input: String,
+    /// Contacts the prompt should know about. Empty means the caller's
+    /// network.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub known_names: Vec<String>,
+    /// The date the input was written, for relative dates like "yesterday".
+    /// `None` means today.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub today: Option<NaiveDate>,
+    pub expected: ParsedInteraction,
+}
+
+/// Reads a JSONL dataset, skipping blank lines.
+pub fn load_dataset(path: &Path) -> Result<Vec<EvalCase>, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).map_err(|e| format!("{} line {}: {}", path.display(), i + 1, e))
+        })
+        .collect()
+}
+
+pub fn save_dataset(path: &Path, cases: &[EvalCase]) -> Result<(), String> {
+    let mut text = String::new();
+    for case in cases {
+        text.push_str(&serde_json::to_string(case).map_err(|e| e.to_string())?);
+        text.push('\n');
+    }
+    fs::write(path, text).map_err(|e| format!("Cannot write {}: {}", path.display(), e))
+}
+
+/// Turns saved corrections into cases: the user's version is the expected
+/// answer, dated when the correction was made. Corrections that can't be
+/// read back are skipped.
+pub fn cases_from_corrections(records: &[CorrectionRecord], known_names: &[String]) -> Vec<EvalCase> {
+    records
+        .iter()
+        .filter_map(|r| {
+            let expected = serde_json::from_str(&r.user_output).ok()?;
+            Some(EvalCase {
+                input: r.original_text.clone(),
+                known_names: known_names.to_vec(),
+                today: r.created_at.get(..10).and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok()),
+                expected,
+            })
+        })
+        .collect()
+}
+
+/// Totals over a run. Accuracies are over all cases, so a failed parse
+/// counts as wrong on every field.
+#[derive(Debug, Clone, Default)]
+pub struct EvalReport {
+    pub cases: usize,
+    pub failures: usize,
+    pub names_correct: usize,
+    pub medium_correct: usize,
+    pub date_correct: usize,
+    pub location_correct: usize,
+    /// Predicted topics that were expected.
+    pub topics_matched: usize,
+    pub topics_predicted: usize,
+    pub topics_expected: usize,
+    pub latencies: Vec<Duration>,
+}
+
+impl EvalReport {
+    /// Adds one case's outcome.
+    pub fn record(
+        &mut self,
+        expected: &ParsedInteraction,
+        result: &Result<ParsedInteraction, LlmError>,
+        elapsed: Duration,
+    ) {
+        self.cases += 1;
+        self.latencies.push(elapsed);
+        self.topics_expected += normalized(&expected.topics).len();
+        let actual = match result {
+            Ok(actual) => actual,
+            Err(_) => {
+                self.failures += 1;
+                return;
+            }
+        };
+
+        if normalized(&actual.person_names) == normalized(&expected.person_names) {
+            self.names_correct += 1;
+        }
+        if actual.medium == expected.medium {
+            self.medium_correct += 1;
+        }
+        if actual.date == expected.date {
+            self.date_correct += 1;
+        }
+        if actual.location.trim().to_lowercase() == expected.location.trim().to_lowercase() {
+            self.location_correct += 1;
+        }
+        let predicted = normalized(&actual.topics);
+        self.topics_predicted += predicted.len();
+        self.topics_matched += predicted.intersection(&normalized(&expected.topics)).count();
+    }
+
+    /// Share of cases with this many correct, from 0 to 1.
+    pub fn accuracy(&self, correct: usize) -> f64 {
+        ratio(correct, self.cases)
+    }
+
+    pub fn topic_precision(&self) -> f64 {
+        ratio(self.topics_matched, self.topics_predicted)
+    }
+
+    pub fn topic_recall(&self) -> f64 {
+        ratio(self.topics_matched, self.topics_expected)
+    }
+
+    pub fn failure_rate(&self) -> f64 {
+        ratio(self.failures, self.cases)
+    }
+
+    pub fn median_latency(&self) -> Duration {
+        let mut sorted = self.latencies.clone();
+        sorted.sort();
+        sorted.get(sorted.len() / 2).copied().unwrap_or_default()
+    }
+
+    pub fn max_latency(&self) -> Duration {
+        self.latencies.iter().max().copied().unwrap_or_default()
+    }
+}
+
+/// Parses every case with `backend` and the prompt `template` (see
+/// `llm_service::interaction_prompt`), without past corrections so the
+/// scores reflect the prompt alone.
+pub fn run(backend: &dyn LlmBackend, template: &str, cases: &[EvalCase]) -> EvalReport {
+    let mut report = EvalReport::default();
+    for case in cases {
+        let today = case.today.unwrap_or_else(|| chrono::Local::now().date_naive());
+        let prompt = llm_service::interaction_prompt(template, today, &case.known_names, &[]);
+        let started = Instant::now();
+        let result = llm_service::parse_with_prompt(backend, &prompt, &case.input);
+        report.record(&case.expected, &result, started.elapsed());
+    }
+    report
+}
+
+fn normalized(items: &[String]) -> BTreeSet<String> {
+    items
+        .iter()
+        .map(|s| s.trim().to_lowercase())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+fn ratio(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::correction_repo::CorrectionKind;
+
+    fn parsed(names: &[&str], medium: &str, location: &str, topics: &[&str], date: Option<&str>) -> ParsedInteraction {
+        ParsedInteraction {
+            person_names: names.iter().map(|s| s.to_string()).collect(),
+            medium: medium.into(),
+            location: location.into(),
+            their_location: None,
+            topics: topics.iter().map(|s| s.to_string()).collect(),
+            note: None,
+            date: date.map(|d| d.to_string()),
+        }
+    }
+
+    #[test]
+    fn report_scores_each_field() {
+        let expected = parsed(&["Ann", "Bo"], "InPerson", "Tartine", &["lunch", "move"], None);
+        let mut report = EvalReport::default();
+
+        // Names in another order and case, one extra topic, wrong date
+        let close = parsed(&["bo", "Ann"], "InPerson", "tartine ", &["Lunch", "move", "work"], Some("2024-06-01"));
+        report.record(&expected, &Ok(close), Duration::from_millis(300));
+        // Missing a name and a topic
+        let off = parsed(&["Ann"], "Text", "", &["lunch"], None);
+        report.record(&expected, &Ok(off), Duration::from_millis(100));
+        report.record(&expected, &Err(LlmError::Backend("down".into())), Duration::from_millis(200));
+
+        assert_eq!(report.cases, 3);
+        assert_eq!(report.accuracy(report.names_correct), 1.0 / 3.0);
+        assert_eq!(report.medium_correct, 1);
+        assert_eq!(report.date_correct, 1);
+        assert_eq!(report.location_correct, 1);
+        assert_eq!(report.topic_precision(), 3.0 / 4.0);
+        assert_eq!(report.topic_recall(), 3.0 / 6.0);
+        assert_eq!(report.failure_rate(), 1.0 / 3.0);
+        assert_eq!(report.median_latency(), Duration::from_millis(200));
+        assert_eq!(report.max_latency(), Duration::from_millis(300));
+    }
+
+    #[test]
+    fn corrections_become_dated_cases() {
+        let expected = parsed(&["Ann"], "PhoneCall", "", &["move"], Some("2024-05-31"));
+        let records = vec![
+            CorrectionRecord {
+                id: uuid::Uuid::new_v4(),
+                original_text: "Called Ann yesterday about the move".into(),
+                ai_output: "{}".into(),
+                user_output: serde_json::to_string(&expected).unwrap(),
+                created_at: "2024-06-01 18:30:00".into(),
+                pinned: false,
+                kind: CorrectionKind::Interaction,
+            },
+            CorrectionRecord {
+                id: uuid::Uuid::new_v4(),
+                original_text: "garbled".into(),
+                ai_output: "{}".into(),
+                user_output: "not json".into(),
+                created_at: "2024-06-02 09:00:00".into(),
+                pinned: false,
+                kind: CorrectionKind::Interaction,
+            },
+        ];
+
+        let cases = cases_from_corrections(&records, &["Ann".to_string()]);
+        assert_eq!(cases.len(), 1);
+        assert_eq!(cases[0].today, NaiveDate::from_ymd_opt(2024, 6, 1));
+        assert_eq!(cases[0].known_names, vec!["Ann"]);
+        assert_eq!(cases[0].expected.date.as_deref(), Some("2024-05-31"));
+    }
+}
@@ -0,0 +1,172 @@
+//! The JSON the model is asked to produce for `ParsedInteraction`: its JSON
+//! schema (passed as the structured-output format) and a validator that
+//! explains what's wrong, so the model can be asked to fix it.
+
+use serde_json::{json, Map, Value};
+
+/// Values accepted for `medium`.
+pub const MEDIUMS: &[&str] = &["InPerson", "Text", "PhoneCall", "VideoCall", "SocialMedia", "Email"];
+
+/// The shape of one field in the model's reply.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    /// A string, possibly empty.
+    Text,
+    /// A string or null.
+    OptionalText,
+    /// A `YYYY-MM-DD` string or null.
+    OptionalDate,
+    /// One of `MEDIUMS`.
+    Medium,
+    /// An array of at least one non-blank string.
+    NonEmptyList,
+}
+
+/// The model's field names (camelCase) for each `ParsedInteraction` field,
+/// in order. All are required so the model always fills them in.
+const FIELDS: &[(&str, Kind)] = &[
+    ("personNames", Kind::NonEmptyList),
+    ("medium", Kind::Medium),
+    ("location", Kind::Text),
+    ("theirLocation", Kind::OptionalText),
+    ("topics", Kind::NonEmptyList),
+    ("note", Kind::OptionalText),
+    ("date", Kind::OptionalDate),
+];
+
+/// JSON schema for the reply, as accepted by Ollama's `format` and the
+/// other backends' structured output options.
+pub fn schema() -> Value {
+    let mut properties = Map::new();
+    for (name, kind) in FIELDS {
+        let property = match kind {
+            Kind::Text => json!({ "type": "string" }),
+            Kind::OptionalText => json!({ "type": ["string", "null"] }),
+            Kind::OptionalDate => json!({ "type": ["string", "null"], "pattern": "^\\d{4}-\\d{2}-\\d{2}$" }),
+            Kind::Medium => json!({ "type": "string", "enum": MEDIUMS }),
+            Kind::NonEmptyList => json!({ "type": "array", "items": { "type": "string" }, "minItems": 1 }),
+        };
+        properties.insert(name.to_string(), property);
+    }
+    let required: Vec<&str> = FIELDS.iter().map(|(name, _)| *name).collect();
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required
+    })
+}
+
+/// Checks a reply against the schema, returning one message per problem.
+pub fn validate(content: &str) -> Result<Value, Vec<String>> {
+    let value: Value = serde_json::from_str(content).map_err(|e| vec![format!("the reply is not valid JSON ({})", e)])?;
+    let Some(object) = value.as_object() else {
+        return Err(vec!["the reply must be a JSON object".into()]);
+    };
+
+    let mut problems = Vec::new();
+    for (name, kind) in FIELDS {
+        let Some(field) = object.get(*name) else {
+            problems.push(format!("\"{}\" is missing", name));
+            continue;
+        };
+        let problem = match kind {
+            Kind::Text if !field.is_string() => Some("must be a string".to_string()),
+            Kind::OptionalText if !(field.is_string() || field.is_null()) => {
+                Some("must be a string or null".to_string())
+            }
+            Kind::OptionalDate => match field {
+                Value::Null => None,
+                Value::String(s) if chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").is_ok() => None,
+                _ => Some("must be a YYYY-MM-DD date or null".to_string()),
+            },
+            Kind::Medium if !field.as_str().is_some_and(|m| MEDIUMS.contains(&m)) => {
+                Some(format!("must be one of {}", MEDIUMS.join(", ")))
+            }
+            Kind::NonEmptyList => match field.as_array() {
+                Some(items) if items.iter().any(|i| i.as_str().is_some_and(|s| !s.trim().is_empty())) => None,
+                Some(_) => Some("must list at least one non-empty string".to_string()),
+                None => Some("must be an array of strings".to_string()),
+            },
+            _ => None,
+        };
+        if let Some(problem) = problem {
+            problems.push(format!("\"{}\" {}", name, problem));
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(value)
+    } else {
+        Err(problems)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::llm_service::ParsedInteraction;
+
+    fn camel_case(name: &str) -> String {
+        let mut out = String::new();
+        let mut upper = false;
+        for c in name.chars() {
+            if c == '_' {
+                upper = true;
+            } else if upper {
+                out.extend(c.to_uppercase());
+                upper = false;
+            } else {
+                out.push(c);
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn schema_covers_every_parsed_interaction_field() {
+        let parsed = ParsedInteraction {
+            person_names: vec![],
+            medium: String::new(),
+            location: String::new(),
+            their_location: None,
+            topics: vec![],
+            note: None,
+            date: None,
+        };
+        let fields = serde_json::to_value(parsed).unwrap();
+        let mut expected: Vec<String> = fields.as_object().unwrap().keys().map(|k| camel_case(k)).collect();
+        expected.sort();
+
+        let schema = schema();
+        let mut properties: Vec<String> = schema["properties"].as_object().unwrap().keys().cloned().collect();
+        properties.sort();
+        assert_eq!(properties, expected);
+        assert_eq!(schema["required"].as_array().unwrap().len(), expected.len());
+    }
+
+    #[test]
+    fn validation_lists_each_problem() {
+        let good = r#"{"personNames": ["Ann"], "medium": "Text", "location": "", "theirLocation": null,
+                       "topics": ["tea"], "note": null, "date": "2024-06-01"}"#;
+        assert!(validate(good).is_ok());
+
+        let problems = validate(
+            r#"{"personNames": [""], "medium": "lunch", "theirLocation": 3, "topics": "tea", "note": null, "date": "June 1"}"#,
+        )
+        .unwrap_err();
+        assert_eq!(
+            problems,
+            vec![
+                "\"personNames\" must list at least one non-empty string",
+                "\"medium\" must be one of InPerson, Text, PhoneCall, VideoCall, SocialMedia, Email",
+                "\"location\" is missing",
+                "\"theirLocation\" must be a string or null",
+                "\"topics\" must be an array of strings",
+                "\"date\" must be a YYYY-MM-DD date or null",
+            ]
+        );
+
+        assert!(validate("[1]").unwrap_err()[0].contains("object"));
+        assert!(validate("{nope").unwrap_err()[0].contains("not valid JSON"));
+    }
+}
@@ -0,0 +1,270 @@
+//! Recorded model-server exchanges, and a local stand-in server that replays
+//! them, so the AI paths can be tested without a model.
+//!
+//! Set `PRM_LLM_RECORD=<dir>` to save every request/response pair to `<dir>`
+//! as one JSON file per exchange. `StandInServer` serves such a directory.
+
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::ai::llm_backend::{LlmBackend, LlmConfig, Message};
+use crate::util;
+
+/// One request to a model server and its response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fixture {
+    /// Path of the generation endpoint, e.g. `/api/chat`.
+    pub endpoint: String,
+    /// The user's part of the request. Replay matches on this rather than
+    /// the whole body, since system prompts contain today's date.
+    pub input: String,
+    pub request: Value,
+    pub response: Value,
+}
+
+/// The user's part of a request body: the last user message for chat
+/// endpoints, the text after `Input:` in a llama.cpp prompt, or the text
+/// to embed.
+pub fn request_input(endpoint: &str, body: &Value) -> Option<String> {
+    if endpoint.ends_with("/completion") {
+        let prompt = body.get("prompt")?.as_str()?;
+        let start = prompt.rfind("\nInput: ")? + "\nInput: ".len();
+        let rest = &prompt[start..];
+        let end = rest.rfind("\nJSON:").unwrap_or(rest.len());
+        return Some(rest[..end].to_string());
+    }
+    if let Some(text) = ["prompt", "input", "content"].iter().find_map(|k| body.get(*k)?.as_str()) {
+        return Some(text.to_string());
+    }
+    body.get("messages")?
+        .as_array()?
+        .iter()
+        .rev()
+        .find(|m| m.get("role").and_then(Value::as_str) == Some("user"))?
+        .get("content")?
+        .as_str()
+        .map(|s| s.to_string())
+}
+
+/// Writes a fixture to `dir`, named after its input. Recording the same
+/// input again replaces the file.
+pub fn save(dir: &Path, fixture: &Fixture) -> Result<PathBuf, String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+    let slug: String = fixture
+        .input
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect::<String>()
+        .split('-')
+        .filter(|w| !w.is_empty())
+        .take(8)
+        .collect::<Vec<_>>()
+        .join("-");
+    let name = format!("{}-{}.json", slug, util::stable_hash(&format!("{}\n{}", fixture.endpoint, fixture.input)));
+    let path = dir.join(name);
+    let text = serde_json::to_string_pretty(fixture).map_err(|e| e.to_string())?;
+    fs::write(&path, text).map_err(|e| format!("Cannot write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// Every `*.json` fixture in `dir`, in file name order.
+pub fn load_dir(dir: &Path) -> Result<Vec<Fixture>, String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("Cannot read {}: {}", dir.display(), e))?;
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+    paths
+        .iter()
+        .map(|path| {
+            let text = fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+            serde_json::from_str(&text).map_err(|e| format!("Invalid fixture {}: {}", path.display(), e))
+        })
+        .collect()
+}
+
+/// Passes requests through to a real backend and saves each exchange.
+pub struct RecordingBackend {
+    inner: Box<dyn LlmBackend>,
+    dir: PathBuf,
+}
+
+impl RecordingBackend {
+    pub fn new(inner: Box<dyn LlmBackend>, dir: PathBuf) -> Self {
+        Self { inner, dir }
+    }
+}
+
+impl LlmBackend for RecordingBackend {
+    fn config(&self) -> &LlmConfig {
+        self.inner.config()
+    }
+
+    fn endpoint(&self) -> String {
+        self.inner.endpoint()
+    }
+
+    fn request_body(&self, messages: &[Message], schema: Option<&Value>) -> Value {
+        self.inner.request_body(messages, schema)
+    }
+
+    fn extract_content(&self, response: &Value) -> Option<String> {
+        self.inner.extract_content(response)
+    }
+
+    fn embedding_endpoint(&self) -> String {
+        self.inner.embedding_endpoint()
+    }
+
+    fn embedding_body(&self, text: &str) -> Value {
+        self.inner.embedding_body(text)
+    }
+
+    fn extract_embedding(&self, response: &Value) -> Option<Vec<f32>> {
+        self.inner.extract_embedding(response)
+    }
+
+    fn health_path(&self) -> &'static str {
+        self.inner.health_path()
+    }
+
+    fn setup_hint(&self) -> &'static str {
+        self.inner.setup_hint()
+    }
+
+    fn post(&self, path: &str, body: &Value) -> Result<Value, String> {
+        let response = self.inner.post(path, body)?;
+        let fixture = Fixture {
+            input: request_input(path, body).unwrap_or_default(),
+            endpoint: path.to_string(),
+            request: body.clone(),
+            response: response.clone(),
+        };
+        // A failed recording shouldn't fail the request itself
+        if let Err(e) = save(&self.dir, &fixture) {
+            eprintln!("Warning: {}", e);
+        }
+        Ok(response)
+    }
+
+    fn health_check(&self) -> Result<(), String> {
+        self.inner.health_check()
+    }
+}
+
+/// A local HTTP server that answers like a model server from fixtures.
+///
+/// A POST is answered with the response of the fixture whose endpoint and
+/// input match, or a 404. Any GET succeeds, so health checks pass. The
+/// server runs on a background thread until the process exits.
+pub struct StandInServer {
+    url: String,
+    received: Arc<Mutex<Vec<(String, Value)>>>,
+}
+
+impl StandInServer {
+    /// Listens on a free port on 127.0.0.1.
+    pub fn start(fixtures: Vec<Fixture>) -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let url = format!("http://{}", listener.local_addr()?);
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&received);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let _ = respond(stream, &fixtures, &log);
+            }
+        });
+        Ok(Self { url, received })
+    }
+
+    /// Base URL, e.g. `http://127.0.0.1:41234`.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Path and body of every POST received so far.
+    pub fn requests(&self) -> Vec<(String, Value)> {
+        self.received.lock().map(|r| r.clone()).unwrap_or_default()
+    }
+}
+
+fn respond(stream: TcpStream, fixtures: &[Fixture], log: &Mutex<Vec<(String, Value)>>) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+
+    let (status, reply) = if method == "GET" {
+        ("200 OK", json!({ "status": "ok" }))
+    } else {
+        let body: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+        let input = request_input(&path, &body);
+        if let Ok(mut log) = log.lock() {
+            log.push((path.clone(), body));
+        }
+        match fixtures.iter().find(|f| f.endpoint == path && Some(&f.input) == input.as_ref()) {
+            Some(fixture) => ("200 OK", fixture.response.clone()),
+            None => (
+                "404 Not Found",
+                json!({ "error": format!("no fixture for {} with input {:?}", path, input) }),
+            ),
+        }
+    };
+
+    let text = reply.to_string();
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        text.len(),
+        text
+    )?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_user_input_in_each_request_shape() {
+        let chat = json!({ "messages": [
+            { "role": "system", "content": "rules" },
+            { "role": "user", "content": "coffee with Ann" }
+        ] });
+        assert_eq!(request_input("/api/chat", &chat).as_deref(), Some("coffee with Ann"));
+        assert_eq!(request_input("/v1/chat/completions", &chat).as_deref(), Some("coffee with Ann"));
+
+        let completion = json!({ "prompt": "rules\n\nInput: coffee with Ann\nJSON:" });
+        assert_eq!(request_input("/completion", &completion).as_deref(), Some("coffee with Ann"));
+        let embedding = json!({ "model": "nomic-embed-text", "prompt": "stress at work" });
+        assert_eq!(request_input("/api/embeddings", &embedding).as_deref(), Some("stress at work"));
+        assert_eq!(request_input("/api/chat", &json!({})), None);
+    }
+}
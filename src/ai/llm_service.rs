@@ -1,8 +1,54 @@
-use std::env;
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
 
-const DEFAULT_OLLAMA_URL: &str = "http://localhost:11434";
-const DEFAULT_MODEL: &str = "llama3.2:3b";
+use crate::ai::interaction_schema;
+use crate::ai::llm_backend::{LlmBackend, LlmConfig, Message};
+
+/// How many times the model is asked to fix a reply that doesn't match the
+/// schema before giving up.
+const MAX_REPAIRS: usize = 2;
+
+/// Why a model couldn't produce a parsed interaction.
+#[derive(Debug, Error)]
+pub enum LlmError {
+    /// The server couldn't be reached, or answered with an error.
+    #[error("{0}")]
+    Backend(String),
+
+    /// Every reply, including the repair attempts, failed validation.
+    #[error("The model's reply didn't match the expected format after {attempts} attempt(s): {}", .problems.join("; "))]
+    InvalidResponse {
+        /// What was wrong with the last reply.
+        problems: Vec<String>,
+        /// The last reply, as received.
+        raw: String,
+        attempts: usize,
+    },
+}
+
+impl From<String> for LlmError {
+    fn from(message: String) -> Self {
+        LlmError::Backend(message)
+    }
+}
+
+/// The system prompt for `parse_interaction`. `{today}`, `{names}` and
+/// `{corrections}` are filled in by `interaction_prompt`.
+pub const INTERACTION_PROMPT: &str = r#"You extract interaction metadata from natural language descriptions.
+Today's date is {today}.
+Known contacts: [{names}]
+Respond with JSON only, no other text.
+JSON schema: { "personNames": ["..."], "medium": "InPerson|Text|PhoneCall|VideoCall|SocialMedia|Email", "location": "...", "theirLocation": null, "topics": ["..."], "note": null, "date": null }
+Rules:
+- personNames is an array of ALL people mentioned in the input. If multiple people are mentioned, include all of them. Use names exactly as written. Match to known contacts when possible. NEVER substitute different names.
+- If the medium is not mentioned, default to "InPerson"
+- location: use the most specific location from the input (e.g. "Charlton, MA" not just "home"). Include the full place name, city, or address as given.
+- theirLocation is only for remote interactions where their location differs; set to null for in-person
+- topics: ONLY include activities or subjects explicitly mentioned in the input. Do NOT infer or add topics that weren't stated. Be aware of slang (e.g. "gas" means great/amazing, not cooking).
+- note is for any additional context not captured in other fields; set to null if none
+- date: ONLY set to a "YYYY-MM-DD" string if the user explicitly mentions a specific date (e.g. "yesterday", "last Friday", "on March 5th"). Otherwise MUST be null. null means today.{corrections}"#;
 
 /// A past correction: what the AI parsed vs what the user actually saved.
 pub struct CorrectionExample {
@@ -30,109 +76,153 @@ fn default_medium() -> String {
     "InPerson".into()
 }
 
-fn ollama_url() -> String {
-    env::var("OLLAMA_HOST").unwrap_or_else(|_| DEFAULT_OLLAMA_URL.to_string())
+/// Check that the configured model server is running and reachable.
+pub fn check_backend() -> Result<(), String> {
+    LlmConfig::load()?.backend().health_check()
 }
 
-fn ollama_model() -> String {
-    env::var("PRM_MODEL").unwrap_or_else(|_| DEFAULT_MODEL.to_string())
+/// Where the configured model server is, for progress messages: "local" or
+/// its host.
+pub fn server_location() -> String {
+    LlmConfig::load().map(|c| c.location()).unwrap_or_else(|_| "local".to_string())
 }
 
-/// Check that Ollama is running and reachable.
-pub fn check_ollama() -> Result<(), String> {
-    let url = ollama_url();
-    ureq::get(&url)
-        .timeout(std::time::Duration::from_secs(3))
-        .call()
-        .map_err(|_| format!(
-            "Cannot connect to Ollama at {}. Is it running?\n  Install: https://ollama.ai\n  Start:   ollama serve",
-            url
-        ))?;
-    Ok(())
+pub fn parse_interaction(
+    input: &str,
+    known_names: &[String],
+    corrections: &[CorrectionExample],
+) -> Result<ParsedInteraction, LlmError> {
+    parse_interaction_with(&*LlmConfig::load()?.backend(), input, known_names, corrections)
 }
 
-pub fn parse_interaction(
+/// `parse_interaction` against a given backend, with the default prompt.
+pub fn parse_interaction_with(
+    backend: &dyn LlmBackend,
     input: &str,
     known_names: &[String],
     corrections: &[CorrectionExample],
-) -> Result<ParsedInteraction, String> {
-    let names_str = known_names.join(", ");
-    let today = chrono::Local::now().format("%Y-%m-%d");
-    let model = ollama_model();
-
-    let corrections_block = if corrections.is_empty() {
-        String::new()
-    } else {
-        let mut block = String::from("\nPast corrections to learn from (most recent first):\n");
-        for (i, c) in corrections.iter().enumerate() {
-            block.push_str(&format!(
-                "\nExample {}:\nInput: {}\nYou parsed: {}\nUser corrected to: {}\n",
-                i + 1,
-                c.original_text,
-                c.ai_output,
-                c.user_output,
-            ));
+) -> Result<ParsedInteraction, LlmError> {
+    let today = chrono::Local::now().date_naive();
+    parse_with_prompt(backend, &interaction_prompt(INTERACTION_PROMPT, today, known_names, corrections), input)
+}
+
+/// Fills in `{today}`, `{names}` and `{corrections}` in a prompt template
+/// such as `INTERACTION_PROMPT`.
+pub fn interaction_prompt(
+    template: &str,
+    today: NaiveDate,
+    known_names: &[String],
+    corrections: &[CorrectionExample],
+) -> String {
+    template
+        .replace("{today}", &today.format("%Y-%m-%d").to_string())
+        .replace("{names}", &known_names.join(", "))
+        .replace("{corrections}", &corrections_block(corrections))
+}
+
+/// Past corrections as worked examples for a system prompt, or nothing if
+/// there are none.
+pub fn corrections_block(corrections: &[CorrectionExample]) -> String {
+    if corrections.is_empty() {
+        return String::new();
+    }
+    let mut block = String::from("\nPast corrections to learn from (most relevant first):\n");
+    for (i, c) in corrections.iter().enumerate() {
+        block.push_str(&format!(
+            "\nExample {}:\nInput: {}\nYou parsed: {}\nUser corrected to: {}\n",
+            i + 1,
+            c.original_text,
+            c.ai_output,
+            c.user_output,
+        ));
+    }
+    block.push_str("\nApply these learnings when parsing the new input.\n");
+    block
+}
+
+/// Parses `input` with a ready-made system prompt. The reply is constrained
+/// by `interaction_schema::schema()` and validated; an invalid reply is sent
+/// back with the problems found, up to `MAX_REPAIRS` times.
+pub fn parse_with_prompt(
+    backend: &dyn LlmBackend,
+    system_prompt: &str,
+    input: &str,
+) -> Result<ParsedInteraction, LlmError> {
+    let messages = vec![Message::system(system_prompt), Message::user(input)];
+    converse_checked(backend, messages, &interaction_schema::schema(), |content| {
+        interaction_schema::validate(content)?;
+        parse_llm_json(content).map_err(|e| vec![e])
+    })
+}
+
+/// Sends `messages` with `schema` as the reply format and reads the reply
+/// with `check`, which lists what's wrong with it. A reply with problems is
+/// sent back to be fixed, up to `MAX_REPAIRS` times.
+pub fn converse_checked<T>(
+    backend: &dyn LlmBackend,
+    mut messages: Vec<Message>,
+    schema: &Value,
+    check: impl Fn(&str) -> Result<T, Vec<String>>,
+) -> Result<T, LlmError> {
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let content = backend.converse(&messages, Some(schema))?;
+        let problems = match check(&content) {
+            Ok(value) => return Ok(value),
+            Err(problems) => problems,
+        };
+        if attempts > MAX_REPAIRS {
+            return Err(LlmError::InvalidResponse { problems, raw: content, attempts });
         }
-        block.push_str("\nApply these learnings when parsing the new input.\n");
-        block
-    };
+        messages.push(Message::assistant(content));
+        messages.push(Message::user(repair_request(&problems)));
+    }
+}
 
-    let system_prompt = format!(
-        r#"You extract interaction metadata from natural language descriptions.
-Today's date is {today}.
-Known contacts: [{names_str}]
+/// Asks the model to fix its last reply.
+fn repair_request(problems: &[String]) -> String {
+    let mut request = String::from("Your reply didn't match the JSON schema:\n");
+    for problem in problems {
+        request.push_str(&format!("- {}\n", problem));
+    }
+    request.push_str("Reply again with the corrected JSON only.");
+    request
+}
+
+/// Summarize a conversation into a few short topics.
+pub fn summarize_topics(conversation: &str) -> Result<Vec<String>, String> {
+    let system_prompt = r#"You summarize a chat conversation into the main topics discussed.
 Respond with JSON only, no other text.
-JSON schema: {{ "personNames": ["..."], "medium": "InPerson|Text|PhoneCall|VideoCall|SocialMedia", "location": "...", "theirLocation": null, "topics": ["..."], "note": null, "date": null }}
+JSON schema: { "topics": ["..."] }
 Rules:
-- personNames is an array of ALL people mentioned in the input. If multiple people are mentioned, include all of them. Use names exactly as written. Match to known contacts when possible. NEVER substitute different names.
-- If the medium is not mentioned, default to "InPerson"
-- location: use the most specific location from the input (e.g. "Charlton, MA" not just "home"). Include the full place name, city, or address as given.
-- theirLocation is only for remote interactions where their location differs; set to null for in-person
-- topics: ONLY include activities or subjects explicitly mentioned in the input. Do NOT infer or add topics that weren't stated. Be aware of slang (e.g. "gas" means great/amazing, not cooking).
-- note is for any additional context not captured in other fields; set to null if none
-- date: ONLY set to a "YYYY-MM-DD" string if the user explicitly mentions a specific date (e.g. "yesterday", "last Friday", "on March 5th"). Otherwise MUST be null. null means today.{corrections_block}"#
-    );
-
-    let request_body = serde_json::json!({
-        "model": model,
-        "messages": [
-            { "role": "system", "content": system_prompt },
-            { "role": "user", "content": input }
-        ],
-        "format": "json",
-        "stream": false
-    });
-
-    let api_url = format!("{}/api/chat", ollama_url());
-
-    let response = ureq::post(&api_url)
-        .set("Content-Type", "application/json")
-        .timeout(std::time::Duration::from_secs(120))
-        .send_json(request_body)
-        .map_err(|e| match e {
-            ureq::Error::Status(code, resp) => {
-                let body = resp.into_string().unwrap_or_default();
-                format!("Ollama request failed (HTTP {}): {}", code, &body[..body.len().min(200)])
-            }
-            ureq::Error::Transport(t) => {
-                if t.to_string().contains("timed out") {
-                    "Request timed out. Local models can be slow on first run — try again.".into()
-                } else {
-                    format!("Could not connect to Ollama: {}", t)
-                }
-            }
-        })?;
+- 1 to 4 topics, each 1-4 words, lowercase
+- ONLY include subjects actually discussed. Ignore greetings, emojis and media placeholders."#;
 
-    let json: serde_json::Value = response
-        .into_json()
-        .map_err(|e| format!("Failed to parse Ollama response: {}", e))?;
+    let content = chat(system_prompt, conversation)?;
+    let json: serde_json::Value =
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse LLM response: {}", e))?;
 
-    let content = json
-        .pointer("/message/content")
-        .and_then(|v| v.as_str())
-        .ok_or("No content in Ollama response")?;
+    let topics: Vec<String> = json
+        .get("topics")
+        .and_then(|v| v.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|v| v.as_str().map(|s| s.trim().to_string()))
+                .filter(|s| !s.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if topics.is_empty() {
+        return Err("LLM did not extract any topics".into());
+    }
+    Ok(topics)
+}
 
-    parse_llm_json(content)
+/// Send a system prompt and user input to the configured backend, returning the JSON content.
+fn chat(system_prompt: &str, input: &str) -> Result<String, String> {
+    LlmConfig::load()?.backend().chat(system_prompt, input)
 }
 
 // Custom deserialization from the LLM JSON which uses camelCase
@@ -211,3 +301,35 @@ fn parse_llm_json(content: &str) -> Result<ParsedInteraction, String> {
         date,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_camel_case_llm_json() {
+        let parsed = parse_llm_json(
+            r#"{"personNames": ["Ann", ""], "medium": "PhoneCall", "location": "Home",
+                "theirLocation": "Lisbon", "topics": ["move"], "note": null, "date": "2024-06-01"}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.person_names, vec!["Ann"]);
+        assert_eq!(parsed.medium, "PhoneCall");
+        assert_eq!(parsed.their_location.as_deref(), Some("Lisbon"));
+        assert_eq!(parsed.note, None);
+        assert_eq!(parsed.date.as_deref(), Some("2024-06-01"));
+
+        // Older single-name shape, and defaults
+        let parsed = parse_llm_json(r#"{"personName": "Bo", "topics": ["tea"]}"#).unwrap();
+        assert_eq!(parsed.person_names, vec!["Bo"]);
+        assert_eq!(parsed.medium, "InPerson");
+        assert_eq!(parsed.location, "");
+    }
+
+    #[test]
+    fn rejects_llm_json_without_names_or_topics() {
+        assert!(parse_llm_json(r#"{"personNames": [], "topics": ["tea"]}"#).is_err());
+        assert!(parse_llm_json(r#"{"personNames": ["Ann"], "topics": []}"#).is_err());
+        assert!(parse_llm_json("not json").is_err());
+    }
+}
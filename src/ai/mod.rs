@@ -1,2 +1,11 @@
+pub mod correction_retrieval;
+pub mod eval;
+pub mod interaction_schema;
+pub mod llm_backend;
+pub mod llm_fixtures;
 pub mod llm_service;
+pub mod person_edit;
+pub mod person_extraction;
+pub mod person_summary;
+pub mod semantic_search;
 pub mod whisper_service;
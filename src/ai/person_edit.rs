@@ -0,0 +1,368 @@
+//! Turns a note about someone ("Alice moved to Berlin and got a new number
+//! 555-1234") into a proposed change to their record: new field values,
+//! contact entries to add and remove, and labels to add and remove.
+
+use chrono::NaiveDate;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::ai::llm_backend::{LlmBackend, Message};
+use crate::ai::llm_service::{self, CorrectionExample, LlmError};
+use crate::ai::person_extraction::{self, ContactDraft, Vocabulary};
+use crate::db::contact_repo;
+use crate::model::{ContactEntry, ContactType, ContactValue, Id, Person, User};
+use crate::queries::relationship_queries;
+
+/// The system prompt for `propose_edit`. `{record}`, `{labels}`,
+/// `{contact_types}` and `{corrections}` are filled in by `edit_prompt`.
+pub const EDIT_PROMPT: &str = r#"You turn a note about someone in the user's network into changes to their record.
+Current record:
+{record}
+Existing labels: [{labels}]
+Custom contact types: [{contact_types}]
+Respond with JSON only, no other text.
+JSON schema: { "fields": { "name": null, "nickname": null, "howWeMet": null, "birthday": null, "location": null, "notes": null }, "addContacts": [{ "type": "phone", "value": "...", "label": null }], "removeContacts": ["..."], "addLabels": ["..."], "removeLabels": ["..."] }
+Rules:
+- fields: the new value of each field the note changes, null for every field it doesn't. "" clears a field.
+- birthday: "YYYY-MM-DD".
+- notes: only for facts that fit no other field. Give the complete new notes, keeping the current ones.
+- addContacts: new phone numbers, email addresses or handles exactly as written. type is "phone", "email" or one of the custom contact types. label is e.g. "work" or "mobile" if stated, otherwise null.
+- removeContacts: values of current contacts, exactly as listed, that the note says are replaced or no longer used (e.g. their old number when they "got a new number"); [] otherwise.
+- addLabels: ONLY existing labels the note says now apply. removeLabels: ONLY their current labels the note says no longer apply.
+- NEVER change anything the note doesn't mention.{corrections}"#;
+
+/// A person's record as the model sees it.
+#[derive(Debug, Clone)]
+pub struct CurrentRecord {
+    pub person: Person,
+    pub contacts: Vec<(Id<ContactEntry>, ContactDraft)>,
+    pub labels: Vec<String>,
+}
+
+impl CurrentRecord {
+    pub fn load(conn: &Connection, owner_id: Id<User>, person: Person) -> Result<Self, String> {
+        let custom_types = contact_repo::find_custom_types(conn, owner_id).map_err(|e| e.to_string())?;
+        let contacts = contact_repo::find_by_person(conn, person.id)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|entry| {
+                let kind = match &entry.contact_type {
+                    ContactType::Phone => "phone".to_string(),
+                    ContactType::Email => "email".to_string(),
+                    ContactType::PhysicalAddress => "address".to_string(),
+                    ContactType::Custom { type_id } => custom_types
+                        .iter()
+                        .find(|t| t.id == *type_id)
+                        .map(|t| t.name.clone())
+                        .unwrap_or_else(|| "custom".into()),
+                };
+                let value = match &entry.value {
+                    ContactValue::StringValue { value } => value.clone(),
+                    ContactValue::AddressValue { value: a } => {
+                        format!("{}, {}, {} {}, {}", a.street, a.city, a.state, a.zip, a.country)
+                    }
+                };
+                (entry.id, ContactDraft { kind, value, label: entry.label })
+            })
+            .collect();
+        let labels = relationship_queries::labels_for(conn, person.id)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|l| l.name)
+            .collect();
+        Ok(CurrentRecord { person, contacts, labels })
+    }
+
+    /// The record as plain text for the prompt.
+    pub fn describe(&self) -> String {
+        let p = &self.person;
+        let mut text = String::new();
+        let fields = [
+            ("Name", Some(p.name.clone())),
+            ("Nickname", p.nickname.clone()),
+            ("How we met", p.how_we_met.clone()),
+            ("Birthday", p.birthday.map(|d| d.to_string())),
+            ("Location", p.location.clone()),
+            ("Notes", p.notes.clone()),
+        ];
+        for (name, value) in fields {
+            text.push_str(&format!("{}: {}\n", name, value.as_deref().unwrap_or("(none)")));
+        }
+        text.push_str("Contacts:");
+        if self.contacts.is_empty() {
+            text.push_str(" (none)");
+        }
+        for (_, contact) in &self.contacts {
+            text.push_str(&format!("\n- {}", contact.describe()));
+        }
+        text.push_str(&format!("\nLabels: [{}]", self.labels.join(", ")));
+        text
+    }
+
+    /// The current contact whose value is `value`, ignoring case and
+    /// surrounding spaces.
+    pub fn contact(&self, value: &str) -> Option<&(Id<ContactEntry>, ContactDraft)> {
+        self.contacts.iter().find(|(_, c)| c.value.trim().eq_ignore_ascii_case(value.trim()))
+    }
+}
+
+/// A proposed change to a person. Field values of `None` are left alone;
+/// `Some("")` clears the field.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PersonEdit {
+    pub fields: FieldChanges,
+    pub add_contacts: Vec<ContactDraft>,
+    /// Values of current contacts.
+    pub remove_contacts: Vec<String>,
+    pub add_labels: Vec<String>,
+    pub remove_labels: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldChanges {
+    pub name: Option<String>,
+    pub nickname: Option<String>,
+    pub how_we_met: Option<String>,
+    /// `YYYY-MM-DD`.
+    pub birthday: Option<String>,
+    pub location: Option<String>,
+    pub notes: Option<String>,
+}
+
+impl FieldChanges {
+    /// Each field's display name, current value and proposed change.
+    pub fn entries(&self, person: &Person) -> [(&'static str, Option<String>, &Option<String>); 6] {
+        [
+            ("Name", Some(person.name.clone()), &self.name),
+            ("Nickname", person.nickname.clone(), &self.nickname),
+            ("How we met", person.how_we_met.clone(), &self.how_we_met),
+            ("Birthday", person.birthday.map(|d| d.to_string()), &self.birthday),
+            ("Location", person.location.clone(), &self.location),
+            ("Notes", person.notes.clone(), &self.notes),
+        ]
+    }
+
+    /// Mutable access to a field's change by its position in `entries`.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut Option<String>> {
+        match index {
+            0 => Some(&mut self.name),
+            1 => Some(&mut self.nickname),
+            2 => Some(&mut self.how_we_met),
+            3 => Some(&mut self.birthday),
+            4 => Some(&mut self.location),
+            5 => Some(&mut self.notes),
+            _ => None,
+        }
+    }
+}
+
+impl PersonEdit {
+    pub fn is_empty(&self) -> bool {
+        *self == PersonEdit::default()
+    }
+
+    /// One line per change, e.g. `Location: Lisbon -> Berlin`.
+    pub fn describe(&self, current: &CurrentRecord) -> Vec<String> {
+        let mut lines = Vec::new();
+        for (name, before, change) in self.fields.entries(&current.person) {
+            if let Some(after) = change {
+                let before = before.unwrap_or_else(|| "(none)".into());
+                let after = if after.is_empty() { "(cleared)" } else { after.as_str() };
+                lines.push(format!("{}: {} -> {}", name, before, after));
+            }
+        }
+        for contact in &self.add_contacts {
+            lines.push(format!("Add contact: {}", contact.describe()));
+        }
+        for value in &self.remove_contacts {
+            let shown = current.contact(value).map(|(_, c)| c.describe()).unwrap_or_else(|| value.clone());
+            lines.push(format!("Remove contact: {}", shown));
+        }
+        for label in &self.add_labels {
+            lines.push(format!("Add label: {}", label));
+        }
+        for label in &self.remove_labels {
+            lines.push(format!("Remove label: {}", label));
+        }
+        lines
+    }
+
+    /// Drops changes that change nothing: fields set to their current value,
+    /// labels already there or already absent, repeats.
+    pub fn normalize(&mut self, current: &CurrentRecord, existing_labels: &[String]) {
+        let person = current.person.clone();
+        for (i, (_, before, _)) in self.fields.clone().entries(&person).into_iter().enumerate() {
+            let Some(change) = self.fields.get_mut(i) else { continue };
+            let unchanged = match (change.as_deref().map(str::trim), before.as_deref().map(str::trim)) {
+                (Some(""), None) => true,
+                (Some(after), Some(before)) => after == before,
+                _ => false,
+            };
+            if unchanged {
+                *change = None;
+            }
+        }
+        let available: Vec<String> =
+            existing_labels.iter().filter(|l| !current.labels.contains(l)).cloned().collect();
+        self.add_labels = person_extraction::known(&self.add_labels, &available);
+        self.remove_labels = person_extraction::known(&self.remove_labels, &current.labels);
+        let mut seen = Vec::new();
+        self.remove_contacts.retain(|v| {
+            let keep = !seen.contains(&v.trim().to_lowercase());
+            seen.push(v.trim().to_lowercase());
+            keep
+        });
+    }
+}
+
+/// Fills in `EDIT_PROMPT`.
+pub fn edit_prompt(current: &CurrentRecord, vocabulary: &Vocabulary, corrections: &[CorrectionExample]) -> String {
+    EDIT_PROMPT
+        .replace("{record}", &current.describe())
+        .replace("{labels}", &vocabulary.labels.join(", "))
+        .replace("{contact_types}", &vocabulary.contact_types.join(", "))
+        .replace("{corrections}", &llm_service::corrections_block(corrections))
+}
+
+/// JSON schema for the reply.
+pub fn schema() -> Value {
+    let optional = json!({ "type": ["string", "null"] });
+    let names = json!({ "type": "array", "items": { "type": "string" } });
+    json!({
+        "type": "object",
+        "properties": {
+            "fields": {
+                "type": "object",
+                "properties": {
+                    "name": optional,
+                    "nickname": optional,
+                    "howWeMet": optional,
+                    "birthday": { "type": ["string", "null"], "pattern": "^(\\d{4}-\\d{2}-\\d{2})?$" },
+                    "location": optional,
+                    "notes": optional
+                },
+                "required": ["name", "nickname", "howWeMet", "birthday", "location", "notes"]
+            },
+            "addContacts": { "type": "array", "items": person_extraction::contact_schema() },
+            "removeContacts": names,
+            "addLabels": names,
+            "removeLabels": names
+        },
+        "required": ["fields", "addContacts", "removeContacts", "addLabels", "removeLabels"]
+    })
+}
+
+/// Asks the model what `note` changes about the person in `current`.
+pub fn propose_edit(
+    backend: &dyn LlmBackend,
+    note: &str,
+    current: &CurrentRecord,
+    vocabulary: &Vocabulary,
+    corrections: &[CorrectionExample],
+) -> Result<PersonEdit, LlmError> {
+    let messages = vec![Message::system(edit_prompt(current, vocabulary, corrections)), Message::user(note)];
+    llm_service::converse_checked(backend, messages, &schema(), |content| check(content, current, vocabulary))
+}
+
+/// Reads a reply, listing what's wrong with it.
+fn check(content: &str, current: &CurrentRecord, vocabulary: &Vocabulary) -> Result<PersonEdit, Vec<String>> {
+    let mut edit: PersonEdit =
+        serde_json::from_str(content).map_err(|e| vec![format!("the reply doesn't match the schema ({})", e)])?;
+    let problems = problems(&edit, current, &vocabulary.contact_types);
+    if !problems.is_empty() {
+        return Err(problems);
+    }
+    edit.normalize(current, &vocabulary.labels);
+    Ok(edit)
+}
+
+/// What stops `edit` from being applied to `current`.
+pub fn problems(edit: &PersonEdit, current: &CurrentRecord, contact_types: &[String]) -> Vec<String> {
+    let mut problems = Vec::new();
+    if edit.fields.name.as_deref().is_some_and(|n| n.trim().is_empty()) {
+        problems.push("\"fields.name\" can't be cleared".to_string());
+    }
+    if let Some(birthday) = edit.fields.birthday.as_deref().filter(|b| !b.is_empty()) {
+        if NaiveDate::parse_from_str(birthday, "%Y-%m-%d").is_err() {
+            problems.push(format!("\"fields.birthday\" must be YYYY-MM-DD, not \"{}\"", birthday));
+        }
+    }
+    problems.extend(person_extraction::check_contacts(&edit.add_contacts, contact_types));
+    for value in &edit.remove_contacts {
+        if current.contact(value).is_none() {
+            problems.push(format!("\"removeContacts\" has \"{}\", which is not one of their current contacts", value));
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn current() -> CurrentRecord {
+        let mut person = Person::create("Alice".into());
+        person.location = Some("Lisbon".into());
+        CurrentRecord {
+            person,
+            contacts: vec![(Id::generate(), ContactDraft { kind: "phone".into(), value: "555-0000".into(), label: None })],
+            labels: vec!["friend".into()],
+        }
+    }
+
+    fn vocabulary() -> Vocabulary {
+        Vocabulary {
+            labels: vec!["friend".into(), "coworker".into()],
+            circles: vec![],
+            contact_types: vec![],
+        }
+    }
+
+    #[test]
+    fn check_drops_changes_that_change_nothing() {
+        let edit = check(
+            r#"{"fields": {"name": null, "nickname": "", "howWeMet": null, "birthday": null, "location": "Berlin", "notes": null},
+                "addContacts": [{"type": "phone", "value": "555-1234", "label": null}],
+                "removeContacts": ["555-0000", " 555-0000"], "addLabels": ["friend", "Coworker", "rival"],
+                "removeLabels": ["neighbor"]}"#,
+            &current(),
+            &vocabulary(),
+        )
+        .unwrap();
+        assert_eq!(edit.fields, FieldChanges { location: Some("Berlin".into()), ..FieldChanges::default() });
+        assert_eq!(edit.remove_contacts, vec!["555-0000"]);
+        assert_eq!(edit.add_labels, vec!["coworker"]);
+        assert!(edit.remove_labels.is_empty());
+        assert_eq!(
+            edit.describe(&current()),
+            vec![
+                "Location: Lisbon -> Berlin",
+                "Add contact: phone 555-1234",
+                "Remove contact: phone 555-0000",
+                "Add label: coworker",
+            ]
+        );
+    }
+
+    #[test]
+    fn check_rejects_unknown_contacts_and_bad_values() {
+        let problems = check(
+            r#"{"fields": {"name": " ", "nickname": null, "howWeMet": null, "birthday": "June 3", "location": null, "notes": null},
+                "addContacts": [], "removeContacts": ["555-9999"], "addLabels": [], "removeLabels": []}"#,
+            &current(),
+            &vocabulary(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            problems,
+            vec![
+                "\"fields.name\" can't be cleared",
+                "\"fields.birthday\" must be YYYY-MM-DD, not \"June 3\"",
+                "\"removeContacts\" has \"555-9999\", which is not one of their current contacts",
+            ]
+        );
+    }
+}
@@ -0,0 +1,272 @@
+//! Reads a new person out of a free-text description ("Met Priya Shah at
+//! RustConf, she lives in Portland, priya@x.com"): their details, contact
+//! entries, and which of the user's labels and circles fit.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::ai::llm_backend::{LlmBackend, Message};
+use crate::ai::llm_service::{self, LlmError};
+
+/// The system prompt for `extract_person`. `{labels}`, `{circles}` and
+/// `{contact_types}` are filled in with the user's own.
+pub const PERSON_PROMPT: &str = r#"You extract a new contact from a natural language description.
+Existing labels: [{labels}]
+Existing circles: [{circles}]
+Custom contact types: [{contact_types}]
+Respond with JSON only, no other text.
+JSON schema: { "name": "...", "nickname": null, "howWeMet": null, "birthday": null, "location": null, "notes": null, "contacts": [{ "type": "phone", "value": "...", "label": null }], "labels": ["..."], "circles": ["..."] }
+Rules:
+- name: the person's full name exactly as written.
+- nickname: only if one is given, otherwise null.
+- howWeMet: where or how the user met them (e.g. "RustConf"), otherwise null.
+- birthday: "YYYY-MM-DD", or "MM-DD" when no year is given; null if not stated.
+- location: the city or region where they live, otherwise null.
+- notes: other facts worth keeping, like their job and employer, as one short sentence; null if none.
+- contacts: phone numbers and email addresses exactly as written, and handles for the custom contact types. type is "phone", "email" or one of the custom contact types. label is e.g. "work" or "mobile" if stated, otherwise null.
+- labels and circles: ONLY names from the existing lists that clearly fit the description; [] if none do. NEVER make up new ones.
+- NEVER invent facts that aren't in the description."#;
+
+/// A person as proposed by the model, before it is saved.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractedPerson {
+    pub name: String,
+    pub nickname: Option<String>,
+    pub how_we_met: Option<String>,
+    /// `YYYY-MM-DD`, or `MM-DD` when the year wasn't given.
+    pub birthday: Option<String>,
+    pub location: Option<String>,
+    pub notes: Option<String>,
+    pub contacts: Vec<ContactDraft>,
+    /// Names of existing labels.
+    pub labels: Vec<String>,
+    /// Names of existing circles.
+    pub circles: Vec<String>,
+}
+
+/// A contact entry to add: `kind` is `phone`, `email` or the name of a
+/// custom contact type.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContactDraft {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub value: String,
+    pub label: Option<String>,
+}
+
+impl ContactDraft {
+    /// `phone 555-1234 (mobile)`.
+    pub fn describe(&self) -> String {
+        match self.label.as_deref().filter(|l| !l.trim().is_empty()) {
+            Some(label) => format!("{} {} ({})", self.kind, self.value, label),
+            None => format!("{} {}", self.kind, self.value),
+        }
+    }
+}
+
+/// A birthday as written by the model: a full date, or a day and month.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Birthday {
+    Date(NaiveDate),
+    /// Month and day.
+    NoYear(u32, u32),
+}
+
+impl Birthday {
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+            return Some(Birthday::Date(date));
+        }
+        let (month, day) = s.split_once('-')?;
+        let (month, day) = (month.parse().ok()?, day.parse().ok()?);
+        // 2000 was a leap year, so 29 February is accepted
+        NaiveDate::from_ymd_opt(2000, month, day).map(|_| Birthday::NoYear(month, day))
+    }
+}
+
+/// The user's own names the model may choose from.
+#[derive(Debug, Clone, Default)]
+pub struct Vocabulary {
+    pub labels: Vec<String>,
+    pub circles: Vec<String>,
+    pub contact_types: Vec<String>,
+}
+
+/// Fills in `PERSON_PROMPT`.
+pub fn person_prompt(vocabulary: &Vocabulary) -> String {
+    PERSON_PROMPT
+        .replace("{labels}", &vocabulary.labels.join(", "))
+        .replace("{circles}", &vocabulary.circles.join(", "))
+        .replace("{contact_types}", &vocabulary.contact_types.join(", "))
+}
+
+/// JSON schema for the reply.
+pub fn schema() -> Value {
+    let optional = json!({ "type": ["string", "null"] });
+    let names = json!({ "type": "array", "items": { "type": "string" } });
+    json!({
+        "type": "object",
+        "properties": {
+            "name": { "type": "string", "minLength": 1 },
+            "nickname": optional,
+            "howWeMet": optional,
+            "birthday": { "type": ["string", "null"], "pattern": "^(\\d{4}-)?\\d{2}-\\d{2}$" },
+            "location": optional,
+            "notes": optional,
+            "contacts": { "type": "array", "items": contact_schema() },
+            "labels": names,
+            "circles": names
+        },
+        "required": ["name", "nickname", "howWeMet", "birthday", "location", "notes", "contacts", "labels", "circles"]
+    })
+}
+
+/// JSON schema for one `ContactDraft`.
+pub fn contact_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "type": { "type": "string" },
+            "value": { "type": "string" },
+            "label": { "type": ["string", "null"] }
+        },
+        "required": ["type", "value", "label"]
+    })
+}
+
+/// Asks the model for the person described in `input`. Label and circle
+/// names it suggests that the user doesn't have are dropped.
+pub fn extract_person(
+    backend: &dyn LlmBackend,
+    input: &str,
+    vocabulary: &Vocabulary,
+) -> Result<ExtractedPerson, LlmError> {
+    let messages = vec![Message::system(person_prompt(vocabulary)), Message::user(input)];
+    llm_service::converse_checked(backend, messages, &schema(), |content| check(content, vocabulary))
+}
+
+/// Reads a reply, listing what's wrong with it.
+fn check(content: &str, vocabulary: &Vocabulary) -> Result<ExtractedPerson, Vec<String>> {
+    let mut person: ExtractedPerson =
+        serde_json::from_str(content).map_err(|e| vec![format!("the reply doesn't match the schema ({})", e)])?;
+
+    let mut problems = Vec::new();
+    if person.name.trim().is_empty() {
+        problems.push("\"name\" must not be empty".to_string());
+    }
+    if let Some(birthday) = person.birthday.as_deref().filter(|b| Birthday::parse(b).is_none()) {
+        problems.push(format!("\"birthday\" must be YYYY-MM-DD or MM-DD, not \"{}\"", birthday));
+    }
+    problems.extend(check_contacts(&person.contacts, &vocabulary.contact_types));
+    if !problems.is_empty() {
+        return Err(problems);
+    }
+
+    person.labels = known(&person.labels, &vocabulary.labels);
+    person.circles = known(&person.circles, &vocabulary.circles);
+    Ok(person)
+}
+
+/// Problems with contact entries: blank values, or types that aren't
+/// `phone`, `email` or one of `contact_types`.
+pub fn check_contacts(contacts: &[ContactDraft], contact_types: &[String]) -> Vec<String> {
+    let mut problems = Vec::new();
+    for (i, contact) in contacts.iter().enumerate() {
+        if canonical_kind(&contact.kind, contact_types).is_none() {
+            problems.push(format!(
+                "\"contacts\" item {} has type \"{}\"; use \"phone\", \"email\"{}",
+                i + 1,
+                contact.kind,
+                contact_types.iter().map(|t| format!(" or \"{}\"", t)).collect::<String>()
+            ));
+        }
+        if contact.value.trim().is_empty() {
+            problems.push(format!("\"contacts\" item {} has no value", i + 1));
+        }
+    }
+    problems
+}
+
+/// `kind` as spelled by the user: `phone`, `email` or a custom type's name.
+pub fn canonical_kind(kind: &str, contact_types: &[String]) -> Option<String> {
+    let kind = kind.trim();
+    ["phone", "email"]
+        .iter()
+        .map(|k| k.to_string())
+        .chain(contact_types.iter().cloned())
+        .find(|k| k.eq_ignore_ascii_case(kind))
+}
+
+/// The names in `suggested` that are in `existing`, spelled as there,
+/// without repeats.
+pub fn known(suggested: &[String], existing: &[String]) -> Vec<String> {
+    let mut result: Vec<String> = Vec::new();
+    for name in suggested {
+        if let Some(found) = existing.iter().find(|e| e.trim().eq_ignore_ascii_case(name.trim())) {
+            if !result.contains(found) {
+                result.push(found.clone());
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vocabulary() -> Vocabulary {
+        Vocabulary {
+            labels: vec!["friend".into(), "coworker".into()],
+            circles: vec!["Rust folks".into()],
+            contact_types: vec!["LinkedIn".into()],
+        }
+    }
+
+    #[test]
+    fn birthdays_may_leave_out_the_year() {
+        assert_eq!(Birthday::parse("1990-06-03"), NaiveDate::from_ymd_opt(1990, 6, 3).map(Birthday::Date));
+        assert_eq!(Birthday::parse("06-03"), Some(Birthday::NoYear(6, 3)));
+        assert_eq!(Birthday::parse("02-29"), Some(Birthday::NoYear(2, 29)));
+        assert_eq!(Birthday::parse("13-01"), None);
+        assert_eq!(Birthday::parse("June 3"), None);
+    }
+
+    #[test]
+    fn check_keeps_only_existing_labels_and_circles() {
+        let person = check(
+            r#"{"name": "Priya Shah", "nickname": null, "howWeMet": "RustConf", "birthday": "06-03",
+                "location": "Portland", "notes": null,
+                "contacts": [{"type": "Email", "value": "priya@x.com", "label": null},
+                             {"type": "linkedin", "value": "priyashah", "label": null}],
+                "labels": ["Coworker", "conference buddy", "coworker"], "circles": ["rust folks"]}"#,
+            &vocabulary(),
+        )
+        .unwrap();
+        assert_eq!(person.labels, vec!["coworker"]);
+        assert_eq!(person.circles, vec!["Rust folks"]);
+        assert_eq!(person.contacts.len(), 2);
+    }
+
+    #[test]
+    fn check_rejects_unknown_contact_types_and_bad_birthdays() {
+        let problems = check(
+            r#"{"name": "Priya", "nickname": null, "howWeMet": null, "birthday": "June 3", "location": null,
+                "notes": null, "contacts": [{"type": "fax", "value": " ", "label": null}],
+                "labels": [], "circles": []}"#,
+            &vocabulary(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            problems,
+            vec![
+                "\"birthday\" must be YYYY-MM-DD or MM-DD, not \"June 3\"",
+                "\"contacts\" item 1 has type \"fax\"; use \"phone\", \"email\" or \"LinkedIn\"",
+                "\"contacts\" item 1 has no value",
+            ]
+        );
+    }
+}
@@ -0,0 +1,255 @@
+//! A briefing on one person before getting in touch: a short narrative, the
+//! points worth remembering and the threads left open, each citing the
+//! interactions it comes from. Summaries are stored with a hash of the facts
+//! they were written from and only regenerated when those change.
+
+use std::collections::BTreeSet;
+
+use chrono::NaiveDate;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::ai::llm_backend::{LlmBackend, LlmConfig, Message};
+use crate::ai::llm_service::{self, LlmError};
+use crate::db::summary_repo::{self, StoredSummary};
+use crate::model::{Id, Person, User};
+use crate::queries::{circle_queries, interaction_queries, relationship_queries};
+use crate::util;
+
+/// How many of the latest interactions the model sees.
+pub const RECENT_INTERACTIONS: usize = 20;
+
+pub const SUMMARY_PROMPT: &str = r#"You brief the user before they get in touch with someone in their network, using only the facts given.
+Respond with JSON only, no other text.
+JSON schema: { "summary": "...", "points": [{ "text": "...", "dates": ["YYYY-MM-DD"] }], "openThreads": [{ "text": "...", "dates": ["YYYY-MM-DD"] }] }
+Rules:
+- summary: 2 or 3 sentences on who they are and how things stand with them.
+- points: what's worth remembering, most important first, at most 5.
+- openThreads: things still unresolved that are worth asking about (e.g. "She was interviewing at Acme in March 2024"), at most 3. Use [] if there are none.
+- dates: the dates of the interactions that support the point, exactly as listed. Use [] only for facts from the profile, labels or circles.
+- Refer to times by month and year, never relative to today.
+- NEVER invent people, events or dates that aren't in the facts."#;
+
+/// What the model writes about a person.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Briefing {
+    pub summary: String,
+    pub points: Vec<Point>,
+    pub open_threads: Vec<Point>,
+}
+
+/// One statement and the interaction dates it rests on; no dates means it
+/// comes from the profile.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Point {
+    pub text: String,
+    pub dates: Vec<NaiveDate>,
+}
+
+/// A briefing and when it was written.
+#[derive(Debug, Clone)]
+pub struct Summary {
+    pub briefing: Briefing,
+    /// UTC, `YYYY-MM-DD HH:MM:SS`.
+    pub created_at: String,
+    /// Read from the cache rather than asked for.
+    pub cached: bool,
+}
+
+/// The facts given to the model and the interaction dates they contain.
+#[derive(Debug, Clone)]
+pub struct Facts {
+    pub text: String,
+    pub dates: BTreeSet<NaiveDate>,
+}
+
+/// JSON schema for the reply.
+pub fn schema() -> Value {
+    let point = json!({
+        "type": "object",
+        "properties": {
+            "text": { "type": "string" },
+            "dates": { "type": "array", "items": { "type": "string", "pattern": "^\\d{4}-\\d{2}-\\d{2}$" } }
+        },
+        "required": ["text", "dates"]
+    });
+    json!({
+        "type": "object",
+        "properties": {
+            "summary": { "type": "string" },
+            "points": { "type": "array", "items": point },
+            "openThreads": { "type": "array", "items": point }
+        },
+        "required": ["summary", "points", "openThreads"]
+    })
+}
+
+/// The profile, labels, circles and latest interactions of `person`, as
+/// plain text for the model.
+pub fn facts(conn: &Connection, owner_id: Id<User>, person: &Person) -> Result<Facts, String> {
+    let labels = relationship_queries::labels_for(conn, person.id).map_err(|e| e.to_string())?;
+    let circles = circle_queries::circles_for_person(conn, owner_id, person.id).map_err(|e| e.to_string())?;
+    let interactions = interaction_queries::interactions_with(conn, person.id).map_err(|e| e.to_string())?;
+
+    let mut text = format!("Name: {}\n", person.name);
+    let fields = [
+        ("Nickname", person.nickname.clone()),
+        ("How we met", person.how_we_met.clone()),
+        ("Birthday", person.birthday.map(|d| d.to_string())),
+        ("Location", person.location.clone()),
+        ("Notes", person.notes.clone()),
+    ];
+    for (name, value) in fields {
+        if let Some(value) = value.filter(|v| !v.trim().is_empty()) {
+            text.push_str(&format!("{}: {}\n", name, value.trim()));
+        }
+    }
+    if !labels.is_empty() {
+        let names: Vec<&str> = labels.iter().map(|l| l.name.as_str()).collect();
+        text.push_str(&format!("Labels: {}\n", names.join(", ")));
+    }
+    if !circles.is_empty() {
+        let names: Vec<&str> = circles.iter().map(|c| c.name.as_str()).collect();
+        text.push_str(&format!("Circles: {}\n", names.join(", ")));
+    }
+
+    let mut dates = BTreeSet::new();
+    if interactions.is_empty() {
+        text.push_str("\nNo interactions logged.\n");
+    } else {
+        text.push_str(&format!("\nInteractions, newest first ({} in total):\n", interactions.len()));
+    }
+    for interaction in interactions.iter().take(RECENT_INTERACTIONS) {
+        dates.insert(interaction.date);
+        text.push_str(&format!(
+            "- {} ({}): {}",
+            interaction.date,
+            interaction.medium.display_name(),
+            interaction.topics.join(", ")
+        ));
+        if let Some(note) = interaction.note.as_deref().filter(|n| !n.trim().is_empty()) {
+            text.push_str(&format!(". Note: {}", note.trim()));
+        }
+        text.push('\n');
+    }
+    Ok(Facts { text, dates })
+}
+
+/// The stored briefing on `person`, if the facts haven't changed since it
+/// was written with `config`'s model. Needs no model server.
+pub fn stored(conn: &Connection, owner_id: Id<User>, config: &LlmConfig, person: &Person) -> Result<Option<Summary>, String> {
+    let facts = facts(conn, owner_id, person)?;
+    let source_hash = source_hash(config, &facts);
+    let stored = summary_repo::find_by_person(conn, person.id).map_err(|e| e.to_string())?;
+    Ok(stored.filter(|s| s.source_hash == source_hash).and_then(|stored| {
+        let briefing = serde_json::from_str(&stored.summary).ok()?;
+        Some(Summary { briefing, created_at: stored.created_at, cached: true })
+    }))
+}
+
+/// The briefing on `person`: the stored one if the facts haven't changed
+/// since it was written and `refresh` is false, otherwise a new one, which
+/// replaces it.
+pub fn summarize(
+    conn: &Connection,
+    owner_id: Id<User>,
+    backend: &dyn LlmBackend,
+    person: &Person,
+    refresh: bool,
+) -> Result<Summary, LlmError> {
+    if !refresh {
+        if let Some(summary) = stored(conn, owner_id, backend.config(), person)? {
+            return Ok(summary);
+        }
+    }
+
+    let facts = facts(conn, owner_id, person)?;
+    let source_hash = source_hash(backend.config(), &facts);
+    let messages = vec![Message::system(SUMMARY_PROMPT), Message::user(facts.text.clone())];
+    let briefing = llm_service::converse_checked(backend, messages, &schema(), |content| check(content, &facts.dates))?;
+    let stored = StoredSummary {
+        source_hash,
+        summary: serde_json::to_string(&briefing).map_err(|e| e.to_string())?,
+        created_at: chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+    };
+    summary_repo::upsert(conn, person.id, &stored).map_err(|e| e.to_string())?;
+    Ok(Summary { briefing, created_at: stored.created_at, cached: false })
+}
+
+fn source_hash(config: &LlmConfig, facts: &Facts) -> String {
+    util::stable_hash(&format!("{}\n{}\n{}", config.model, SUMMARY_PROMPT, facts.text))
+}
+
+/// Reads a reply, listing what's wrong with it: blank text, or dates that
+/// aren't among the interactions given.
+fn check(content: &str, known_dates: &BTreeSet<NaiveDate>) -> Result<Briefing, Vec<String>> {
+    let briefing: Briefing =
+        serde_json::from_str(content).map_err(|e| vec![format!("the reply doesn't match the schema ({})", e)])?;
+
+    let mut problems = Vec::new();
+    if briefing.summary.trim().is_empty() {
+        problems.push("\"summary\" must not be empty".to_string());
+    }
+    let sections = [("points", &briefing.points), ("openThreads", &briefing.open_threads)];
+    for (section, points) in sections {
+        for (i, point) in points.iter().enumerate() {
+            if point.text.trim().is_empty() {
+                problems.push(format!("\"{}\" item {} has no text", section, i + 1));
+            }
+            for date in point.dates.iter().filter(|d| !known_dates.contains(d)) {
+                problems.push(format!(
+                    "\"{}\" item {} cites {}, which is not the date of any interaction listed",
+                    section,
+                    i + 1,
+                    date
+                ));
+            }
+        }
+    }
+    if problems.is_empty() {
+        Ok(briefing)
+    } else {
+        Err(problems)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dates() -> BTreeSet<NaiveDate> {
+        [NaiveDate::from_ymd_opt(2024, 3, 2).unwrap()].into_iter().collect()
+    }
+
+    #[test]
+    fn check_accepts_cited_interaction_dates() {
+        let briefing = check(
+            r#"{"summary": "A friend from school.", "points": [{"text": "Moved to Berlin", "dates": ["2024-03-02"]}],
+                "openThreads": [{"text": "Knows Rust", "dates": []}]}"#,
+            &dates(),
+        )
+        .unwrap();
+        assert_eq!(briefing.points[0].dates, vec![NaiveDate::from_ymd_opt(2024, 3, 2).unwrap()]);
+        assert!(briefing.open_threads[0].dates.is_empty());
+    }
+
+    #[test]
+    fn check_rejects_invented_dates_and_blank_text() {
+        let problems = check(
+            r#"{"summary": " ", "points": [{"text": "", "dates": ["2024-03-09"]}], "openThreads": []}"#,
+            &dates(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            problems,
+            vec![
+                "\"summary\" must not be empty",
+                "\"points\" item 1 has no text",
+                "\"points\" item 1 cites 2024-03-09, which is not the date of any interaction listed",
+            ]
+        );
+        assert!(check(r#"{"summary": "x", "points": []}"#, &dates()).is_err());
+    }
+}
@@ -0,0 +1,252 @@
+//! Search by meaning: interaction topics and notes, and people's notes, are
+//! embedded through the model server and compared to a question by cosine
+//! similarity. Embeddings are stored with a hash of their text, so only new
+//! and edited entries are sent to the server again.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::NaiveDate;
+use rusqlite::Connection;
+use uuid::Uuid;
+
+use crate::ai::llm_backend::LlmBackend;
+use crate::db::embedding_repo::{self, SourceType, StoredEmbedding};
+use crate::model::{Id, Interaction, Person, User};
+use crate::queries::{interaction_queries, person_queries};
+use crate::util;
+
+/// Something that can be searched, with the text that gets embedded.
+#[derive(Debug, Clone)]
+enum Source {
+    /// One interaction per person it was logged for.
+    Interaction { person: Person, interaction: Interaction },
+    PersonNotes(Person),
+}
+
+impl Source {
+    fn key(&self) -> (SourceType, Uuid) {
+        match self {
+            Source::Interaction { interaction, .. } => (SourceType::Interaction, interaction.id.value),
+            Source::PersonNotes(person) => (SourceType::PersonNotes, person.id.value),
+        }
+    }
+
+    fn text(&self) -> String {
+        match self {
+            Source::Interaction { interaction, .. } => {
+                let mut text = format!("Talked about: {}.", interaction.topics.join(", "));
+                if let Some(note) = interaction.note.as_deref().filter(|n| !n.trim().is_empty()) {
+                    text.push_str(&format!(" {}", note.trim()));
+                }
+                text
+            }
+            Source::PersonNotes(person) => person.notes.clone().unwrap_or_default(),
+        }
+    }
+}
+
+/// A match for a question, best first from `search`.
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    /// Cosine similarity, from -1 to 1.
+    pub score: f32,
+    pub hit: Hit,
+}
+
+#[derive(Debug, Clone)]
+pub enum Hit {
+    /// An interaction; one logged with several people appears once.
+    Interaction { names: Vec<String>, interaction: Interaction },
+    PersonNotes { name: String, notes: String },
+}
+
+/// What `refresh` did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RefreshSummary {
+    /// Sent to the server.
+    pub embedded: usize,
+    /// Copied from an entry with the same text, like the other people's
+    /// copies of a group interaction.
+    pub reused: usize,
+    /// Dropped because their entry is gone.
+    pub removed: usize,
+}
+
+/// Brings the stored embeddings up to date, embedding only entries that are
+/// new or whose text or embedding model changed. `progress` is called with
+/// (done, total) before each request to the server.
+pub fn refresh(
+    conn: &Connection,
+    owner_id: Id<User>,
+    backend: &dyn LlmBackend,
+    mut progress: impl FnMut(usize, usize),
+) -> Result<RefreshSummary, String> {
+    let sources = sources(conn, owner_id)?;
+    let stored = embedding_repo::find_by_owner(conn, owner_id).map_err(|e| e.to_string())?;
+    let model = &backend.config().embed_model;
+
+    let mut by_hash: HashMap<String, Vec<f32>> =
+        stored.iter().map(|e| (e.text_hash.clone(), e.vector.clone())).collect();
+    let current: HashMap<(SourceType, Uuid), &str> =
+        stored.iter().map(|e| ((e.source_type, e.source_id), e.text_hash.as_str())).collect();
+
+    let stale: Vec<(&Source, String, String)> = sources
+        .iter()
+        .filter_map(|source| {
+            let text = source.text();
+            let hash = text_hash(model, &text);
+            (current.get(&source.key()) != Some(&hash.as_str())).then_some((source, text, hash))
+        })
+        .collect();
+    let to_send: HashSet<&str> = stale
+        .iter()
+        .filter(|(_, _, hash)| !by_hash.contains_key(hash))
+        .map(|(_, _, hash)| hash.as_str())
+        .collect();
+
+    let mut summary = RefreshSummary::default();
+    for (source, text, hash) in &stale {
+        let vector = match by_hash.get(hash) {
+            Some(vector) => {
+                summary.reused += 1;
+                vector.clone()
+            }
+            None => {
+                progress(summary.embedded, to_send.len());
+                let vector = backend.embed(text)?;
+                summary.embedded += 1;
+                by_hash.insert(hash.clone(), vector.clone());
+                vector
+            }
+        };
+        let (source_type, source_id) = source.key();
+        let embedding = StoredEmbedding { source_type, source_id, text_hash: hash.clone(), vector };
+        embedding_repo::upsert(conn, owner_id, &embedding).map_err(|e| e.to_string())?;
+    }
+
+    let live: HashSet<(SourceType, Uuid)> = sources.iter().map(Source::key).collect();
+    for e in stored.iter().filter(|e| !live.contains(&(e.source_type, e.source_id))) {
+        embedding_repo::delete(conn, e.source_type, e.source_id).map_err(|e| e.to_string())?;
+        summary.removed += 1;
+    }
+    Ok(summary)
+}
+
+/// The `limit` entries closest in meaning to `question`. Call `refresh`
+/// first so recent entries are included.
+pub fn search(
+    conn: &Connection,
+    owner_id: Id<User>,
+    backend: &dyn LlmBackend,
+    question: &str,
+    limit: usize,
+) -> Result<Vec<SearchResult>, String> {
+    let query = backend.embed(question)?;
+    let stored: HashMap<(SourceType, Uuid), Vec<f32>> = embedding_repo::find_by_owner(conn, owner_id)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|e| ((e.source_type, e.source_id), e.vector))
+        .collect();
+
+    let mut scored: Vec<(f32, Source)> = sources(conn, owner_id)?
+        .into_iter()
+        .filter_map(|source| {
+            let vector = stored.get(&source.key())?;
+            Some((cosine(&query, vector)?, source))
+        })
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    // Group interactions: one entry per date and text, naming everyone
+    let mut results: Vec<SearchResult> = Vec::new();
+    let mut groups: HashMap<(NaiveDate, String), usize> = HashMap::new();
+    for (score, source) in scored {
+        let text = source.text();
+        match source {
+            Source::Interaction { person, interaction } => {
+                let key = (interaction.date, text);
+                if let Some(&i) = groups.get(&key) {
+                    if let Hit::Interaction { names, .. } = &mut results[i].hit {
+                        names.push(person.name);
+                    }
+                    continue;
+                }
+                if results.len() == limit {
+                    continue;
+                }
+                groups.insert(key, results.len());
+                results.push(SearchResult { score, hit: Hit::Interaction { names: vec![person.name], interaction } });
+            }
+            Source::PersonNotes(person) => {
+                if results.len() < limit {
+                    results.push(SearchResult { score, hit: Hit::PersonNotes { name: person.name, notes: text } });
+                }
+            }
+        }
+    }
+    Ok(results)
+}
+
+/// Cosine similarity, or `None` if the vectors can't be compared (different
+/// lengths, e.g. after switching embedding models, or all zeros).
+pub fn cosine(a: &[f32], b: &[f32]) -> Option<f32> {
+    if a.len() != b.len() || a.is_empty() {
+        return None;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let denominator = norm(a) * norm(b);
+    (denominator > 0.0).then(|| dot / denominator)
+}
+
+/// Every searchable entry of active people: interactions, and notes that
+/// aren't blank.
+fn sources(conn: &Connection, owner_id: Id<User>) -> Result<Vec<Source>, String> {
+    let people: HashMap<Id<Person>, Person> = person_queries::active_people(conn, owner_id)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|p| (p.id, p))
+        .collect();
+    let interactions = interaction_queries::all_interactions(conn, owner_id).map_err(|e| e.to_string())?;
+
+    let mut sources: Vec<Source> = interactions
+        .into_iter()
+        .filter_map(|(person_id, interaction)| {
+            let person = people.get(&person_id)?.clone();
+            Some(Source::Interaction { person, interaction })
+        })
+        .collect();
+    let mut with_notes: Vec<&Person> = people
+        .values()
+        .filter(|p| p.notes.as_deref().is_some_and(|n| !n.trim().is_empty()))
+        .collect();
+    with_notes.sort_by(|a, b| a.name.cmp(&b.name));
+    sources.extend(with_notes.into_iter().cloned().map(Source::PersonNotes));
+    Ok(sources)
+}
+
+/// Hash of the model and text, to tell when an embedding is stale.
+fn text_hash(model: &str, text: &str) -> String {
+    util::stable_hash(&format!("{}\n{}", model, text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cosine_compares_direction_only() {
+        assert_eq!(cosine(&[1.0, 0.0], &[3.0, 0.0]), Some(1.0));
+        assert_eq!(cosine(&[1.0, 0.0], &[0.0, 2.0]), Some(0.0));
+        assert!((cosine(&[1.0, 1.0], &[1.0, 0.0]).unwrap() - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+        assert_eq!(cosine(&[1.0, 0.0], &[1.0, 0.0, 0.0]), None);
+        assert_eq!(cosine(&[0.0, 0.0], &[1.0, 0.0]), None);
+    }
+
+    #[test]
+    fn text_hash_depends_on_model_and_text() {
+        assert_eq!(text_hash("m", "tea"), text_hash("m", "tea"));
+        assert_ne!(text_hash("m", "tea"), text_hash("m", "coffee"));
+        assert_ne!(text_hash("m", "tea"), text_hash("n", "tea"));
+    }
+}
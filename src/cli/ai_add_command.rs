@@ -0,0 +1,370 @@
+use chrono::NaiveDate;
+
+use crate::ai::llm_backend::LlmConfig;
+use crate::ai::person_extraction::{self, Birthday, ContactDraft, ExtractedPerson, Vocabulary};
+use crate::cli::ai_log_command::print_parse_error;
+use crate::cli::context::CLIContext;
+use crate::db::contact_repo;
+use crate::error::{PrmError, PrmResult};
+use crate::matching;
+use crate::model::*;
+use crate::ops::*;
+use crate::queries::*;
+
+/// `ai-add <description>`
+pub fn ai_add(ctx: &CLIContext, args: &str) {
+    if args.trim().is_empty() {
+        println!("Usage: ai-add <natural language description>");
+        println!("Example: ai-add Met Priya Shah at RustConf, she's a staff eng at Fastly, lives in Portland, priya@x.com");
+        return;
+    }
+
+    let backend = match LlmConfig::load() {
+        Ok(config) => config.backend(),
+        Err(e) => return println!("Error: {}", e),
+    };
+    if let Err(err) = backend.health_check() {
+        println!("Error: {}", err);
+        return;
+    }
+
+    let vocabulary = vocabulary(ctx);
+    println!("Parsing with AI ({})...", backend.config().location());
+    let proposed = match person_extraction::extract_person(&*backend, args.trim(), &vocabulary) {
+        Ok(p) => p,
+        Err(e) => return print_parse_error(&e),
+    };
+
+    let Some(person) = review(ctx, proposed, &vocabulary) else { return };
+    if !confirm_if_known(ctx, &person.name) {
+        println!("Discarded.");
+        return;
+    }
+    match save(ctx, &person) {
+        Ok(saved) => {
+            println!("Added {}.", saved.name);
+            if !person.contacts.is_empty() {
+                println!("  Contacts: {}", describe_contacts(&person.contacts));
+            }
+            if !person.labels.is_empty() {
+                println!("  Labels: {}", person.labels.join(", "));
+            }
+            if !person.circles.is_empty() {
+                println!("  Circles: {}", person.circles.join(", "));
+            }
+        }
+        Err(e) => ctx.print_error(&e),
+    }
+}
+
+/// The labels, circles and custom contact types the model may use.
+pub fn vocabulary(ctx: &CLIContext) -> Vocabulary {
+    Vocabulary {
+        labels: relationship_queries::active_labels(&ctx.conn, ctx.owner_id())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|l| l.name)
+            .collect(),
+        circles: circle_queries::active_circles(&ctx.conn, ctx.owner_id())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|c| c.name)
+            .collect(),
+        contact_types: contact_repo::find_custom_types(&ctx.conn, ctx.owner_id())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|t| t.name)
+            .collect(),
+    }
+}
+
+/// Shows the proposed person and lets the user edit fields until they save
+/// (returning the final version) or discard it.
+fn review(ctx: &CLIContext, initial: ExtractedPerson, vocabulary: &Vocabulary) -> Option<ExtractedPerson> {
+    let mut current = initial;
+    loop {
+        display(&current);
+        println!();
+        println!("Actions: (s)ave, (e)dit a field, (d)iscard");
+
+        let choice = ctx.prompt("Choice: ")?.to_lowercase();
+        match choice.as_str() {
+            "s" | "save" => {
+                if complete_birthday(ctx, &mut current) {
+                    return Some(current);
+                }
+            }
+            "e" | "edit" => edit_field(ctx, &mut current, vocabulary),
+            "d" | "discard" => {
+                println!("Discarded.");
+                return None;
+            }
+            _ => println!("Invalid choice. Enter 's' to save, 'e' to edit, or 'd' to discard."),
+        }
+    }
+}
+
+/// Birthdays are stored as full dates, so one given without a year needs the
+/// year before saving, or is left out if the user says so. Returns false to
+/// go back to the review.
+fn complete_birthday(ctx: &CLIContext, person: &mut ExtractedPerson) -> bool {
+    let Some(Birthday::NoYear(month, day)) = person.birthday.as_deref().and_then(Birthday::parse) else {
+        return true;
+    };
+    let input = ctx
+        .prompt(&format!(
+            "The birthday {} has no year. Year (e.g. 1990), '-' to save without a birthday, or Enter to go back: ",
+            describe_month_day(month, day)
+        ))
+        .unwrap_or_default();
+    match input.as_str() {
+        "" => false,
+        "-" => {
+            person.birthday = None;
+            true
+        }
+        _ => match input.parse().ok().and_then(|year| NaiveDate::from_ymd_opt(year, month, day)) {
+            Some(date) => {
+                person.birthday = Some(date.to_string());
+                true
+            }
+            None => {
+                println!("{} is not a year with {} in it.", input, describe_month_day(month, day));
+                false
+            }
+        },
+    }
+}
+
+fn display(person: &ExtractedPerson) {
+    let or_none = |value: &Option<String>| value.clone().unwrap_or_else(|| "(none)".into());
+    println!();
+    println!("Proposed person:");
+    println!("  1. Name:       {}", person.name);
+    println!("  2. Nickname:   {}", or_none(&person.nickname));
+    println!("  3. How we met: {}", or_none(&person.how_we_met));
+    println!("  4. Birthday:   {}", describe_birthday(person.birthday.as_deref()));
+    println!("  5. Location:   {}", or_none(&person.location));
+    println!("  6. Notes:      {}", or_none(&person.notes));
+    println!("  7. Contacts:   {}", describe_contacts(&person.contacts));
+    println!("  8. Labels:     {}", describe_names(&person.labels));
+    println!("  9. Circles:    {}", describe_names(&person.circles));
+}
+
+fn edit_field(ctx: &CLIContext, person: &mut ExtractedPerson, vocabulary: &Vocabulary) {
+    let Some(field) = ctx.prompt("Field (1-9): ") else { return };
+    match field.as_str() {
+        "1" => {
+            let input = ctx.prompt(&format!("Name [{}]: ", person.name)).unwrap_or_default();
+            if !input.is_empty() {
+                person.name = input;
+            }
+        }
+        "2" => edit_optional(ctx, "Nickname", &mut person.nickname),
+        "3" => edit_optional(ctx, "How we met", &mut person.how_we_met),
+        "4" => {
+            let current = person.birthday.as_deref().unwrap_or("(none)");
+            let input = ctx
+                .prompt(&format!("Birthday (YYYY-MM-DD or MM-DD, '-' to clear) [{}]: ", current))
+                .unwrap_or_default();
+            match input.as_str() {
+                "" => {}
+                "-" => person.birthday = None,
+                s if Birthday::parse(s).is_some() => person.birthday = Some(s.to_string()),
+                _ => println!("Invalid date, keeping current."),
+            }
+        }
+        "5" => edit_optional(ctx, "Location", &mut person.location),
+        "6" => edit_optional(ctx, "Notes", &mut person.notes),
+        "7" => edit_contacts(ctx, &mut person.contacts, &vocabulary.contact_types),
+        "8" => edit_names(ctx, "Labels", &mut person.labels, &vocabulary.labels),
+        "9" => edit_names(ctx, "Circles", &mut person.circles, &vocabulary.circles),
+        _ => println!("Invalid field number."),
+    }
+}
+
+/// Prompts for a new value: Enter keeps the current one, `-` clears it.
+pub fn edit_optional(ctx: &CLIContext, name: &str, value: &mut Option<String>) {
+    let current = value.as_deref().unwrap_or("(none)");
+    let input = ctx.prompt(&format!("{} ('-' to clear) [{}]: ", name, current)).unwrap_or_default();
+    match input.as_str() {
+        "" => {}
+        "-" => *value = None,
+        _ => *value = Some(input),
+    }
+}
+
+/// Removes contacts by number and adds ones typed as `phone 555-1234`,
+/// `email a@x.com (work)` or `<custom type> <value>`, until Enter.
+pub fn edit_contacts(ctx: &CLIContext, contacts: &mut Vec<ContactDraft>, contact_types: &[String]) {
+    loop {
+        for (i, contact) in contacts.iter().enumerate() {
+            println!("  {}. {}", i + 1, contact.describe());
+        }
+        let input = match ctx.prompt("Remove (number), add (e.g. 'phone 555-1234 (mobile)'), or Enter when done: ") {
+            Some(s) if !s.is_empty() => s,
+            _ => return,
+        };
+        if let Ok(n) = input.parse::<usize>() {
+            if n > 0 && n <= contacts.len() {
+                contacts.remove(n - 1);
+            } else {
+                println!("No contact {}.", n);
+            }
+            continue;
+        }
+        match parse_contact(&input, contact_types) {
+            Some(contact) => contacts.push(contact),
+            None => println!(
+                "Type phone, email{} followed by the value.",
+                contact_types.iter().map(|t| format!(", {}", t)).collect::<String>()
+            ),
+        }
+    }
+}
+
+/// `phone 555-1234 (mobile)`: a kind, a value and an optional label.
+pub fn parse_contact(input: &str, contact_types: &[String]) -> Option<ContactDraft> {
+    let (kind, rest) = input.trim().split_once(char::is_whitespace)?;
+    let kind = person_extraction::canonical_kind(kind, contact_types)?;
+    let rest = rest.trim();
+    let (value, label) = match rest.strip_suffix(')').and_then(|r| r.rsplit_once('(')) {
+        Some((value, label)) => (value.trim(), Some(label.trim().to_string()).filter(|l| !l.is_empty())),
+        None => (rest, None),
+    };
+    (!value.is_empty()).then(|| ContactDraft { kind, value: value.to_string(), label })
+}
+
+/// Prompts for a comma-separated list of names from `existing`.
+pub fn edit_names(ctx: &CLIContext, what: &str, names: &mut Vec<String>, existing: &[String]) {
+    if existing.is_empty() {
+        println!("No {} available.", what.to_lowercase());
+        return;
+    }
+    println!("Available: {}", existing.join(", "));
+    let input = ctx
+        .prompt(&format!("{} (comma-separated, '-' for none) [{}]: ", what, describe_names(names)))
+        .unwrap_or_default();
+    match input.as_str() {
+        "" => {}
+        "-" => names.clear(),
+        _ => {
+            let typed: Vec<String> = input.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect();
+            let found = person_extraction::known(&typed, existing);
+            for name in typed.iter().filter(|t| !found.iter().any(|f| f.eq_ignore_ascii_case(t))) {
+                println!("Unknown: {} (skipped)", name);
+            }
+            *names = found;
+        }
+    }
+}
+
+/// Asks before adding a second person with a name already in use.
+fn confirm_if_known(ctx: &CLIContext, name: &str) -> bool {
+    let same: Vec<Person> = ctx
+        .person_matches(name)
+        .into_iter()
+        .filter(|p| matching::fold(&p.name) == matching::fold(name.trim()))
+        .collect();
+    if same.is_empty() {
+        return true;
+    }
+    let answer = ctx
+        .prompt(&format!("You already have {}. Add another person with this name? (y/n): ", same[0].name))
+        .unwrap_or_default();
+    answer.eq_ignore_ascii_case("y") || answer.eq_ignore_ascii_case("yes")
+}
+
+/// Adds the person with their contacts, labels and circles, all or nothing.
+fn save(ctx: &CLIContext, person: &ExtractedPerson) -> PrmResult<Person> {
+    let birthday = match person.birthday.as_deref().and_then(Birthday::parse) {
+        Some(Birthday::Date(date)) => Some(date),
+        _ => None,
+    };
+
+    let tx = ctx.conn.unchecked_transaction()?;
+    let saved = person_ops::add_person(
+        &tx,
+        ctx.owner_id(),
+        &person.name,
+        person.nickname.as_deref(),
+        person.how_we_met.as_deref(),
+        birthday,
+        person.notes.as_deref(),
+        person.location.as_deref(),
+    )?;
+    for contact in &person.contacts {
+        add_contact(&tx, ctx.owner_id(), saved.id, contact)?;
+    }
+
+    let labels = relationship_queries::active_labels(&tx, ctx.owner_id())?;
+    let label_ids: Vec<Id<RelationshipLabel>> = labels
+        .iter()
+        .filter(|l| person.labels.contains(&l.name))
+        .map(|l| l.id)
+        .collect();
+    if !label_ids.is_empty() {
+        relationship_ops::set_labels(&tx, ctx.owner_id(), saved.id, label_ids)?;
+    }
+    for circle in circle_queries::active_circles(&tx, ctx.owner_id())? {
+        if person.circles.contains(&circle.name) {
+            circle_ops::add_members(&tx, circle.id, vec![saved.id])?;
+        }
+    }
+    tx.commit()?;
+    Ok(saved)
+}
+
+/// Adds one contact entry through `contact_ops`.
+pub fn add_contact(
+    conn: &rusqlite::Connection,
+    owner_id: Id<User>,
+    person_id: Id<Person>,
+    contact: &ContactDraft,
+) -> PrmResult<ContactEntry> {
+    let label = contact.label.as_deref();
+    match contact.kind.to_lowercase().as_str() {
+        "phone" => contact_ops::add_phone(conn, person_id, &contact.value, label),
+        "email" => contact_ops::add_email(conn, person_id, &contact.value, label),
+        _ => {
+            let custom = contact_repo::find_custom_type_by_name(conn, owner_id, &contact.kind)?
+                .ok_or_else(|| PrmError::NotFound {
+                    entity_type: "CustomContactType".into(),
+                    id: contact.kind.clone(),
+                })?;
+            contact_ops::add_custom_contact(conn, person_id, custom.id, &contact.value, label)
+        }
+    }
+}
+
+fn describe_birthday(birthday: Option<&str>) -> String {
+    match birthday.and_then(Birthday::parse) {
+        Some(Birthday::Date(date)) => date.to_string(),
+        Some(Birthday::NoYear(month, day)) => {
+            format!("{} (no year; you'll be asked for it when saving)", describe_month_day(month, day))
+        }
+        None => "(none)".into(),
+    }
+}
+
+fn describe_month_day(month: u32, day: u32) -> String {
+    NaiveDate::from_ymd_opt(2000, month, day)
+        .map(|d| d.format("%B %-d").to_string())
+        .unwrap_or_default()
+}
+
+pub fn describe_contacts(contacts: &[ContactDraft]) -> String {
+    if contacts.is_empty() {
+        "(none)".into()
+    } else {
+        contacts.iter().map(ContactDraft::describe).collect::<Vec<_>>().join("; ")
+    }
+}
+
+pub fn describe_names(names: &[String]) -> String {
+    if names.is_empty() {
+        "(none)".into()
+    } else {
+        names.join(", ")
+    }
+}
@@ -0,0 +1,282 @@
+use chrono::NaiveDate;
+
+use crate::ai::llm_backend::LlmConfig;
+use crate::ai::person_edit::{self, CurrentRecord, PersonEdit};
+use crate::ai::person_extraction::{ContactDraft, Vocabulary};
+use crate::cli::ai_add_command::{self, describe_contacts, describe_names};
+use crate::cli::ai_log_command::{print_parse_error, relevant_corrections};
+use crate::cli::context::CLIContext;
+use crate::db::correction_repo::{self, CorrectionKind};
+use crate::error::PrmResult;
+use crate::matching;
+use crate::model::*;
+use crate::ops::*;
+use crate::queries::*;
+
+/// `ai-edit <note>`
+pub fn ai_edit(ctx: &CLIContext, args: &str) {
+    let note = args.trim();
+    if note.is_empty() {
+        println!("Usage: ai-edit <what changed, in your own words>");
+        println!("Example: ai-edit Alice moved to Berlin and got a new number 555-1234");
+        return;
+    }
+    let Some(person) = mentioned_person(ctx, note) else { return };
+
+    let backend = match LlmConfig::load() {
+        Ok(config) => config.backend(),
+        Err(e) => return println!("Error: {}", e),
+    };
+    if let Err(err) = backend.health_check() {
+        println!("Error: {}", err);
+        return;
+    }
+
+    let current = match CurrentRecord::load(&ctx.conn, ctx.owner_id(), person) {
+        Ok(c) => c,
+        Err(e) => return println!("Error: {}", e),
+    };
+    let vocabulary = ai_add_command::vocabulary(ctx);
+    let corrections = relevant_corrections(ctx, CorrectionKind::PersonEdit, note);
+    println!("Parsing with AI ({})...", backend.config().location());
+    let proposed = match person_edit::propose_edit(&*backend, note, &current, &vocabulary, &corrections) {
+        Ok(edit) => edit,
+        Err(e) => return print_parse_error(&e),
+    };
+    if proposed.is_empty() {
+        println!("No changes to {} found in that note.", current.person.name);
+        return;
+    }
+
+    let Some(edit) = review(ctx, &current, proposed.clone(), &vocabulary) else { return };
+    match apply(ctx, &current, &edit) {
+        Ok(updated) => {
+            if edit != proposed {
+                save_correction(ctx, note, &proposed, &edit);
+            }
+            println!("Updated {}.", updated.name);
+            for line in edit.describe(&current) {
+                println!("  {}", line);
+            }
+        }
+        Err(e) => ctx.print_error(&e),
+    }
+}
+
+/// The person the note is about: the one whose full name it mentions, or
+/// failing that whose first name or nickname it mentions. Asks when that
+/// leaves several or none.
+fn mentioned_person(ctx: &CLIContext, note: &str) -> Option<Person> {
+    let text = format!(" {} ", words(note));
+    let mentions = |name: &str| {
+        let name = words(name);
+        !name.is_empty() && text.contains(&format!(" {} ", name))
+    };
+    let people = person_queries::active_people(&ctx.conn, ctx.owner_id()).unwrap_or_default();
+    let mut matches: Vec<Person> = people.iter().filter(|p| mentions(&p.name)).cloned().collect();
+    if matches.is_empty() {
+        matches = people
+            .into_iter()
+            .filter(|p| {
+                let first = p.name.split_whitespace().next().unwrap_or_default();
+                mentions(first) || p.nickname.as_deref().is_some_and(mentions)
+            })
+            .collect();
+    }
+    match matches.len() {
+        0 => {
+            let name = ctx.prompt("Who is this about? ")?;
+            ctx.find_person(&name)
+        }
+        1 => matches.pop(),
+        _ => ctx.choose_person(note, matches),
+    }
+}
+
+/// Lowercase words without accents or punctuation, separated by single spaces.
+fn words(s: &str) -> String {
+    matching::fold(s)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Shows the proposed changes and lets the user amend them until they apply
+/// (returning the final version) or discard them.
+fn review(ctx: &CLIContext, current: &CurrentRecord, initial: PersonEdit, vocabulary: &Vocabulary) -> Option<PersonEdit> {
+    let mut edit = initial;
+    loop {
+        display(current, &edit);
+        println!();
+        println!("Actions: (a)pply, (e)dit a change, (d)iscard");
+
+        let choice = ctx.prompt("Choice: ")?.to_lowercase();
+        match choice.as_str() {
+            "a" | "apply" if edit.is_empty() => println!("Nothing to apply. Edit a change or discard."),
+            "a" | "apply" => return Some(edit),
+            "e" | "edit" => edit_change(ctx, current, &mut edit, vocabulary),
+            "d" | "discard" => {
+                println!("Discarded.");
+                return None;
+            }
+            _ => println!("Invalid choice. Enter 'a' to apply, 'e' to edit, or 'd' to discard."),
+        }
+    }
+}
+
+fn display(current: &CurrentRecord, edit: &PersonEdit) {
+    println!();
+    println!("Proposed changes to {}:", current.person.name);
+    for (i, (name, before, change)) in edit.fields.entries(&current.person).into_iter().enumerate() {
+        let before = before.unwrap_or_else(|| "(none)".into());
+        let shown = match change.as_deref() {
+            None => format!("{} (no change)", before),
+            Some("") => format!("{} -> (cleared)", before),
+            Some(after) => format!("{} -> {}", before, after),
+        };
+        println!("  {:>2}. {:<18}{}", i + 1, format!("{}:", name), shown);
+    }
+    let removed: Vec<ContactDraft> = edit
+        .remove_contacts
+        .iter()
+        .filter_map(|v| current.contact(v).map(|(_, c)| c.clone()))
+        .collect();
+    let lists = [
+        ("Add contacts", describe_contacts(&edit.add_contacts)),
+        ("Remove contacts", describe_contacts(&removed)),
+        ("Add labels", describe_names(&edit.add_labels)),
+        ("Remove labels", describe_names(&edit.remove_labels)),
+    ];
+    for (i, (name, shown)) in lists.into_iter().enumerate() {
+        println!("  {:>2}. {:<18}{}", i + 7, format!("{}:", name), shown);
+    }
+}
+
+fn edit_change(ctx: &CLIContext, current: &CurrentRecord, edit: &mut PersonEdit, vocabulary: &Vocabulary) {
+    let Some(field) = ctx.prompt("Change (1-10): ") else { return };
+    match field.parse::<usize>() {
+        Ok(n @ 1..=6) => {
+            let (name, before, _) = edit.fields.entries(&current.person)[n - 1].clone();
+            if let Some(change) = edit.fields.get_mut(n - 1) {
+                edit_field(ctx, name, before, change);
+            }
+        }
+        Ok(7) => ai_add_command::edit_contacts(ctx, &mut edit.add_contacts, &vocabulary.contact_types),
+        Ok(8) => edit_removed_contacts(ctx, current, &mut edit.remove_contacts),
+        Ok(9) => {
+            let available: Vec<String> =
+                vocabulary.labels.iter().filter(|l| !current.labels.contains(l)).cloned().collect();
+            ai_add_command::edit_names(ctx, "Labels to add", &mut edit.add_labels, &available);
+        }
+        Ok(10) => ai_add_command::edit_names(ctx, "Labels to remove", &mut edit.remove_labels, &current.labels),
+        _ => println!("Invalid change number."),
+    }
+}
+
+/// Prompts for a field's new value: Enter keeps the proposed change, `=`
+/// leaves the field as it is, `-` clears it.
+fn edit_field(ctx: &CLIContext, name: &str, before: Option<String>, change: &mut Option<String>) {
+    let proposed = match change.as_deref() {
+        None => before.unwrap_or_else(|| "(none)".into()),
+        Some("") => "(cleared)".into(),
+        Some(after) => after.to_string(),
+    };
+    let hint = if name == "Birthday" { "YYYY-MM-DD, " } else { "" };
+    let input = ctx
+        .prompt(&format!("{} ({}'=' for no change, '-' to clear) [{}]: ", name, hint, proposed))
+        .unwrap_or_default();
+    match input.as_str() {
+        "" => {}
+        "=" => *change = None,
+        "-" if name == "Name" => println!("The name can't be cleared."),
+        "-" => *change = Some(String::new()),
+        s if name == "Birthday" && NaiveDate::parse_from_str(s, "%Y-%m-%d").is_err() => {
+            println!("Invalid date, keeping the proposed change.")
+        }
+        _ => *change = Some(input),
+    }
+}
+
+/// Toggles which of the current contacts are removed, by number, until Enter.
+fn edit_removed_contacts(ctx: &CLIContext, current: &CurrentRecord, removed: &mut Vec<String>) {
+    if current.contacts.is_empty() {
+        println!("No contacts to remove.");
+        return;
+    }
+    loop {
+        for (i, (_, contact)) in current.contacts.iter().enumerate() {
+            let marked = removed.iter().any(|v| v.trim().eq_ignore_ascii_case(contact.value.trim()));
+            println!("  {}. [{}] {}", i + 1, if marked { "x" } else { " " }, contact.describe());
+        }
+        let input = match ctx.prompt("Toggle removal (number), or Enter when done: ") {
+            Some(s) if !s.is_empty() => s,
+            _ => return,
+        };
+        match input.parse::<usize>() {
+            Ok(n) if n >= 1 && n <= current.contacts.len() => {
+                let value = &current.contacts[n - 1].1.value;
+                let before = removed.len();
+                removed.retain(|v| !v.trim().eq_ignore_ascii_case(value.trim()));
+                if removed.len() == before {
+                    removed.push(value.clone());
+                }
+            }
+            _ => println!("Enter a number from the list, or press Enter when done."),
+        }
+    }
+}
+
+/// Keeps the user's amendment as an example for later edits.
+fn save_correction(ctx: &CLIContext, note: &str, proposed: &PersonEdit, edit: &PersonEdit) {
+    let (Ok(ai_json), Ok(user_json)) = (serde_json::to_string(proposed), serde_json::to_string(edit)) else { return };
+    let _ = correction_repo::insert_kind(&ctx.conn, ctx.owner_id(), CorrectionKind::PersonEdit, note, &ai_json, &user_json);
+}
+
+/// Applies the changes, all or nothing.
+fn apply(ctx: &CLIContext, current: &CurrentRecord, edit: &PersonEdit) -> PrmResult<Person> {
+    let fields = &edit.fields;
+    let birthday = fields
+        .birthday
+        .as_deref()
+        .map(|b| NaiveDate::parse_from_str(b, "%Y-%m-%d").ok());
+    let person_id = current.person.id;
+
+    let tx = ctx.conn.unchecked_transaction()?;
+    let updated = person_ops::update_person(
+        &tx,
+        person_id,
+        fields.name.as_deref(),
+        update(&fields.nickname),
+        update(&fields.how_we_met),
+        birthday,
+        update(&fields.notes),
+        update(&fields.location),
+    )?;
+    for value in &edit.remove_contacts {
+        if let Some((entry_id, _)) = current.contact(value) {
+            contact_ops::remove_contact(&tx, *entry_id)?;
+        }
+    }
+    for contact in &edit.add_contacts {
+        ai_add_command::add_contact(&tx, ctx.owner_id(), person_id, contact)?;
+    }
+
+    let labels = relationship_queries::active_labels(&tx, ctx.owner_id())?;
+    let ids = |names: &[String]| -> Vec<Id<RelationshipLabel>> {
+        labels.iter().filter(|l| names.contains(&l.name)).map(|l| l.id).collect()
+    };
+    if !edit.add_labels.is_empty() {
+        relationship_ops::add_labels(&tx, ctx.owner_id(), person_id, ids(&edit.add_labels))?;
+    }
+    if !edit.remove_labels.is_empty() {
+        relationship_ops::remove_labels(&tx, ctx.owner_id(), person_id, ids(&edit.remove_labels))?;
+    }
+    tx.commit()?;
+    Ok(updated)
+}
+
+/// A field change in `update_person`'s terms, where `Some(None)` clears.
+fn update(change: &Option<String>) -> Option<Option<&str>> {
+    change.as_deref().map(|v| Some(v).filter(|v| !v.is_empty()))
+}
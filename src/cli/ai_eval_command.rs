@@ -0,0 +1,144 @@
+use std::path::Path;
+use std::time::Duration;
+
+use crate::ai::eval::{self, EvalReport};
+use crate::ai::llm_backend::LlmConfig;
+use crate::ai::llm_service::{self, INTERACTION_PROMPT};
+use crate::cli::args;
+use crate::cli::context::CLIContext;
+use crate::db::correction_repo::{self, CorrectionKind};
+use crate::queries::person_queries;
+
+const USAGE: &str = "Usage: ai-eval <dataset.jsonl>|corrections [--model <name>] [--prompt <file>] \
+                     [--vs-model <name>] [--vs-prompt <file>] [--save <file>]";
+
+/// A model and prompt to score.
+struct Variant {
+    label: String,
+    config: LlmConfig,
+    prompt: String,
+}
+
+/// `ai-eval <dataset.jsonl>|corrections [--model] [--prompt] [--vs-model] [--vs-prompt] [--save]`
+pub fn ai_eval(ctx: &CLIContext, args: &str) {
+    let parsed = args::parse(args);
+    if parsed.words.is_empty() {
+        println!("{}", USAGE);
+        println!("Example: ai-eval evals.jsonl --vs-model qwen2.5:7b");
+        return;
+    }
+
+    let network_names: Vec<String> = person_queries::active_people(&ctx.conn, ctx.owner_id())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|p| !p.is_self)
+        .map(|p| p.name)
+        .collect();
+
+    let source = parsed.positional.as_str();
+    let mut cases = if source == "corrections" {
+        match correction_repo::all_of_kind(&ctx.conn, ctx.owner_id(), CorrectionKind::Interaction) {
+            Ok(records) => eval::cases_from_corrections(&records, &network_names),
+            Err(e) => return ctx.print_error(&e),
+        }
+    } else {
+        match eval::load_dataset(Path::new(source)) {
+            Ok(cases) => cases,
+            Err(e) => return println!("Error: {}", e),
+        }
+    };
+    if cases.is_empty() {
+        println!("No cases found in {}.", source);
+        return;
+    }
+
+    if let Some(path) = parsed.flag("save").filter(|p| !p.is_empty()) {
+        match eval::save_dataset(Path::new(path), &cases) {
+            Ok(()) => println!("Saved {} case(s) to {}.", cases.len(), path),
+            Err(e) => println!("Error: {}", e),
+        }
+        return;
+    }
+    for case in cases.iter_mut().filter(|c| c.known_names.is_empty()) {
+        case.known_names = network_names.clone();
+    }
+
+    let base = match LlmConfig::load() {
+        Ok(config) => config,
+        Err(e) => return println!("Error: {}", e),
+    };
+    let first = match variant(&base, parsed.flag("model"), parsed.flag("prompt")) {
+        Ok(v) => v,
+        Err(e) => return println!("Error: {}\n{}", e, USAGE),
+    };
+    let mut variants = vec![first];
+    if parsed.has("vs-model") || parsed.has("vs-prompt") {
+        let model = parsed.flag("vs-model").or(parsed.flag("model"));
+        let prompt = parsed.flag("vs-prompt").or(parsed.flag("prompt"));
+        match variant(&base, model, prompt) {
+            Ok(v) => variants.push(v),
+            Err(e) => return println!("Error: {}\n{}", e, USAGE),
+        }
+    }
+
+    if let Err(err) = llm_service::check_backend() {
+        println!("Error: {}", err);
+        return;
+    }
+
+    let mut reports = Vec::new();
+    for v in &variants {
+        println!("Running {} case(s) with {}...", cases.len(), v.label);
+        reports.push(eval::run(&*v.config.backend(), &v.prompt, &cases));
+    }
+    println!();
+    print_reports(&variants, &reports);
+}
+
+fn variant(base: &LlmConfig, model: Option<&str>, prompt_path: Option<&str>) -> Result<Variant, String> {
+    let mut config = base.clone();
+    if let Some(model) = model.filter(|m| !m.is_empty()) {
+        config.model = model.to_string();
+    }
+    let (prompt, prompt_name) = match prompt_path.filter(|p| !p.is_empty()) {
+        Some(path) => {
+            let text = std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+            (text, path.to_string())
+        }
+        None => (INTERACTION_PROMPT.to_string(), "default prompt".to_string()),
+    };
+    Ok(Variant { label: format!("{} ({})", config.model, prompt_name), config, prompt })
+}
+
+fn print_reports(variants: &[Variant], reports: &[EvalReport]) {
+    let width = variants.iter().map(|v| v.label.len()).max().unwrap_or(0).max(14) + 2;
+    let row = |name: &str, cell: &dyn Fn(&EvalReport) -> String| {
+        let cells: String = reports.iter().map(|r| format!("{:<width$}", cell(r), width = width)).collect();
+        println!("  {:<18}{}", name, cells.trim_end());
+    };
+
+    let header: String = variants.iter().map(|v| format!("{:<width$}", v.label, width = width)).collect();
+    println!("  {:<18}{}", "", header.trim_end());
+    row("Names", &|r| share(r.names_correct, r.cases));
+    row("Medium", &|r| share(r.medium_correct, r.cases));
+    row("Date", &|r| share(r.date_correct, r.cases));
+    row("Location", &|r| share(r.location_correct, r.cases));
+    row("Topic precision", &|r| share(r.topics_matched, r.topics_predicted));
+    row("Topic recall", &|r| share(r.topics_matched, r.topics_expected));
+    row("Failures", &|r| share(r.failures, r.cases));
+    row("Latency (median)", &|r| seconds(r.median_latency()));
+    row("Latency (max)", &|r| seconds(r.max_latency()));
+}
+
+/// `83% (10/12)`
+fn share(part: usize, whole: usize) -> String {
+    if whole == 0 {
+        return "-".into();
+    }
+    format!("{:.0}% ({}/{})", part as f64 * 100.0 / whole as f64, part, whole)
+}
+
+fn seconds(d: Duration) -> String {
+    format!("{:.1}s", d.as_secs_f64())
+}
+
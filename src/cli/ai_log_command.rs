@@ -1,8 +1,9 @@
 use chrono::NaiveDate;
 
-use crate::ai::llm_service::{self, CorrectionExample, ParsedInteraction};
+use crate::ai::correction_retrieval;
+use crate::ai::llm_service::{self, CorrectionExample, LlmError, ParsedInteraction};
 use crate::cli::context::CLIContext;
-use crate::db::correction_repo;
+use crate::db::correction_repo::{self, CorrectionKind};
 use crate::model::*;
 use crate::ops::*;
 use crate::queries::*;
@@ -14,7 +15,7 @@ pub fn ai_log(ctx: &CLIContext, args: &str) {
         return;
     }
 
-    if let Err(err) = llm_service::check_ollama() {
+    if let Err(err) = llm_service::check_backend() {
         println!("Error: {}", err);
         return;
     }
@@ -26,26 +27,51 @@ pub fn ai_log(ctx: &CLIContext, args: &str) {
         .map(|p| p.name)
         .collect();
 
-    let corrections: Vec<CorrectionExample> =
-        correction_repo::recent(&ctx.conn, ctx.owner_id(), 5)
-            .unwrap_or_default()
-            .into_iter()
-            .map(|r| CorrectionExample {
-                original_text: r.original_text,
-                ai_output: r.ai_output,
-                user_output: r.user_output,
-            })
-            .collect();
-
-    println!("Parsing with AI (local)...");
+    let corrections = relevant_corrections(ctx, CorrectionKind::Interaction, args);
+
+    println!("Parsing with AI ({})...", llm_service::server_location());
     match llm_service::parse_interaction(args, &known_names, &corrections) {
-        Err(err) => println!("Error: {}", err),
+        Err(err) => print_parse_error(&err),
         Ok(parsed) => review_and_save(ctx, args, parsed),
     }
 }
 
+/// Past corrections of `kind` most like `input`, to include in the prompt.
+pub fn relevant_corrections(ctx: &CLIContext, kind: CorrectionKind, input: &str) -> Vec<CorrectionExample> {
+    let mut records = correction_repo::all_of_kind(&ctx.conn, ctx.owner_id(), kind).unwrap_or_default();
+    records.reverse();
+    correction_retrieval::select(input, records, correction_retrieval::TOKEN_BUDGET)
+}
+
+/// Prints why parsing failed, with the model's last reply when it was
+/// unusable so it can be inspected.
+pub fn print_parse_error(err: &LlmError) {
+    println!("Error: {}", err);
+    if let LlmError::InvalidResponse { raw, .. } = err {
+        println!("The model's last reply was:");
+        println!("  {}", raw.trim());
+    }
+}
+
 pub fn review_and_save(ctx: &CLIContext, original_text: &str, initial: ParsedInteraction) {
     let ai_original = initial.clone();
+    if let ReviewOutcome::Save(current) = review(ctx, initial) {
+        maybe_save_correction(ctx, original_text, &ai_original, &current);
+        save_interaction(ctx, &current);
+    }
+}
+
+/// How the user left the review loop.
+pub enum ReviewOutcome {
+    Save(ParsedInteraction),
+    Discard,
+    /// Input ended before a choice was made.
+    Closed,
+}
+
+/// Shows a proposed interaction and lets the user edit fields until they
+/// save or discard it.
+pub fn review(ctx: &CLIContext, initial: ParsedInteraction) -> ReviewOutcome {
     let mut current = initial;
 
     loop {
@@ -55,21 +81,17 @@ pub fn review_and_save(ctx: &CLIContext, original_text: &str, initial: ParsedInt
 
         let choice = match ctx.prompt("Choice: ") {
             Some(c) => c.to_lowercase(),
-            None => return,
+            None => return ReviewOutcome::Closed,
         };
 
         match choice.as_str() {
-            "s" | "save" => {
-                maybe_save_correction(ctx, original_text, &ai_original, &current);
-                save_interaction(ctx, &current);
-                return;
-            }
+            "s" | "save" => return ReviewOutcome::Save(current),
             "e" | "edit" => {
                 current = edit_field(ctx, current);
             }
             "d" | "discard" => {
                 println!("Discarded.");
-                return;
+                return ReviewOutcome::Discard;
             }
             _ => println!("Invalid choice. Enter 's' to save, 'e' to edit, or 'd' to discard."),
         }
@@ -137,7 +159,7 @@ fn edit_field(ctx: &CLIContext, parsed: ParsedInteraction) -> ParsedInteraction
             }
         }
         "2" => {
-            println!("  1. In Person  2. Text  3. Phone Call  4. Video Call  5. Social Media");
+            println!("  1. In Person  2. Text  3. Phone Call  4. Video Call  5. Social Media  6. Email");
             let input = ctx
                 .prompt(&format!("Medium [{}]: ", format_medium(&result.medium)))
                 .unwrap_or_default();
@@ -147,6 +169,7 @@ fn edit_field(ctx: &CLIContext, parsed: ParsedInteraction) -> ParsedInteraction
                 "3" => "PhoneCall".into(),
                 "4" => "VideoCall".into(),
                 "5" => "SocialMedia".into(),
+                "6" => "Email".into(),
                 "" => result.medium,
                 _ => {
                     println!("Invalid selection, keeping current.");
@@ -262,11 +285,12 @@ fn format_medium(s: &str) -> &str {
         "PhoneCall" => "Phone Call",
         "VideoCall" => "Video Call",
         "SocialMedia" => "Social Media",
+        "Email" => "Email",
         other => other,
     }
 }
 
-fn save_interaction(ctx: &CLIContext, parsed: &ParsedInteraction) {
+pub fn save_interaction(ctx: &CLIContext, parsed: &ParsedInteraction) {
     if parsed.location.is_empty() {
         println!("Error: Location is required. Please edit the location field first.");
         return;
@@ -337,32 +361,11 @@ fn save_interaction(ctx: &CLIContext, parsed: &ParsedInteraction) {
 }
 
 fn resolve_person(ctx: &CLIContext, name: &str) -> Option<Person> {
-    let people = person_queries::active_people(&ctx.conn, ctx.owner_id()).unwrap_or_default();
-    let lower = name.to_lowercase();
-    let matches: Vec<&Person> = people
-        .iter()
-        .filter(|p| {
-            p.name.to_lowercase().contains(&lower)
-                || p.nickname
-                    .as_ref()
-                    .map(|n| n.to_lowercase().contains(&lower))
-                    .unwrap_or(false)
-        })
-        .collect();
+    let matches = ctx.person_matches(name);
 
     match matches.len() {
-        1 => Some(matches[0].clone()),
-        n if n > 1 => {
-            if let Some(exact) = matches.iter().find(|p| p.name.eq_ignore_ascii_case(name)) {
-                return Some((*exact).clone());
-            }
-            println!("Multiple matches for '{}':", name);
-            for p in &matches {
-                println!("  {}", p.name);
-            }
-            println!("Please edit the people field to be more specific.");
-            None
-        }
+        1 => matches.into_iter().next(),
+        n if n > 1 => ctx.choose_person(name, matches),
         _ => {
             println!("'{}' is not in your network.", name);
             let answer = ctx
@@ -394,6 +397,7 @@ fn parse_medium(s: &str) -> InteractionMedium {
         "PhoneCall" => InteractionMedium::PhoneCall,
         "VideoCall" => InteractionMedium::VideoCall,
         "SocialMedia" => InteractionMedium::SocialMedia,
+        "Email" => InteractionMedium::Email,
         other => {
             println!("Unknown medium '{}', defaulting to In Person", other);
             InteractionMedium::InPerson
@@ -0,0 +1,68 @@
+use crate::util;
+
+/// Command arguments split into free text and `--flag value` options.
+pub struct ParsedArgs {
+    pub positional: String,
+    /// The positional words individually, with quoted groups kept whole.
+    pub words: Vec<String>,
+    pub flags: Vec<(String, String)>,
+}
+
+impl ParsedArgs {
+    /// Value of a flag, if given. Boolean flags have an empty value.
+    pub fn flag(&self, name: &str) -> Option<&str> {
+        self.flags
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Every value of a flag that may be repeated, e.g. `--ignore a --ignore b`.
+    pub fn flag_values(&self, name: &str) -> Vec<&str> {
+        self.flags
+            .iter()
+            .filter(|(k, v)| k == name && !v.is_empty())
+            .map(|(_, v)| v.as_str())
+            .collect()
+    }
+
+    pub fn has(&self, name: &str) -> bool {
+        self.flag(name).is_some()
+    }
+}
+
+/// Splits args into positional words and `--flag [value]` pairs.
+/// Double quotes group words, so `--circle "book club"` works.
+pub fn parse(args: &str) -> ParsedArgs {
+    parse_tokens(util::tokenize(args))
+}
+
+/// Like `parse`, for arguments the shell has already split.
+pub fn parse_tokens(tokens: Vec<String>) -> ParsedArgs {
+    let mut positional = Vec::new();
+    let mut flags = Vec::new();
+
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = &tokens[i];
+        if let Some(name) = token.strip_prefix("--") {
+            let value = match tokens.get(i + 1) {
+                Some(next) if !next.starts_with("--") => {
+                    i += 1;
+                    next.clone()
+                }
+                _ => String::new(),
+            };
+            flags.push((name.to_string(), value));
+        } else {
+            positional.push(token.clone());
+        }
+        i += 1;
+    }
+
+    ParsedArgs {
+        positional: positional.join(" "),
+        words: positional,
+        flags,
+    }
+}
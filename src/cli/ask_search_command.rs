@@ -0,0 +1,118 @@
+use std::io::Write;
+
+use crate::ai::llm_backend::LlmConfig;
+use crate::ai::semantic_search::{self, Hit, RefreshSummary};
+use crate::cli::args;
+use crate::cli::context::CLIContext;
+use crate::db::embedding_repo;
+
+const USAGE: &str = "Usage: ask-search <question> [--limit N]";
+const DEFAULT_LIMIT: usize = 5;
+
+/// `ask-search <question> [--limit N]`
+pub fn ask_search(ctx: &CLIContext, args: &str) {
+    let parsed = args::parse(args);
+    let question = parsed.positional.trim();
+    if question.is_empty() {
+        println!("{}", USAGE);
+        println!("Example: ask-search who did I talk to about burnout");
+        return;
+    }
+    let limit = match parsed.flag("limit") {
+        None => DEFAULT_LIMIT,
+        Some(n) => match n.parse::<usize>() {
+            Ok(n) if n > 0 => n,
+            _ => return println!("--limit must be a positive number.\n{}", USAGE),
+        },
+    };
+
+    let backend = match LlmConfig::load() {
+        Ok(config) => config.backend(),
+        Err(e) => return println!("Error: {}", e),
+    };
+    if let Err(err) = backend.health_check() {
+        println!("Error: {}", err);
+        return;
+    }
+
+    // Embed what's new or changed since the last search
+    let refreshed = semantic_search::refresh(&ctx.conn, ctx.owner_id(), &*backend, |done, total| {
+        print!("\rIndexing notes ({}/{})...", done + 1, total);
+        let _ = std::io::stdout().flush();
+    });
+    match refreshed {
+        Ok(summary) if summary.embedded > 0 => println!(),
+        Ok(_) => {}
+        Err(e) => {
+            println!();
+            println!("Error: {}", e);
+            println!("Semantic search needs an embedding model, e.g. `ollama pull {}`.", backend.config().embed_model);
+            return;
+        }
+    }
+
+    let results = match semantic_search::search(&ctx.conn, ctx.owner_id(), &*backend, question, limit) {
+        Ok(r) => r,
+        Err(e) => return println!("Error: {}", e),
+    };
+    if results.is_empty() {
+        println!("Nothing to search yet: log some interactions or add notes to people.");
+        return;
+    }
+
+    println!("Closest matches for \"{}\":", question);
+    for result in results {
+        match result.hit {
+            Hit::Interaction { names, interaction } => {
+                println!(
+                    "  {:.2}  {}  {} ({})",
+                    result.score,
+                    interaction.date,
+                    names.join(", "),
+                    interaction.medium.display_name()
+                );
+                println!("        Topics: {}", interaction.topics.join(", "));
+                if let Some(note) = interaction.note.filter(|n| !n.trim().is_empty()) {
+                    println!("        Note: {}", note);
+                }
+            }
+            Hit::PersonNotes { name, notes } => {
+                println!("  {:.2}  {} (notes)", result.score, name);
+                println!("        {}", notes);
+            }
+        }
+    }
+}
+
+/// Embeds what a command added or edited, so searches don't have to catch up
+/// on it. Does nothing until `ask-search` has built an index, or when the
+/// model server doesn't answer its quick health check. `progress` is called
+/// as in `semantic_search::refresh`.
+pub fn update_index(ctx: &CLIContext, progress: impl FnMut(usize, usize)) -> Option<Result<RefreshSummary, String>> {
+    if !embedding_repo::any_for_owner(&ctx.conn, ctx.owner_id()).unwrap_or(false) {
+        return None;
+    }
+    let backend = match LlmConfig::load() {
+        Ok(config) => config.backend(),
+        Err(e) => return Some(Err(e)),
+    };
+    // Writes shouldn't wait on a server that's down; a search catches up
+    backend.health_check().ok()?;
+    Some(semantic_search::refresh(&ctx.conn, ctx.owner_id(), &*backend, progress))
+}
+
+/// `update_index` after a shell command that wrote to the database, showing
+/// progress when there is more than one entry to embed.
+pub fn update_index_after_command(ctx: &CLIContext) {
+    let refreshed = update_index(ctx, |done, total| {
+        if total > 1 {
+            print!("\rIndexing for ask-search ({}/{})...", done + 1, total);
+            let _ = std::io::stdout().flush();
+        }
+    });
+    match refreshed {
+        Some(Ok(summary)) if summary.embedded > 1 => println!(),
+        Some(Err(e)) => println!("Search index not updated ({}); ask-search will catch up.", e),
+        _ => {}
+    }
+}
@@ -1,10 +1,27 @@
 use crate::cli::context::CLIContext;
+use crate::cli::output;
+use crate::error::PrmResult;
 use crate::model::*;
 use crate::ops::*;
 use crate::queries::*;
 
-pub fn list(ctx: &CLIContext) {
-    let circles = circle_queries::active_circles(&ctx.conn, ctx.owner_id()).unwrap_or_default();
+pub fn list(ctx: &CLIContext) -> PrmResult<()> {
+    let circles = circle_queries::active_circles(&ctx.conn, ctx.owner_id())?;
+    if ctx.json() {
+        let summaries: Vec<output::CircleSummary> = circles
+            .iter()
+            .map(|c| {
+                Ok(output::CircleSummary {
+                    id: c.id,
+                    name: c.name.clone(),
+                    description: c.description.clone(),
+                    members: circle_queries::circle_members(&ctx.conn, c.id)?.into_iter().map(|p| p.name).collect(),
+                })
+            })
+            .collect::<PrmResult<_>>()?;
+        output::print_json(&summaries);
+        return Ok(());
+    }
     if circles.is_empty() {
         println!("No circles yet. Use 'add-circle <name>' to create one.");
     } else {
@@ -13,6 +30,7 @@ pub fn list(ctx: &CLIContext) {
             println!("  {} ({} members)", circle.name, circle.member_ids.len());
         }
     }
+    Ok(())
 }
 
 pub fn add(ctx: &CLIContext, args: &str) {
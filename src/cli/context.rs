@@ -1,8 +1,14 @@
 use chrono::Local;
 use rusqlite::Connection;
-use std::io::{self, Write};
+use std::cell::{Cell, RefCell};
+use std::io::{self, IsTerminal, Write};
+use std::path::PathBuf;
+
+use crate::cli::editor::{Completions, LineEditor};
+use crate::cli::output::OutputFormat;
 
 use crate::db::contact_repo;
+use crate::matching;
 use crate::model::*;
 use crate::queries::*;
 
@@ -10,11 +16,31 @@ pub struct CLIContext {
     pub conn: Connection,
     pub user: User,
     pub self_id: Id<Person>,
+    output: Cell<OutputFormat>,
+    editor: RefCell<Option<LineEditor>>,
 }
 
 impl CLIContext {
     pub fn new(conn: Connection, user: User, self_id: Id<Person>) -> Self {
-        Self { conn, user, self_id }
+        Self { conn, user, self_id, output: Cell::new(OutputFormat::Text), editor: RefCell::new(None) }
+    }
+
+    /// Switches prompts to the line editor, with history kept in `history_path`.
+    /// Piped input, or a terminal the editor can't drive, is still read plainly.
+    pub fn enable_line_editor(&self, history_path: PathBuf) {
+        if !io::stdin().is_terminal() {
+            return;
+        }
+        *self.editor.borrow_mut() = LineEditor::new(history_path);
+    }
+
+    pub fn set_output(&self, format: OutputFormat) {
+        self.output.set(format);
+    }
+
+    /// Whether listing commands should print JSON instead of text.
+    pub fn json(&self) -> bool {
+        self.output.get() == OutputFormat::Json
     }
 
     pub fn owner_id(&self) -> Id<User> {
@@ -23,6 +49,9 @@ impl CLIContext {
 
     /// Prompt and read a line from stdin. Returns None on EOF.
     pub fn read_line(&self, prompt: &str) -> Option<String> {
+        if let Some(editor) = self.editor.borrow_mut().as_mut() {
+            return editor.read_line(prompt);
+        }
         print!("{}", prompt);
         io::stdout().flush().ok();
         let mut buf = String::new();
@@ -33,6 +62,26 @@ impl CLIContext {
         }
     }
 
+    /// Read a REPL command, with tab completion and history when the line
+    /// editor is enabled.
+    pub fn read_command(&self, prompt: &str) -> Option<String> {
+        if let Some(editor) = self.editor.borrow_mut().as_mut() {
+            return editor.read_command(prompt, self.completions());
+        }
+        self.read_line(prompt)
+    }
+
+    fn completions(&self) -> Completions {
+        let people = person_queries::active_people(&self.conn, self.owner_id()).unwrap_or_default();
+        let circles = circle_queries::active_circles(&self.conn, self.owner_id()).unwrap_or_default();
+        let labels = relationship_queries::active_labels(&self.conn, self.owner_id()).unwrap_or_default();
+        Completions {
+            people: people.into_iter().filter(|p| p.id != self.self_id).map(|p| p.name).collect(),
+            circles: circles.into_iter().map(|c| c.name).collect(),
+            labels: labels.into_iter().filter(|l| l.name != "me").map(|l| l.name).collect(),
+        }
+    }
+
     /// Read a line, trimmed.
     pub fn prompt(&self, prompt: &str) -> Option<String> {
         self.read_line(prompt).map(|s| s.trim().to_string())
@@ -45,40 +94,82 @@ impl CLIContext {
             return None;
         }
 
-        let people = person_queries::active_people(&self.conn, self.owner_id()).unwrap_or_default();
-        let lower = query.to_lowercase();
-        let matches: Vec<&Person> = people
-            .iter()
-            .filter(|p| {
-                p.name.to_lowercase().contains(&lower)
-                    || p.nickname
-                        .as_ref()
-                        .map(|n| n.to_lowercase().contains(&lower))
-                        .unwrap_or(false)
-            })
-            .collect();
-
+        let matches = self.person_matches(query);
         match matches.len() {
             0 => {
                 println!("No person found matching '{}'", query);
                 None
             }
-            1 => Some(matches[0].clone()),
-            _ => {
-                // Check for exact match
-                if let Some(exact) = matches.iter().find(|p| p.name.eq_ignore_ascii_case(query)) {
-                    return Some((*exact).clone());
-                }
-                println!("Multiple matches found:");
-                for p in &matches {
-                    println!("  {}", p.name);
-                }
-                println!("Please be more specific.");
-                None
+            1 => matches.into_iter().next(),
+            _ => self.choose_person(query, matches),
+        }
+    }
+
+    /// Asks which of several matching people was meant.
+    pub fn choose_person(&self, query: &str, matches: Vec<Person>) -> Option<Person> {
+        self.choose(&format!("Multiple people match '{}':", query), matches, |p| self.describe_person(p))
+    }
+
+    /// A one-line summary that tells people with similar names apart:
+    /// `John Smith "Johnny" - Boston - friend, coworker - last seen 2024-05-01 (3 week(s) ago)`.
+    pub fn describe_person(&self, person: &Person) -> String {
+        let mut line = person.name.clone();
+        if let Some(nick) = person.nickname.as_deref().filter(|n| !n.is_empty()) {
+            line.push_str(&format!(" \"{}\"", nick));
+        }
+        let mut details: Vec<String> = Vec::new();
+        if let Some(location) = person.location.as_deref().filter(|l| !l.is_empty()) {
+            details.push(location.to_string());
+        }
+        let mut labels: Vec<String> = self.labels_for(person.id).into_iter().map(|l| l.name).collect();
+        labels.sort();
+        if !labels.is_empty() {
+            details.push(labels.join(", "));
+        }
+        match interaction_queries::last_interaction_date(&self.conn, person.id).ok().flatten() {
+            Some(date) => details.push(format!(
+                "last seen {} ({})",
+                date,
+                Self::format_days_ago((Self::today() - date).num_days())
+            )),
+            None => details.push("no interactions".into()),
+        }
+        format!("{} - {}", line, details.join(" - "))
+    }
+
+    /// Prints a numbered list and reads a choice. Enter, `0` or `c` cancels;
+    /// anything else invalid asks again.
+    pub fn choose<T>(&self, heading: &str, items: Vec<T>, describe: impl Fn(&T) -> String) -> Option<T> {
+        println!("{}", heading);
+        for (i, item) in items.iter().enumerate() {
+            println!("  {}. {}", i + 1, describe(item));
+        }
+        println!("  0. Cancel");
+        loop {
+            let input = self.prompt(&format!("Choose (1-{}, Enter to cancel): ", items.len()))?;
+            if input.is_empty() || input == "0" || input.eq_ignore_ascii_case("c") {
+                return None;
+            }
+            match input.parse::<usize>() {
+                Ok(n) if n >= 1 && n <= items.len() => return items.into_iter().nth(n - 1),
+                _ => println!("Enter a number from the list, or press Enter to cancel."),
             }
         }
     }
 
+    /// Active people whose name or nickname fuzzily matches the query, best
+    /// first. An exact name match wins over all others.
+    pub fn person_matches(&self, query: &str) -> Vec<Person> {
+        let people = person_queries::active_people(&self.conn, self.owner_id()).unwrap_or_default();
+        let ranked = matching::rank(query, people, |p| p.names());
+
+        // An exact name match (ignoring case and accents) wins outright
+        if let Some((exact, _)) = ranked.iter().find(|(p, _)| matching::fold(&p.name) == matching::fold(query.trim())) {
+            return vec![exact.clone()];
+        }
+        ranked.into_iter().map(|(p, _)| p).collect()
+    }
+
     /// Find an active circle by name query.
     pub fn find_circle(&self, args: &str) -> Option<Circle> {
         let query = args.trim();
@@ -103,12 +194,11 @@ impl CLIContext {
                 if let Some(exact) = matches.iter().find(|c| c.name.eq_ignore_ascii_case(query)) {
                     return Some((*exact).clone());
                 }
-                println!("Multiple matches found:");
-                for c in &matches {
-                    println!("  {}", c.name);
-                }
-                println!("Please be more specific.");
-                None
+                let heading = format!("Multiple circles match '{}':", query);
+                self.choose(&heading, matches, |c| {
+                    format!("{} ({} members)", c.name, c.member_ids.len())
+                })
+                .cloned()
             }
         }
     }
@@ -137,12 +227,8 @@ impl CLIContext {
                 if let Some(exact) = matches.iter().find(|l| l.name.eq_ignore_ascii_case(query)) {
                     return Some((*exact).clone());
                 }
-                println!("Multiple matches found:");
-                for l in &matches {
-                    println!("  {}", l.name);
-                }
-                println!("Please be more specific.");
-                None
+                self.choose(&format!("Multiple labels match '{}':", query), matches, |l| l.name.clone())
+                    .cloned()
             }
         }
     }
@@ -177,8 +263,21 @@ impl CLIContext {
         circle_queries::circles_for_person(&self.conn, self.owner_id(), person_id).unwrap_or_default()
     }
 
+    /// Rows written through this connection so far, to tell whether a
+    /// command changed anything.
+    pub fn total_changes(&self) -> i64 {
+        self.conn.query_row("SELECT total_changes()", [], |row| row.get(0)).unwrap_or(0)
+    }
+
     /// Print an error.
     pub fn print_error(&self, e: &crate::error::PrmError) {
         println!("Error: {}", e);
     }
+
+    /// Print the error a command failed with, if any.
+    pub fn report(&self, result: crate::error::PrmResult<()>) {
+        if let Err(e) = result {
+            self.print_error(&e);
+        }
+    }
 }
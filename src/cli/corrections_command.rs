@@ -0,0 +1,185 @@
+use serde_json::Value;
+
+use crate::cli::args;
+use crate::cli::context::CLIContext;
+use crate::db::correction_repo::{self, CorrectionKind, CorrectionRecord};
+use crate::ops::correction_ops;
+
+const USAGE: &str = "Usage: corrections [list [--pinned]] | show <id> | delete <id> | pin <id> | unpin <id> \
+                     | export <file.json> | import <file.json>";
+
+/// Characters of an id shown in listings; any unique prefix is accepted.
+const SHORT_ID: usize = 8;
+
+/// `corrections list|show|delete|pin|unpin|export|import`
+pub fn corrections(ctx: &CLIContext, args: &str) {
+    let parsed = args::parse(args);
+    let action = parsed.words.first().map(|w| w.to_lowercase()).unwrap_or_else(|| "list".into());
+    let target = parsed.words.get(1..).unwrap_or_default().join(" ");
+
+    match action.as_str() {
+        "list" => list(ctx, parsed.has("pinned")),
+        "show" => {
+            if let Some(record) = find(ctx, &target) {
+                show(&record);
+            }
+        }
+        "delete" => delete(ctx, &target),
+        "pin" => set_pinned(ctx, &target, true),
+        "unpin" => set_pinned(ctx, &target, false),
+        "export" => export(ctx, &target),
+        "import" => import(ctx, &target),
+        _ => println!("{}", USAGE),
+    }
+}
+
+fn list(ctx: &CLIContext, pinned_only: bool) {
+    let mut records = match correction_repo::all(&ctx.conn, ctx.owner_id()) {
+        Ok(r) => r,
+        Err(e) => return ctx.print_error(&e),
+    };
+    records.reverse();
+    records.retain(|r| r.pinned || !pinned_only);
+    if records.is_empty() {
+        println!("No corrections saved yet. They're kept when you edit an ai-log parse or an ai-edit proposal before saving.");
+        return;
+    }
+
+    println!("AI corrections ({}), newest first:", records.len());
+    for r in &records {
+        let fields: Vec<String> = correction_ops::changed_fields(r).into_iter().map(|(name, _, _)| name).collect();
+        println!(
+            "  {}  {}  {}{}{}",
+            short_id(r),
+            r.created_at.get(..10).unwrap_or(&r.created_at),
+            if r.pinned { "[pinned] " } else { "" },
+            if r.kind == CorrectionKind::PersonEdit { "[ai-edit] " } else { "" },
+            truncate(&r.original_text, 50),
+        );
+        if !fields.is_empty() {
+            println!("{:>width$}changed: {}", "", fields.join(", "), width = SHORT_ID + 16);
+        }
+    }
+    println!();
+    println!("Use 'corrections show <id>' for details; pinned corrections are always given to the model.");
+}
+
+fn show(record: &CorrectionRecord) {
+    println!("Correction {}", record.id);
+    println!("  Saved:  {}", record.created_at);
+    println!("  Pinned: {}", if record.pinned { "yes" } else { "no" });
+    println!("  From:   {}", source(record.kind));
+    println!("  Input:  {}", record.original_text);
+    let changes = correction_ops::changed_fields(record);
+    if changes.is_empty() {
+        println!("  No fields changed.");
+    }
+    for (name, before, after) in changes {
+        println!("  {}: {} -> {}", name, describe(&before), describe(&after));
+    }
+}
+
+fn delete(ctx: &CLIContext, target: &str) {
+    let Some(record) = find(ctx, target) else { return };
+    show(&record);
+    let confirm = ctx.prompt("Delete this correction? (y/n): ").unwrap_or_default();
+    if !confirm.eq_ignore_ascii_case("y") {
+        println!("Kept.");
+        return;
+    }
+    match correction_repo::delete(&ctx.conn, record.id) {
+        Ok(()) => println!("Deleted correction {}.", short_id(&record)),
+        Err(e) => ctx.print_error(&e),
+    }
+}
+
+fn set_pinned(ctx: &CLIContext, target: &str, pinned: bool) {
+    let Some(record) = find(ctx, target) else { return };
+    match correction_repo::set_pinned(&ctx.conn, record.id, pinned) {
+        Ok(()) if pinned => println!("Pinned {}: it will be included in every prompt.", short_id(&record)),
+        Ok(()) => println!("Unpinned {}.", short_id(&record)),
+        Err(e) => ctx.print_error(&e),
+    }
+}
+
+fn export(ctx: &CLIContext, path: &str) {
+    if path.is_empty() {
+        println!("Usage: corrections export <file.json>");
+        return;
+    }
+    let json = match correction_ops::export(&ctx.conn, ctx.owner_id()) {
+        Ok(j) => j,
+        Err(e) => return ctx.print_error(&e),
+    };
+    match std::fs::write(path, json) {
+        Ok(()) => println!("Exported corrections to {}.", path),
+        Err(e) => println!("Error: Could not write {}: {}", path, e),
+    }
+}
+
+fn import(ctx: &CLIContext, path: &str) {
+    if path.is_empty() {
+        println!("Usage: corrections import <file.json>");
+        return;
+    }
+    let json = match std::fs::read_to_string(path) {
+        Ok(j) => j,
+        Err(e) => return println!("Error: Could not read {}: {}", path, e),
+    };
+    match correction_ops::import(&ctx.conn, ctx.owner_id(), &json) {
+        Ok(summary) => println!(
+            "Imported {} correction(s); {} already present.",
+            summary.added, summary.skipped
+        ),
+        Err(e) => ctx.print_error(&e),
+    }
+}
+
+/// The correction whose id starts with `prefix`, asking if several do.
+fn find(ctx: &CLIContext, prefix: &str) -> Option<CorrectionRecord> {
+    if prefix.is_empty() {
+        println!("Give a correction id from 'corrections list'.");
+        return None;
+    }
+    let mut matches = correction_ops::find_by_prefix(&ctx.conn, ctx.owner_id(), prefix).unwrap_or_default();
+    match matches.len() {
+        0 => {
+            println!("No correction with id '{}'.", prefix);
+            None
+        }
+        1 => matches.pop(),
+        _ => ctx.choose("Several corrections match:", matches, |r| {
+            format!("{}  {}", r.id, truncate(&r.original_text, 40))
+        }),
+    }
+}
+
+/// The command whose proposal was corrected.
+fn source(kind: CorrectionKind) -> &'static str {
+    match kind {
+        CorrectionKind::Interaction => "ai-log",
+        CorrectionKind::PersonEdit => "ai-edit",
+    }
+}
+
+fn short_id(record: &CorrectionRecord) -> String {
+    record.id.to_string()[..SHORT_ID].to_string()
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        text.to_string()
+    } else {
+        format!("{}...", text.chars().take(max.saturating_sub(3)).collect::<String>())
+    }
+}
+
+fn describe(value: &Value) -> String {
+    match value {
+        Value::Null => "(none)".into(),
+        Value::String(s) if s.is_empty() => "(empty)".into(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items.iter().map(describe).collect::<Vec<_>>().join(", "),
+        other => other.to_string(),
+    }
+}
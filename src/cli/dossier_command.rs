@@ -0,0 +1,88 @@
+use std::path::{Path, PathBuf};
+
+use crate::cli::args;
+use crate::cli::context::CLIContext;
+use crate::export::dossier::{self, DossierFormat};
+use crate::model::*;
+use crate::queries::*;
+
+pub fn dossier(ctx: &CLIContext, args: &str) {
+    let parsed = args::parse(args);
+
+    let format = match parsed.flag("format") {
+        None => DossierFormat::Markdown,
+        Some(f) => match DossierFormat::parse(f) {
+            Some(fmt) => fmt,
+            None => {
+                println!("Unknown format '{}'. Use md or html.", f);
+                return;
+            }
+        },
+    };
+
+    let out_dir = PathBuf::from(parsed.flag("out").filter(|s| !s.is_empty()).unwrap_or("."));
+    if !out_dir.is_dir() {
+        println!("Error: Output directory not found: {}", out_dir.display());
+        return;
+    }
+
+    if let Some(circle_query) = parsed.flag("circle") {
+        if circle_query.is_empty() {
+            println!("Usage: dossier --circle <circle> [--format md|html] [--out <dir>]");
+            return;
+        }
+        let circle = match ctx.find_circle(circle_query) {
+            Some(c) => c,
+            None => return,
+        };
+        let members: Vec<Person> = circle_queries::circle_members(&ctx.conn, circle.id)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|p| p.id != ctx.self_id && !p.archived)
+            .collect();
+        if members.is_empty() {
+            println!("Circle '{}' has no members.", circle.name);
+            return;
+        }
+        let mut written = 0;
+        for person in &members {
+            if write_dossier(ctx, person, format, &out_dir) {
+                written += 1;
+            }
+        }
+        println!("Wrote {} dossier(s) for circle '{}'.", written, circle.name);
+        return;
+    }
+
+    if parsed.positional.is_empty() {
+        println!("Usage: dossier <name> [--format md|html] [--out <dir>]");
+        println!("       dossier --circle <circle> [--format md|html] [--out <dir>]");
+        return;
+    }
+
+    if let Some(person) = ctx.find_person(&parsed.positional) {
+        write_dossier(ctx, &person, format, &out_dir);
+    }
+}
+
+fn write_dossier(ctx: &CLIContext, person: &Person, format: DossierFormat, out_dir: &Path) -> bool {
+    let doc = match dossier::build(&ctx.conn, ctx.owner_id(), person.id, CLIContext::today()) {
+        Ok(d) => d,
+        Err(e) => {
+            ctx.print_error(&e);
+            return false;
+        }
+    };
+
+    let path = out_dir.join(format!("{}.{}", dossier::file_stem(person), format.extension()));
+    match std::fs::write(&path, dossier::render(&doc, format)) {
+        Ok(_) => {
+            println!("Wrote dossier for {} to {}", person.name, path.display());
+            true
+        }
+        Err(e) => {
+            println!("Error writing {}: {}", path.display(), e);
+            false
+        }
+    }
+}
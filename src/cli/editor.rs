@@ -0,0 +1,229 @@
+use std::path::PathBuf;
+
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{CompletionType, Config, Context, Editor, Helper};
+
+/// Command names accepted by the REPL, used for tab completion.
+pub const COMMANDS: &[&str] = &[
+    "help", "quit", "exit", "set",
+    "people", "add-person", "ai-add", "ai-edit", "show-person", "show", "view", "history", "dossier", "edit-person", "find",
+    "archive-person", "unarchive-person", "archived-people",
+    "edit-name", "edit-nickname", "edit-birthday", "edit-how-we-met", "edit-notes", "edit-location",
+    "edit-labels", "edit-circles", "edit-phone", "edit-email",
+    "circles", "add-circle", "show-circle", "edit-circle", "archive-circle", "unarchive-circle", "archived-circles",
+    "labels", "add-label", "show-label", "edit-label", "archive-label", "unarchive-label", "archived-labels",
+    "log", "remind", "set-reminder", "timeline", "drifting", "review", "ai-log", "voice-log", "ai-eval", "corrections", "ask-search", "summarize",
+    "stats", "vault-sync", "import-whatsapp", "import-android", "import-ics", "import-mbox",
+];
+
+/// What a command's argument names, for completion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgKind {
+    Person,
+    Circle,
+    Label,
+    Other,
+}
+
+pub fn arg_kind(command: &str) -> ArgKind {
+    match command {
+        "show-person" | "show" | "view" | "history" | "dossier" | "edit-person" | "archive-person" | "log"
+        | "set-reminder" | "summarize" => ArgKind::Person,
+        c if c.starts_with("edit-") && !matches!(c, "edit-circle" | "edit-label") => ArgKind::Person,
+        "show-circle" | "edit-circle" | "archive-circle" => ArgKind::Circle,
+        "show-label" | "edit-label" | "archive-label" => ArgKind::Label,
+        _ => ArgKind::Other,
+    }
+}
+
+/// Active names offered after commands that take a person, circle or label.
+#[derive(Debug, Clone, Default)]
+pub struct Completions {
+    pub people: Vec<String>,
+    pub circles: Vec<String>,
+    pub labels: Vec<String>,
+}
+
+impl Completions {
+    /// Completes the word under the cursor: a command name at the start of the
+    /// line, otherwise the whole argument (names may contain spaces). A name
+    /// matches when the typed text is a case-insensitive prefix of the name or
+    /// of any word in it. Returns the start of the replaced text.
+    pub fn complete(&self, line: &str, pos: usize) -> (usize, Vec<String>) {
+        let line = &line[..pos];
+        let leading = line.len() - line.trim_start().len();
+        let trimmed = &line[leading..];
+
+        let Some(space) = trimmed.find(char::is_whitespace) else {
+            let mut matches: Vec<String> = COMMANDS
+                .iter()
+                .filter(|c| c.starts_with(trimmed))
+                .map(|c| c.to_string())
+                .collect();
+            matches.sort();
+            return (leading, matches);
+        };
+
+        let command = &trimmed[..space];
+        let rest = &trimmed[space..];
+        let start = leading + space + (rest.len() - rest.trim_start().len());
+        let typed = rest.trim_start().to_lowercase();
+
+        let names = match arg_kind(command) {
+            ArgKind::Person => &self.people,
+            ArgKind::Circle => &self.circles,
+            ArgKind::Label => &self.labels,
+            ArgKind::Other => return (start, Vec::new()),
+        };
+        let mut matches: Vec<String> = names
+            .iter()
+            .filter(|name| {
+                let lower = name.to_lowercase();
+                lower.starts_with(&typed) || lower.split_whitespace().any(|w| w.starts_with(&typed))
+            })
+            .cloned()
+            .collect();
+        matches.sort();
+        matches.dedup();
+        (start, matches)
+    }
+}
+
+/// Completion is only offered at the command prompt, not at the prompts
+/// commands show while they run.
+struct ReplHelper {
+    completions: Completions,
+    active: bool,
+}
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+        if !self.active {
+            return Ok((pos, Vec::new()));
+        }
+        let (start, matches) = self.completions.complete(line, pos);
+        // A completed command is followed by its argument
+        let is_command = !line[..pos].trim_start().contains(char::is_whitespace);
+        let pairs = matches
+            .into_iter()
+            .map(|m| Pair {
+                replacement: if is_command { format!("{} ", m) } else { m.clone() },
+                display: m,
+            })
+            .collect();
+        Ok((start, pairs))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+/// The REPL's line editor: arrow-key editing, history persisted to a file,
+/// and tab completion at the command prompt.
+pub struct LineEditor {
+    editor: Editor<ReplHelper, DefaultHistory>,
+    history_path: PathBuf,
+}
+
+impl LineEditor {
+    /// Opens an editor, loading any history already in `history_path`.
+    pub fn new(history_path: PathBuf) -> Option<Self> {
+        let config = Config::builder()
+            .completion_type(CompletionType::List)
+            .history_ignore_dups(true)
+            .ok()?
+            .history_ignore_space(true)
+            .max_history_size(1000)
+            .ok()?
+            .build();
+        let mut editor = Editor::with_config(config).ok()?;
+        editor.set_helper(Some(ReplHelper { completions: Completions::default(), active: false }));
+        // A missing file just means there is no history yet
+        let _ = editor.load_history(&history_path);
+        Some(Self { editor, history_path })
+    }
+
+    /// Reads a command with completion enabled and records it in the history.
+    /// Ctrl-C clears the line; Ctrl-D returns `None`.
+    pub fn read_command(&mut self, prompt: &str, completions: Completions) -> Option<String> {
+        if let Some(helper) = self.editor.helper_mut() {
+            helper.completions = completions;
+            helper.active = true;
+        }
+        let result = self.editor.readline(prompt);
+        if let Some(helper) = self.editor.helper_mut() {
+            helper.active = false;
+        }
+        match result {
+            Ok(line) => {
+                if !line.trim().is_empty() && self.editor.add_history_entry(line.as_str()).unwrap_or(false) {
+                    let _ = self.editor.append_history(&self.history_path);
+                }
+                Some(line)
+            }
+            Err(ReadlineError::Interrupted) => Some(String::new()),
+            Err(_) => None,
+        }
+    }
+
+    /// Reads a line for a command's own prompt. Ctrl-C or Ctrl-D returns `None`.
+    pub fn read_line(&mut self, prompt: &str) -> Option<String> {
+        self.editor.readline(prompt).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn completions() -> Completions {
+        Completions {
+            people: vec!["Alice Smith".into(), "Bob Jones".into(), "Alicia Keys".into()],
+            circles: vec!["Book Club".into()],
+            labels: vec!["friend".into(), "family".into()],
+        }
+    }
+
+    #[test]
+    fn completes_command_names() {
+        let (start, matches) = completions().complete("show-c", 6);
+        assert_eq!(start, 0);
+        assert_eq!(matches, vec!["show-circle"]);
+
+        let (_, matches) = completions().complete("archived-", 9);
+        assert_eq!(matches, vec!["archived-circles", "archived-labels", "archived-people"]);
+    }
+
+    #[test]
+    fn completes_arguments_by_command() {
+        let (start, matches) = completions().complete("show ali", 8);
+        assert_eq!(start, 5);
+        assert_eq!(matches, vec!["Alice Smith", "Alicia Keys"]);
+
+        // Any word of the name, and names with spaces
+        let (_, matches) = completions().complete("log  jon", 8);
+        assert_eq!(matches, vec!["Bob Jones"]);
+        let (start, matches) = completions().complete("show-circle book c", 18);
+        assert_eq!(start, 12);
+        assert_eq!(matches, vec!["Book Club"]);
+
+        let (_, matches) = completions().complete("show-label fa", 13);
+        assert_eq!(matches, vec!["family"]);
+        let (_, matches) = completions().complete("stats x", 7);
+        assert!(matches.is_empty());
+    }
+}
@@ -0,0 +1,413 @@
+use chrono::NaiveDate;
+
+use crate::ai::llm_service::{self, ParsedInteraction};
+use crate::cli::ai_log_command::{self, ReviewOutcome};
+use crate::cli::args;
+use crate::cli::context::CLIContext;
+use crate::db::person_repo;
+use crate::import::android::{self, Backup};
+use crate::import::ics;
+use crate::import::mbox::{self, MailFilter};
+use crate::import::whatsapp::{self, DateOrder};
+use crate::model::ContactValue;
+use crate::ops::contact_ops;
+use crate::queries::contact_queries;
+
+const DEFAULT_CHAT_TOPIC: &str = "chat";
+
+pub fn import_whatsapp(ctx: &CLIContext, args: &str) {
+    let parsed = args::parse(args);
+    if parsed.words.len() < 2 {
+        println!("Usage: import-whatsapp <file.txt> <name> [--notes] [--summarize] [--date-order dmy|mdy] [--location <place>]");
+        println!("Example: import-whatsapp \"WhatsApp Chat with Alice.txt\" alice --notes");
+        return;
+    }
+
+    let order = match parsed.flag("date-order") {
+        None => None,
+        Some(o) => match DateOrder::parse(o) {
+            Some(order) => Some(order),
+            None => {
+                println!("Unknown date order '{}'. Use dmy or mdy.", o);
+                return;
+            }
+        },
+    };
+
+    let path = &parsed.words[0];
+    let text = match std::fs::read_to_string(path) {
+        Ok(t) => t,
+        Err(e) => {
+            println!("Error: Could not read {}: {}", path, e);
+            return;
+        }
+    };
+
+    let person = match ctx.find_person(&parsed.words[1..].join(" ")) {
+        Some(p) => p,
+        None => return,
+    };
+
+    let days = whatsapp::group_by_day(&whatsapp::parse_chat(&text, order));
+    if days.is_empty() {
+        println!("No messages found. Is this a WhatsApp \"Export chat\" text file?");
+        return;
+    }
+    let total_messages: usize = days.iter().map(|d| d.message_count()).sum();
+    println!(
+        "Found {} messages over {} days ({} to {}).",
+        total_messages,
+        days.len(),
+        days[0].date,
+        days[days.len() - 1].date
+    );
+
+    let (days, skipped) = match whatsapp::new_days(&ctx.conn, person.id, days) {
+        Ok(r) => r,
+        Err(e) => {
+            ctx.print_error(&e);
+            return;
+        }
+    };
+    if skipped > 0 {
+        println!("Skipping {} day(s) already logged as texts with {}.", skipped, person.name);
+    }
+    if days.is_empty() {
+        println!("Nothing new to import.");
+        return;
+    }
+
+    let my_location = match my_location(ctx, parsed.flag("location")) {
+        Some(l) => l,
+        None => {
+            println!("Your location is required.");
+            return;
+        }
+    };
+
+    let summarize = parsed.has("summarize");
+    if summarize {
+        if let Err(err) = llm_service::check_backend() {
+            println!("Error: {}", err);
+            return;
+        }
+    }
+
+    match ctx.prompt(&format!("Import {} day(s) as text interactions with {}? (y/n): ", days.len(), person.name)) {
+        Some(s) if s.eq_ignore_ascii_case("y") || s.eq_ignore_ascii_case("yes") => {}
+        _ => {
+            println!("Cancelled.");
+            return;
+        }
+    }
+
+    let server = llm_service::server_location();
+    let topics_for = |day: &whatsapp::ChatDay| {
+        if summarize {
+            println!("Summarizing {} ({})...", day.date, server);
+            match llm_service::summarize_topics(&day.transcript()) {
+                Ok(topics) => return topics,
+                Err(err) => println!("  {} — using '{}'", err, DEFAULT_CHAT_TOPIC),
+            }
+        }
+        vec![DEFAULT_CHAT_TOPIC.to_string()]
+    };
+
+    match whatsapp::import_days(
+        &ctx.conn,
+        ctx.owner_id(),
+        person.id,
+        &days,
+        &my_location,
+        person.location.as_deref(),
+        parsed.has("notes"),
+        topics_for,
+    ) {
+        Ok(n) => println!("Imported {} text interaction(s) with {}.", n, person.name),
+        Err(e) => ctx.print_error(&e),
+    }
+}
+
+pub fn import_android(ctx: &CLIContext, args: &str) {
+    let parsed = args::parse(args);
+    if parsed.words.is_empty() {
+        println!("Usage: import-android <calls.xml> [sms.xml] [--notes] [--location <place>]");
+        println!("Reads backups written by the \"SMS Backup & Restore\" Android app.");
+        return;
+    }
+
+    let mut backup = Backup::default();
+    for path in &parsed.words {
+        let xml = match std::fs::read_to_string(path) {
+            Ok(x) => x,
+            Err(e) => {
+                println!("Error: Could not read {}: {}", path, e);
+                return;
+            }
+        };
+        match android::parse_backup(&xml) {
+            Ok(b) => backup.extend(b),
+            Err(e) => {
+                println!("Error in {}:", path);
+                ctx.print_error(&e);
+                return;
+            }
+        }
+    }
+    println!("Found {} calls and {} messages.", backup.calls.len(), backup.messages.len());
+    if backup.calls.is_empty() && backup.messages.is_empty() {
+        return;
+    }
+
+    let my_location = match my_location(ctx, parsed.flag("location")) {
+        Some(l) => l,
+        None => {
+            println!("Your location is required.");
+            return;
+        }
+    };
+
+    let report = match android::import(&ctx.conn, ctx.owner_id(), &backup, &my_location, parsed.has("notes")) {
+        Ok(r) => r,
+        Err(e) => {
+            ctx.print_error(&e);
+            return;
+        }
+    };
+
+    println!("Logged {} phone call(s) and {} day(s) of texts.", report.calls, report.text_days);
+    if report.skipped > 0 {
+        println!("Skipped {} already logged.", report.skipped);
+    }
+    if report.unmatched.is_empty() {
+        return;
+    }
+
+    println!();
+    println!("Numbers not matched to anyone ({}):", report.unmatched.len());
+    for (i, u) in report.unmatched.iter().enumerate() {
+        let name = u.contact_name.as_ref().map(|n| format!(" ({})", n)).unwrap_or_default();
+        println!("  {}. {}{} — {} call(s), {} message(s)", i + 1, u.number, name, u.calls, u.messages);
+    }
+    println!();
+
+    let mut attached = 0;
+    loop {
+        let input = match ctx.prompt("Attach a number to a person (number from list, Enter to finish): ") {
+            Some(s) if !s.is_empty() => s,
+            _ => break,
+        };
+        let unmatched = match input.parse::<usize>() {
+            Ok(idx) if idx > 0 && idx <= report.unmatched.len() => &report.unmatched[idx - 1],
+            _ => {
+                println!("Invalid choice.");
+                continue;
+            }
+        };
+        let query = match ctx.prompt(&format!("Person for {}: ", unmatched.number)) {
+            Some(s) if !s.is_empty() => s,
+            _ => continue,
+        };
+        let person = match ctx.find_person(&query) {
+            Some(p) => p,
+            None => continue,
+        };
+        match contact_ops::add_phone(&ctx.conn, person.id, &unmatched.number, None) {
+            Ok(_) => {
+                println!("Added {} to {}.", unmatched.number, person.name);
+                attached += 1;
+            }
+            Err(e) => ctx.print_error(&e),
+        }
+    }
+    if attached > 0 {
+        println!("Run import-android again to log their calls and messages.");
+    }
+}
+
+pub fn import_ics(ctx: &CLIContext, args: &str) {
+    let parsed = args::parse(args);
+    if parsed.positional.is_empty() {
+        println!("Usage: import-ics <file.ics> [--from YYYY-MM-DD] [--to YYYY-MM-DD]");
+        println!("Defaults to the last 30 days.");
+        return;
+    }
+
+    let today = CLIContext::today();
+    let from = match parse_date_flag(parsed.flag("from")) {
+        Ok(d) => d.unwrap_or(today - chrono::Duration::days(30)),
+        Err(msg) => {
+            println!("{}", msg);
+            return;
+        }
+    };
+    let to = match parse_date_flag(parsed.flag("to")) {
+        Ok(d) => d.unwrap_or(today),
+        Err(msg) => {
+            println!("{}", msg);
+            return;
+        }
+    };
+
+    let text = match std::fs::read_to_string(&parsed.positional) {
+        Ok(t) => t,
+        Err(e) => {
+            println!("Error: Could not read {}: {}", parsed.positional, e);
+            return;
+        }
+    };
+
+    let events = ics::parse_calendar(&text);
+    let proposals = match ics::propose(&ctx.conn, ctx.owner_id(), events, from, to) {
+        Ok(p) => p,
+        Err(e) => {
+            ctx.print_error(&e);
+            return;
+        }
+    };
+    if proposals.is_empty() {
+        println!("No unlogged events with people in your network between {} and {}.", from, to);
+        return;
+    }
+    println!("Found {} event(s) with people in your network between {} and {}.", proposals.len(), from, to);
+
+    let self_location = person_repo::find_by_id(&ctx.conn, ctx.self_id)
+        .ok()
+        .flatten()
+        .and_then(|p| p.location);
+
+    for (i, proposal) in proposals.iter().enumerate() {
+        let event = &proposal.event;
+        println!();
+        println!(
+            "Event {} of {}: {} — {}",
+            i + 1,
+            proposals.len(),
+            event.date,
+            event.summary.as_deref().unwrap_or("(no title)")
+        );
+
+        let video = event.is_video();
+        let location = if video {
+            self_location.clone()
+        } else {
+            event.physical_location().map(|l| l.to_string())
+        };
+        let proposed = ParsedInteraction {
+            person_names: proposal.people.iter().map(|p| p.name.clone()).collect(),
+            medium: if video { "VideoCall" } else { "InPerson" }.to_string(),
+            location: location.unwrap_or_default(),
+            their_location: None,
+            topics: event.summary.iter().cloned().collect(),
+            note: None,
+            date: Some(event.date.format("%Y-%m-%d").to_string()),
+        };
+
+        match ai_log_command::review(ctx, proposed) {
+            ReviewOutcome::Save(final_version) => ai_log_command::save_interaction(ctx, &final_version),
+            ReviewOutcome::Discard => {}
+            ReviewOutcome::Closed => return,
+        }
+    }
+}
+
+pub fn import_mbox(ctx: &CLIContext, args: &str) {
+    let parsed = args::parse(args);
+    if parsed.positional.is_empty() {
+        println!("Usage: import-mbox <file.mbox> [--me <address>]... [--ignore <pattern>]... [--include-lists] [--notes] [--location <place>]");
+        println!("Your account email and your own email entries are always treated as you.");
+        return;
+    }
+
+    let bytes = match std::fs::read(&parsed.positional) {
+        Ok(b) => b,
+        Err(e) => {
+            println!("Error: Could not read {}: {}", parsed.positional, e);
+            return;
+        }
+    };
+    let messages = mbox::parse_mbox(&String::from_utf8_lossy(&bytes));
+    if messages.is_empty() {
+        println!("No messages found. Is this an mbox file?");
+        return;
+    }
+
+    let mut self_addresses: Vec<String> = vec![ctx.user.email.clone()];
+    for entry in contact_queries::emails_for(&ctx.conn, ctx.self_id).unwrap_or_default() {
+        if let ContactValue::StringValue { value } = entry.value {
+            self_addresses.push(value);
+        }
+    }
+    self_addresses.extend(parsed.flag_values("me").iter().map(|s| s.to_string()));
+    self_addresses.retain(|a| !a.trim().is_empty());
+    if self_addresses.is_empty() {
+        println!("Error: No email address for you. Pass --me <address> or add an email to yourself.");
+        return;
+    }
+
+    let mut filter = MailFilter::new(self_addresses);
+    filter.ignore.extend(parsed.flag_values("ignore").iter().map(|s| s.to_lowercase()));
+    filter.include_lists = parsed.has("include-lists");
+
+    let my_location = match my_location(ctx, parsed.flag("location")) {
+        Some(l) => l,
+        None => {
+            println!("Your location is required.");
+            return;
+        }
+    };
+
+    println!("Scanning {} messages...", messages.len());
+    let report = match mbox::import(&ctx.conn, ctx.owner_id(), &messages, &filter, &my_location, parsed.has("notes")) {
+        Ok(r) => r,
+        Err(e) => {
+            ctx.print_error(&e);
+            return;
+        }
+    };
+
+    println!("Logged {} email interaction(s).", report.created);
+    if report.skipped > 0 {
+        println!("Skipped {} thread-day(s) already logged.", report.skipped);
+    }
+    if report.filtered > 0 {
+        println!("Ignored {} list, automated, self-sent or unrelated message(s).", report.filtered);
+    }
+    if !report.unmatched.is_empty() {
+        println!();
+        println!("Addresses not matched to anyone ({}):", report.unmatched.len());
+        for (address, count) in report.unmatched.iter().take(20) {
+            println!("  {} — {} message(s)", address, count);
+        }
+        if report.unmatched.len() > 20 {
+            println!("  ... and {} more", report.unmatched.len() - 20);
+        }
+        println!("Add these with edit-email <name>, then import again.");
+    }
+}
+
+fn parse_date_flag(value: Option<&str>) -> Result<Option<NaiveDate>, String> {
+    match value {
+        None => Ok(None),
+        Some(v) => NaiveDate::parse_from_str(v, "%Y-%m-%d")
+            .map(Some)
+            .map_err(|_| format!("Invalid date '{}'. Use YYYY-MM-DD.", v)),
+    }
+}
+
+/// The user's location for imported remote interactions: the flag, then the
+/// self person's location, then a prompt.
+fn my_location(ctx: &CLIContext, flag: Option<&str>) -> Option<String> {
+    if let Some(loc) = flag.filter(|l| !l.trim().is_empty()) {
+        return Some(loc.trim().to_string());
+    }
+    if let Some(loc) = person_repo::find_by_id(&ctx.conn, ctx.self_id)
+        .ok()
+        .flatten()
+        .and_then(|p| p.location)
+        .filter(|l| !l.trim().is_empty())
+    {
+        return Some(loc);
+    }
+    ctx.prompt("Your location: ").filter(|l| !l.is_empty())
+}
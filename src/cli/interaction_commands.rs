@@ -1,4 +1,7 @@
+use crate::cli::args;
 use crate::cli::context::CLIContext;
+use crate::cli::output;
+use crate::error::PrmResult;
 use crate::model::*;
 use crate::ops::*;
 use crate::queries::*;
@@ -18,7 +21,7 @@ pub fn log_for_person(ctx: &CLIContext, person: &Person) {
         println!("  {}. {}", i + 1, medium.display_name());
     }
 
-    let medium_input = ctx.prompt("Medium (1-5): ").unwrap_or_default();
+    let medium_input = ctx.prompt(&format!("Medium (1-{}): ", InteractionMedium::ALL.len())).unwrap_or_default();
     let medium = match medium_input
         .parse::<usize>()
         .ok()
@@ -93,10 +96,10 @@ pub fn log_for_person(ctx: &CLIContext, person: &Person) {
     }
 }
 
-pub fn show_reminders(ctx: &CLIContext) {
+pub fn show_reminders(ctx: &CLIContext) -> PrmResult<()> {
     const DUE_SOON_DAYS: i64 = 7;
     let today = CLIContext::today();
-    let all = reminder_queries::all_reminders(&ctx.conn, ctx.owner_id(), today).unwrap_or_default();
+    let all = reminder_queries::all_reminders(&ctx.conn, ctx.owner_id(), today)?;
 
     let overdue: Vec<_> = all.iter().filter(|s| match &s.overdue_status {
         reminder_queries::OverdueStatus::NeverContacted => true,
@@ -108,9 +111,14 @@ pub fn show_reminders(ctx: &CLIContext) {
         reminder_queries::OverdueStatus::DaysOverdue(d) => *d <= 0 && *d > -DUE_SOON_DAYS,
     }).collect();
 
+    if ctx.json() {
+        output::print_json(&output::Reminders { overdue, due_soon });
+        return Ok(());
+    }
+
     if overdue.is_empty() && due_soon.is_empty() {
         println!("No reminders due. You're all caught up.");
-        return;
+        return Ok(());
     }
 
     if !overdue.is_empty() {
@@ -147,6 +155,7 @@ pub fn show_reminders(ctx: &CLIContext) {
             }
         }
     }
+    Ok(())
 }
 
 pub fn set_reminder(ctx: &CLIContext, args: &str) {
@@ -177,29 +186,132 @@ pub fn set_reminder(ctx: &CLIContext, args: &str) {
     }
 }
 
-pub fn print_stats(ctx: &CLIContext) {
-    match stats_queries::stats(&ctx.conn, ctx.owner_id(), ctx.self_id) {
-        Ok(s) => {
-            println!();
-            println!("People: {} active, {} archived", s.active_people, s.archived_people);
-            if s.never_contacted > 0 {
-                println!("  Never contacted: {}", s.never_contacted);
-            }
-            if s.no_reminder_set > 0 {
-                println!("  No reminder set: {}", s.no_reminder_set);
-            }
-            println!("Interactions: {}", s.total_interactions);
-            if let Some((name, days)) = &s.longest_gap {
-                println!("  Longest gap: {} ({})", name, CLIContext::format_days_ago(*days));
-            }
-            if s.active_circles > 0 {
-                println!("Circles: {} active, {} archived", s.active_circles, s.archived_circles);
+/// `drifting [days] [--label X] [--circle Y] [--sort longest|recent|name]`:
+/// people not contacted in `days` (default 60), whether or not they have a
+/// reminder, with a suggested reminder cadence you can accept by number.
+pub fn drifting(ctx: &CLIContext, args: &str) {
+    const USAGE: &str = "Usage: drifting [days] [--label <name>] [--circle <name>] [--sort longest|recent|name]";
+    const DEFAULT_DAYS: i64 = 60;
+
+    let parsed = args::parse(args);
+    let days = match parsed.positional.trim() {
+        "" => DEFAULT_DAYS,
+        d => match d.parse::<i64>() {
+            Ok(n) if n > 0 => n,
+            _ => return println!("{}", USAGE),
+        },
+    };
+
+    let today = CLIContext::today();
+    let mut people = interaction_queries::not_contacted_in(&ctx.conn, ctx.owner_id(), days, today).unwrap_or_default();
+    people.retain(|(p, _)| p.id != ctx.self_id);
+
+    if let Some(query) = parsed.flag("label") {
+        let Some(label) = ctx.find_label(query) else { return };
+        people.retain(|(p, _)| ctx.labels_for(p.id).iter().any(|l| l.id == label.id));
+    }
+    if let Some(query) = parsed.flag("circle") {
+        let Some(circle) = ctx.find_circle(query) else { return };
+        people.retain(|(p, _)| circle.member_ids.contains(&p.id));
+    }
+
+    // Already sorted longest-first, with never-contacted people at the top
+    match parsed.flag("sort").unwrap_or("longest") {
+        "longest" => {}
+        "recent" => people.reverse(),
+        "name" => people.sort_by_key(|(p, _)| p.name.to_lowercase()),
+        other => return println!("Unknown sort '{}'.\n{}", other, USAGE),
+    }
+
+    if people.is_empty() {
+        println!("Nobody has gone {} days without contact.", days);
+        return;
+    }
+
+    struct Row {
+        person: Person,
+        reminder: Option<i32>,
+        suggested: i32,
+    }
+
+    println!("Not contacted in {}+ days ({}):", days, people.len());
+    let mut rows = Vec::new();
+    for (i, (person, since)) in people.into_iter().enumerate() {
+        let reminder = relationship_queries::get_relationship(&ctx.conn, person.id)
+            .ok()
+            .flatten()
+            .and_then(|r| r.reminder_days);
+        let suggested = reminder_queries::suggested_cadence(&ctx.conn, person.id).unwrap_or(30);
+        let last = since
+            .map(|d| format!("last contact {}", CLIContext::format_days_ago(d)))
+            .unwrap_or_else(|| "never contacted".into());
+        let cadence = match reminder {
+            Some(r) => format!("reminder every {} days", r),
+            None => format!("suggest every {} days", suggested),
+        };
+        println!("  {}. {} - {} - {}", i + 1, person.name, last, cadence);
+        rows.push(Row { person, reminder, suggested });
+    }
+
+    if rows.iter().all(|r| r.reminder.is_some()) {
+        return;
+    }
+    println!();
+    loop {
+        let input = ctx
+            .prompt("Number to set its suggested reminder, 'a' for everyone without one, Enter to finish: ")
+            .unwrap_or_default();
+        if input.is_empty() {
+            return;
+        }
+        let all = input.eq_ignore_ascii_case("a");
+        let chosen: Vec<&Row> = if all {
+            rows.iter().filter(|r| r.reminder.is_none()).collect()
+        } else {
+            match input.parse::<usize>().ok().and_then(|n| rows.get(n.wrapping_sub(1))) {
+                Some(row) => vec![row],
+                None => {
+                    println!("Invalid selection.");
+                    continue;
+                }
             }
-            if s.reminders_overdue > 0 {
-                println!("Reminders overdue: {}", s.reminders_overdue);
+        };
+        for row in chosen {
+            match relationship_ops::set_reminder(&ctx.conn, row.person.id, Some(row.suggested)) {
+                Ok(_) => println!("Reminder set: reach out to {} every {} days", row.person.name, row.suggested),
+                Err(e) => ctx.print_error(&e),
             }
-            println!();
         }
-        Err(e) => ctx.print_error(&e),
+        if all {
+            return;
+        }
+    }
+}
+
+pub fn print_stats(ctx: &CLIContext) -> PrmResult<()> {
+    let s = stats_queries::stats(&ctx.conn, ctx.owner_id(), ctx.self_id)?;
+    if ctx.json() {
+        output::print_json(&s);
+        return Ok(());
+    }
+    println!();
+    println!("People: {} active, {} archived", s.active_people, s.archived_people);
+    if s.never_contacted > 0 {
+        println!("  Never contacted: {}", s.never_contacted);
+    }
+    if s.no_reminder_set > 0 {
+        println!("  No reminder set: {}", s.no_reminder_set);
+    }
+    println!("Interactions: {}", s.total_interactions);
+    if let Some((name, days)) = &s.longest_gap {
+        println!("  Longest gap: {} ({})", name, CLIContext::format_days_ago(*days));
+    }
+    if s.active_circles > 0 {
+        println!("Circles: {} active, {} archived", s.active_circles, s.archived_circles);
+    }
+    if s.reminders_overdue > 0 {
+        println!("Reminders overdue: {}", s.reminders_overdue);
     }
+    println!();
+    Ok(())
 }
@@ -1,22 +1,37 @@
 use crate::cli::context::CLIContext;
+use crate::cli::output;
+use crate::error::PrmResult;
 use crate::model::*;
 use crate::ops::*;
 use crate::queries::*;
 
-pub fn list(ctx: &CLIContext) {
-    let all_labels = relationship_queries::active_labels(&ctx.conn, ctx.owner_id()).unwrap_or_default();
+pub fn list(ctx: &CLIContext) -> PrmResult<()> {
+    let all_labels = relationship_queries::active_labels(&ctx.conn, ctx.owner_id())?;
     let labels: Vec<_> = all_labels.iter().filter(|l| l.name != "me").collect();
+    if ctx.json() {
+        let summaries: Vec<output::LabelSummary> = labels
+            .iter()
+            .map(|l| {
+                Ok(output::LabelSummary {
+                    id: l.id,
+                    name: l.name.clone(),
+                    people: relationship_queries::people_with_label(&ctx.conn, ctx.owner_id(), l.id)?.len(),
+                })
+            })
+            .collect::<PrmResult<_>>()?;
+        output::print_json(&summaries);
+        return Ok(());
+    }
     if labels.is_empty() {
         println!("No labels yet. Use 'add-label <name>' to create one.");
     } else {
         println!("Labels ({}):", labels.len());
         for label in &labels {
-            let count = relationship_queries::people_with_label(&ctx.conn, ctx.owner_id(), label.id)
-                .map(|p| p.len())
-                .unwrap_or(0);
+            let count = relationship_queries::people_with_label(&ctx.conn, ctx.owner_id(), label.id)?.len();
             println!("  {} ({})", label.name, count);
         }
     }
+    Ok(())
 }
 
 pub fn show(ctx: &CLIContext, args: &str) {
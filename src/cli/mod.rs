@@ -1,10 +1,25 @@
+pub mod args;
 pub mod context;
+pub mod editor;
 pub mod person_commands;
 pub mod circle_commands;
 pub mod label_commands;
 pub mod interaction_commands;
 pub mod ai_log_command;
+pub mod ai_add_command;
+pub mod ai_edit_command;
+pub mod ai_eval_command;
+pub mod corrections_command;
+pub mod ask_search_command;
+pub mod summarize_command;
 pub mod voice_log_command;
+pub mod dossier_command;
+pub mod vault_command;
+pub mod import_command;
+pub mod timeline_command;
+pub mod review_command;
+pub mod subcommand;
+pub mod output;
 
 use std::path::Path;
 use rusqlite::Connection;
@@ -13,9 +28,10 @@ use crate::db::{schema, network_repo, person_repo, relationship_repo};
 use crate::model::*;
 use crate::queries::reminder_queries;
 use context::CLIContext;
+use output::OutputFormat;
 
 /// Run the interactive REPL.
-pub fn run(db_path: &Path) {
+pub fn run(db_path: &Path, output: OutputFormat) {
     println!("Personal Relationship Manager");
     println!("Type 'help' for commands, 'exit' to quit.");
     println!();
@@ -37,12 +53,19 @@ pub fn run(db_path: &Path) {
         Some(ctx) => ctx,
         None => return,
     };
+    ctx.set_output(output);
+    ctx.enable_line_editor(history_path(db_path));
 
     show_startup_reminders(&ctx);
 
     repl_loop(&ctx);
 }
 
+/// Command history lives next to the database.
+fn history_path(db_path: &Path) -> std::path::PathBuf {
+    db_path.with_file_name("history.txt")
+}
+
 fn load_or_init(conn: Connection) -> Option<CLIContext> {
     // Check if there's an existing user
     match network_repo::find_first_user(&conn) {
@@ -95,6 +118,15 @@ fn init_new_network(conn: Connection) -> Option<CLIContext> {
         return None;
     }
 
+    let ctx = create_network(conn, &name)?;
+    println!("Welcome, {}! Your network has been created.", name);
+    println!();
+    Some(ctx)
+}
+
+/// Creates the user, their self person, metadata and default labels.
+pub(crate) fn create_network(conn: Connection, name: &str) -> Option<CLIContext> {
+    let name = name.trim().to_string();
     let user = User::create(name.clone(), String::new());
     network_repo::insert_user(&conn, &user).ok()?;
 
@@ -119,9 +151,6 @@ fn init_new_network(conn: Connection) -> Option<CLIContext> {
         let _ = relationship_repo::upsert(&conn, user.id, &rel);
     }
 
-    println!("Welcome, {}! Your network has been created.", name);
-    println!();
-
     Some(CLIContext::new(conn, user, self_person.id))
 }
 
@@ -166,7 +195,7 @@ fn show_startup_reminders(ctx: &CLIContext) {
 
 fn repl_loop(ctx: &CLIContext) {
     loop {
-        let input = match ctx.read_line("> ") {
+        let input = match ctx.read_command("> ") {
             Some(s) => s,
             None => break,
         };
@@ -177,16 +206,19 @@ fn repl_loop(ctx: &CLIContext) {
         }
 
         let (command, args) = parse_command(input);
+        let changes = ctx.total_changes();
 
         match command {
             "help" | "?" => print_help(),
             "quit" | "exit" | "q" => break,
+            "set" => set_option(ctx, args),
 
             // Person commands
-            "people" | "list" | "ls" => person_commands::list(ctx),
+            "people" | "list" | "ls" => person_commands::list(ctx, args),
             "add-person" => person_commands::add(ctx, args),
             "show-person" | "show" | "view" => person_commands::show(ctx, args),
             "history" => person_commands::history(ctx, args),
+            "dossier" => dossier_command::dossier(ctx, args),
             "edit-person" => person_commands::edit(ctx, args),
             "find" => person_commands::find(ctx, args),
             "archive-person" => person_commands::archive(ctx, args),
@@ -206,7 +238,7 @@ fn repl_loop(ctx: &CLIContext) {
             "edit-email" => person_commands::edit_email(ctx, args),
 
             // Circle commands
-            "circles" => circle_commands::list(ctx),
+            "circles" => ctx.report(circle_commands::list(ctx)),
             "add-circle" => circle_commands::add(ctx, args),
             "show-circle" => circle_commands::show(ctx, args),
             "edit-circle" => circle_commands::edit(ctx, args),
@@ -215,7 +247,7 @@ fn repl_loop(ctx: &CLIContext) {
             "archived-circles" => circle_commands::list_archived(ctx),
 
             // Label commands
-            "labels" => label_commands::list(ctx),
+            "labels" => ctx.report(label_commands::list(ctx)),
             "add-label" => label_commands::add(ctx, args),
             "show-label" => label_commands::show(ctx, args),
             "edit-label" => label_commands::edit(ctx, args),
@@ -225,18 +257,53 @@ fn repl_loop(ctx: &CLIContext) {
 
             // Interaction commands
             "log" => interaction_commands::log(ctx, args),
-            "remind" | "reminders" => interaction_commands::show_reminders(ctx),
+            "remind" | "reminders" => ctx.report(interaction_commands::show_reminders(ctx)),
             "set-reminder" => interaction_commands::set_reminder(ctx, args),
+            "timeline" => timeline_command::timeline(ctx, args),
+            "drifting" => interaction_commands::drifting(ctx, args),
+            "review" => review_command::review(ctx, args),
 
             // AI-assisted
             "ai-log" => ai_log_command::ai_log(ctx, args),
+            "ai-add" => ai_add_command::ai_add(ctx, args),
+            "ai-edit" => ai_edit_command::ai_edit(ctx, args),
             "voice-log" => voice_log_command::voice_log(ctx, args),
+            "ai-eval" => ai_eval_command::ai_eval(ctx, args),
+            "corrections" => corrections_command::corrections(ctx, args),
+            "ask-search" => ask_search_command::ask_search(ctx, args),
+            "summarize" => summarize_command::summarize(ctx, args),
+
+            // Import
+            "import-whatsapp" => import_command::import_whatsapp(ctx, args),
+            "import-android" => import_command::import_android(ctx, args),
+            "import-ics" => import_command::import_ics(ctx, args),
+            "import-mbox" => import_command::import_mbox(ctx, args),
 
             // Other
-            "stats" => interaction_commands::print_stats(ctx),
+            "stats" => ctx.report(interaction_commands::print_stats(ctx)),
+            "vault-sync" => vault_command::vault_sync(ctx, args),
 
             _ => println!("Unknown command: {}. Type 'help' for commands.", command),
         }
+
+        if ctx.total_changes() != changes {
+            ask_search_command::update_index_after_command(ctx);
+        }
+    }
+}
+
+/// `set output json|text`
+fn set_option(ctx: &CLIContext, args: &str) {
+    let (option, value) = parse_command(args);
+    match option {
+        "output" => match OutputFormat::parse(value) {
+            Some(format) => {
+                ctx.set_output(format);
+                println!("Output set to {}.", value.trim().to_lowercase());
+            }
+            None => println!("Usage: set output json|text"),
+        },
+        _ => println!("Usage: set output json|text"),
     }
 }
 
@@ -254,10 +321,19 @@ fn print_help() {
 COMMANDS:
 
   People:
-    people                  List all people
+    people [filter]         List people, optionally filtered and sorted, e.g.
+                              people label:friend circle:"book club" last>60d
+                              (label: circle: last>Nd last<Nd last:never medium:
+                               location: has: birthday:nextNd sort: -term negates)
     add-person [name]       Add a new person (interactive)
+    ai-add <description>    Add a person from a description via AI, with contacts,
+                              labels and circles, reviewed before saving
+    ai-edit <note>          Update a person from a note via AI, e.g. "Alice moved
+                              to Berlin", reviewed before applying
     show-person <name>      Show person details
     history <name>          Show full interaction history
+    dossier <name>          Export a one-pager (--format md|html, --out <dir>)
+    dossier --circle <name> Export one dossier per circle member
     edit-person <name>      Edit a person (menu)
     find <query>            Search people, circles, and labels
     archive-person <name>   Archive a person
@@ -298,15 +374,45 @@ COMMANDS:
     log <name>              Log an interaction (manual prompts)
     ai-log <description>    Log via AI (natural language)
     voice-log <wav-file>    Log via voice recording (local Whisper transcription)
+    ai-eval <dataset>       Score the AI parser on labeled examples (a .jsonl file
+                              or "corrections") [--model <name>] [--prompt <file>]
+                              [--vs-model <name>] [--vs-prompt <file>] [--save <file>]
+    ask-search <question>   Find interactions and notes by meaning, using local
+                              embeddings [--limit N]
+    summarize <name>        AI briefing: what you know about someone and open threads,
+                              citing interaction dates [--refresh]
+    corrections [action]    Manage past AI corrections: list [--pinned], show <id>,
+                              delete <id>, pin <id>, unpin <id>, export <file>,
+                              import <file>
     remind                  Show overdue reminders
     set-reminder <name>     Set reminder frequency
+    drifting [days]         People not contacted in N days (default 60), with
+                              suggested reminders [--label <name>] [--circle <name>]
+                              [--sort longest|recent|name]
+    timeline                Interactions over time, by week or month
+                              [--from YYYY-MM-DD] [--to YYYY-MM-DD] [--circle <name>]
+                              [--label <name>] [--medium <medium>] [--by week|month]
+    review                  Weekly review: this week's interactions, newly overdue
+                              people, new people to file, upcoming birthdays
+                              (resumes where you stopped)
+
+  Import:
+    import-whatsapp <file> <name>  Log texts from a WhatsApp chat export
+    import-android <xml files>     Log calls/texts from an Android SMS Backup & Restore export
+    import-ics <file>              Review past calendar events as interactions
+    import-mbox <file>             Log email threads from an mbox archive
 
   Other:
     stats                   Show statistics
+    vault-sync <dir>        Two-way sync with a folder of <name>.md files
+    set output json|text    Print listings as JSON or text
     help                    Show this help
     exit / quit / q         Exit
 
 TIPS:
-  - Names are case-insensitive and partial matches work
+  - Names are case- and accent-insensitive; partial names, small typos and
+    common nicknames (Bill for William) match; if several match, pick one from the list
+  - Tab completes commands and the names of people, circles and labels
+  - Up/down arrows recall earlier commands (kept across sessions)
   - Press 's' during add-person to save and exit early"#);
 }
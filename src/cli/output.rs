@@ -0,0 +1,91 @@
+use std::io::Write;
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::model::*;
+use crate::queries::reminder_queries::ReminderStatus;
+
+/// How listing commands print their results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+impl OutputFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "text" => Some(OutputFormat::Text),
+            "json" => Some(OutputFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Prints a value as pretty JSON on stdout. Write errors (e.g. a closed pipe
+/// when piped into `head`) are ignored.
+pub fn print_json<T: Serialize + ?Sized>(value: &T) {
+    match serde_json::to_string_pretty(value) {
+        Ok(json) => {
+            let _ = writeln!(std::io::stdout().lock(), "{}", json);
+        }
+        Err(e) => eprintln!("Error: {}", e),
+    }
+}
+
+/// One entry of `people`.
+#[derive(Debug, Serialize)]
+pub struct PersonSummary {
+    pub id: Id<Person>,
+    pub name: String,
+    pub nickname: Option<String>,
+    pub location: Option<String>,
+    pub labels: Vec<String>,
+    pub last_contact: Option<NaiveDate>,
+    pub days_since_last_contact: Option<i64>,
+}
+
+/// `show-person`: the person's fields plus everything attached to them.
+#[derive(Debug, Serialize)]
+pub struct PersonDetail {
+    #[serde(flatten)]
+    pub person: Person,
+    pub labels: Vec<String>,
+    pub circles: Vec<String>,
+    pub contacts: Vec<ContactEntry>,
+    pub reminder_days: Option<i32>,
+    pub interactions: Vec<Interaction>,
+}
+
+/// `history`
+#[derive(Debug, Serialize)]
+pub struct History {
+    pub person_id: Id<Person>,
+    pub name: String,
+    pub interactions: Vec<Interaction>,
+}
+
+/// `remind`
+#[derive(Debug, Serialize)]
+pub struct Reminders<'a> {
+    pub overdue: Vec<&'a ReminderStatus>,
+    pub due_soon: Vec<&'a ReminderStatus>,
+}
+
+/// One entry of `labels`.
+#[derive(Debug, Serialize)]
+pub struct LabelSummary {
+    pub id: Id<RelationshipLabel>,
+    pub name: String,
+    pub people: usize,
+}
+
+/// One entry of `circles`.
+#[derive(Debug, Serialize)]
+pub struct CircleSummary {
+    pub id: Id<Circle>,
+    pub name: String,
+    pub description: Option<String>,
+    pub members: Vec<String>,
+}
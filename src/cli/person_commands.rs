@@ -1,20 +1,65 @@
 use chrono::NaiveDate;
 
 use crate::cli::context::CLIContext;
+use crate::cli::output;
 use crate::db::person_repo;
+use crate::error::PrmResult;
+use crate::matching;
 use crate::model::*;
 use crate::ops::*;
 use crate::queries::*;
+use crate::queries::person_filter::PersonFilter;
 
-pub fn list(ctx: &CLIContext) {
-    let all = person_queries::active_people(&ctx.conn, ctx.owner_id()).unwrap_or_default();
-    let people: Vec<_> = all.iter().filter(|p| p.id != ctx.self_id).collect();
+/// `people [filter]`, e.g. `people label:friend last>60d sort:last-contact`.
+pub fn list(ctx: &CLIContext, args: &str) {
+    match PersonFilter::parse(args) {
+        Ok(filter) => ctx.report(print_people(ctx, &filter)),
+        Err(e) => {
+            println!("{}", e);
+            println!("Usage: people [{}]", person_filter::SYNTAX);
+        }
+    }
+}
+
+/// Lists the people matching `filter` (everyone, for an empty filter).
+pub fn print_people(ctx: &CLIContext, filter: &PersonFilter) -> PrmResult<()> {
+    let people = person_filter::filter_people(&ctx.conn, ctx.owner_id(), filter, CLIContext::today())?;
+    if ctx.json() {
+        let today = CLIContext::today();
+        let summaries: Vec<output::PersonSummary> = people
+            .iter()
+            .map(|p| {
+                let last_contact = interaction_queries::last_interaction_date(&ctx.conn, p.id)?;
+                let mut labels: Vec<String> = ctx.labels_for(p.id).into_iter().map(|l| l.name).collect();
+                labels.sort();
+                Ok(output::PersonSummary {
+                    id: p.id,
+                    name: p.name.clone(),
+                    nickname: p.nickname.clone(),
+                    location: p.location.clone(),
+                    labels,
+                    last_contact,
+                    days_since_last_contact: last_contact.map(|d| (today - d).num_days()),
+                })
+            })
+            .collect::<PrmResult<_>>()?;
+        output::print_json(&summaries);
+        return Ok(());
+    }
     if people.is_empty() {
-        println!("No people in your network yet. Use 'add-person' to add someone.");
-        return;
+        if filter.terms.is_empty() {
+            println!("No people in your network yet. Use 'add-person' to add someone.");
+        } else {
+            println!("No people match that filter.");
+        }
+        return Ok(());
     }
 
-    println!("People in your network ({}):", people.len());
+    if filter.terms.is_empty() {
+        println!("People in your network ({}):", people.len());
+    } else {
+        println!("{} people match:", people.len());
+    }
     println!();
     for person in &people {
         let labels = ctx.labels_for(person.id);
@@ -30,13 +75,14 @@ pub fn list(ctx: &CLIContext) {
             &ctx.conn,
             person.id,
             CLIContext::today(),
-        ).ok().flatten() {
+        )? {
             Some(d) => format!(" - last contact: {}", CLIContext::format_days_ago(d)),
             None => " - never contacted".to_string(),
         };
 
         println!("  {}{}{}", person.name, label_str, last_contact);
     }
+    Ok(())
 }
 
 pub fn add(ctx: &CLIContext, args: &str) {
@@ -175,6 +221,28 @@ pub fn show(ctx: &CLIContext, args: &str) {
         Some(p) => p,
         None => return,
     };
+    print_person(ctx, &person);
+}
+
+/// Prints a person's profile and their latest interactions.
+pub fn print_person(ctx: &CLIContext, person: &Person) {
+    if ctx.json() {
+        let mut labels: Vec<String> = ctx.labels_for(person.id).into_iter().map(|l| l.name).collect();
+        labels.sort();
+        let detail = output::PersonDetail {
+            person: person.clone(),
+            labels,
+            circles: ctx.circles_for(person.id).into_iter().map(|c| c.name).collect(),
+            contacts: ctx.contacts_for(person.id),
+            reminder_days: relationship_queries::get_relationship(&ctx.conn, person.id)
+                .ok()
+                .flatten()
+                .and_then(|r| r.reminder_days),
+            interactions: interaction_queries::interactions_with(&ctx.conn, person.id).unwrap_or_default(),
+        };
+        output::print_json(&detail);
+        return;
+    }
 
     println!();
     println!("Name: {}", person.name);
@@ -242,9 +310,22 @@ pub fn history(ctx: &CLIContext, args: &str) {
         Some(p) => p,
         None => return,
     };
+    print_history(ctx, &person);
+}
 
+/// Prints every interaction with a person, newest first.
+pub fn print_history(ctx: &CLIContext, person: &Person) {
     let interactions = interaction_queries::interactions_with(&ctx.conn, person.id).unwrap_or_default();
 
+    if ctx.json() {
+        output::print_json(&output::History {
+            person_id: person.id,
+            name: person.name.clone(),
+            interactions,
+        });
+        return;
+    }
+
     if interactions.is_empty() {
         println!("No interactions logged with {}.", person.name);
         return;
@@ -350,12 +431,17 @@ pub fn find(ctx: &CLIContext, args: &str) {
         return;
     }
 
-    let lower = args.to_lowercase();
     let people = person_queries::find_by_name(&ctx.conn, ctx.owner_id(), args).unwrap_or_default();
     let all_circles = circle_queries::active_circles(&ctx.conn, ctx.owner_id()).unwrap_or_default();
-    let circles: Vec<&Circle> = all_circles.iter().filter(|c| c.name.to_lowercase().contains(&lower)).collect();
+    let circles: Vec<Circle> = matching::rank(args, all_circles, |c| vec![c.name.clone()])
+        .into_iter()
+        .map(|(c, _)| c)
+        .collect();
     let all_labels = relationship_queries::active_labels(&ctx.conn, ctx.owner_id()).unwrap_or_default();
-    let labels: Vec<&RelationshipLabel> = all_labels.iter().filter(|l| l.name.to_lowercase().contains(&lower)).collect();
+    let labels: Vec<RelationshipLabel> = matching::rank(args, all_labels, |l| vec![l.name.clone()])
+        .into_iter()
+        .map(|(l, _)| l)
+        .collect();
 
     let total = people.len() + circles.len() + labels.len();
     if total == 0 {
@@ -717,7 +803,7 @@ fn edit_emails_cmd(ctx: &CLIContext, person: &Person) {
     }
 }
 
-fn select_labels(ctx: &CLIContext, person_id: Id<Person>) {
+pub fn select_labels(ctx: &CLIContext, person_id: Id<Person>) {
     let all_labels = relationship_queries::active_labels(&ctx.conn, ctx.owner_id()).unwrap_or_default();
     if all_labels.is_empty() {
         println!("No labels available.");
@@ -770,7 +856,7 @@ fn select_labels(ctx: &CLIContext, person_id: Id<Person>) {
     }
 }
 
-fn select_circles(ctx: &CLIContext, person_id: Id<Person>) {
+pub fn select_circles(ctx: &CLIContext, person_id: Id<Person>) {
     let all_circles = circle_queries::active_circles(&ctx.conn, ctx.owner_id()).unwrap_or_default();
     if all_circles.is_empty() {
         println!("No circles available.");
@@ -865,7 +951,7 @@ fn add_emails_loop(ctx: &CLIContext, person_id: Id<Person>) {
     }
 }
 
-fn set_reminder_for(ctx: &CLIContext, person_id: Id<Person>) {
+pub fn set_reminder_for(ctx: &CLIContext, person_id: Id<Person>) {
     let input = match ctx.prompt("Remind every how many days (0 to remove): ") {
         Some(s) => s,
         None => return,
@@ -0,0 +1,400 @@
+use chrono::{Datelike, Duration, NaiveDate};
+
+use crate::cli::context::CLIContext;
+use crate::cli::interaction_commands;
+use crate::cli::person_commands;
+use crate::db::review_repo::{self, ReviewProgress};
+use crate::model::{Id, Person};
+use crate::ops::{interaction_ops, relationship_ops};
+use crate::queries::person_filter::{self, PersonFilter, SortKey, Term};
+use crate::queries::person_queries;
+use crate::queries::relationship_queries;
+use crate::queries::reminder_queries::{self, OverdueStatus};
+use crate::queries::timeline_queries::{self, Period, TimelineFilter};
+
+/// Days covered by a review, ending on the day it started.
+const WINDOW_DAYS: i64 = 7;
+
+/// How far ahead birthdays are listed.
+const BIRTHDAY_DAYS: i64 = 14;
+
+/// Default snooze length, in days.
+const SNOOZE_DAYS: i64 = 7;
+
+const SECTIONS: &[&str] = &["Interactions this week", "Newly overdue", "New people", "Upcoming birthdays"];
+
+/// Whether to go on to the next item or stop and keep the progress for later.
+enum Step {
+    Next,
+    Quit,
+}
+
+/// `review` — step through the past week: interactions missing topics or
+/// notes, people who just became overdue, people added without labels,
+/// circles or a reminder, and upcoming birthdays. Progress is saved after
+/// each item, so `q` (or Ctrl-D) leaves a review that `review` resumes.
+pub fn review(ctx: &CLIContext, _args: &str) {
+    let today = CLIContext::today();
+    let saved = match review_repo::find_by_owner(&ctx.conn, ctx.owner_id()) {
+        Ok(p) => p,
+        Err(e) => return ctx.print_error(&e),
+    };
+
+    let mut progress = match saved {
+        Some(p) if p.section < SECTIONS.len() => {
+            let answer = ctx
+                .prompt(&format!(
+                    "Resume the review started on {} ({})? (Y/n): ",
+                    p.started_on,
+                    SECTIONS[p.section]
+                ))
+                .unwrap_or_default();
+            if answer.eq_ignore_ascii_case("n") {
+                ReviewProgress { started_on: today, section: 0, done: Vec::new() }
+            } else {
+                p
+            }
+        }
+        _ => ReviewProgress { started_on: today, section: 0, done: Vec::new() },
+    };
+    if !save(ctx, &progress) {
+        return;
+    }
+
+    let from = progress.started_on - Duration::days(WINDOW_DAYS - 1);
+    println!("Weekly review: {} to {}", from, progress.started_on);
+    println!("At each item press Enter to move on, or q to stop and resume later.");
+
+    while progress.section < SECTIONS.len() {
+        println!();
+        println!("== {} ({}/{}) ==", SECTIONS[progress.section], progress.section + 1, SECTIONS.len());
+        let step = match progress.section {
+            0 => review_interactions(ctx, &mut progress),
+            1 => review_overdue(ctx, &mut progress),
+            2 => review_new_people(ctx, &mut progress),
+            _ => show_birthdays(ctx, &progress),
+        };
+        if let Step::Quit = step {
+            println!("Review paused. Run 'review' to pick up where you left off.");
+            return;
+        }
+        progress.section += 1;
+        progress.done.clear();
+        if !save(ctx, &progress) {
+            return;
+        }
+    }
+
+    if let Err(e) = review_repo::delete(&ctx.conn, ctx.owner_id()) {
+        return ctx.print_error(&e);
+    }
+    println!();
+    println!("Review done.");
+}
+
+/// Interactions in the window, to add topics or a note. A group event is
+/// shown, and edited, once.
+fn review_interactions(ctx: &CLIContext, progress: &mut ReviewProgress) -> Step {
+    let to = progress.started_on;
+    let from = to - Duration::days(WINDOW_DAYS - 1);
+    let periods = match timeline_queries::timeline(
+        &ctx.conn,
+        ctx.owner_id(),
+        from,
+        to,
+        &TimelineFilter::default(),
+        Period::Week,
+    ) {
+        Ok(p) => p,
+        Err(e) => {
+            ctx.print_error(&e);
+            return Step::Quit;
+        }
+    };
+    let events: Vec<_> = periods
+        .into_iter()
+        .flat_map(|p| p.events)
+        .filter(|e| !progress.done.contains(&key_of(&e.interaction_ids[0])))
+        .collect();
+    if events.is_empty() {
+        println!("Nothing to review.");
+        return Step::Next;
+    }
+
+    let total = events.len();
+    for (i, event) in events.into_iter().enumerate() {
+        let names: Vec<&str> = event.people.iter().map(|p| p.name.as_str()).collect();
+        println!();
+        println!(
+            "[{}/{}] {}  {}  {} @ {}",
+            i + 1,
+            total,
+            event.date.format("%a %b %e"),
+            event.medium.display_name(),
+            names.join(", "),
+            event.my_location
+        );
+        println!("  Topics: {}", event.topics.join(", "));
+        println!("  Note:   {}", event.note.as_deref().unwrap_or("(none)"));
+
+        let mut topics = event.topics.clone();
+        let mut note = event.note.clone();
+        loop {
+            let input = match ctx.prompt("  [t] add topics, [n] note, Enter for next, [q] quit: ") {
+                Some(s) => s.to_lowercase(),
+                None => return Step::Quit,
+            };
+            match input.as_str() {
+                "" => break,
+                "q" => return Step::Quit,
+                "t" => {
+                    let Some(added) = ctx.prompt("  Topics to add (comma-separated): ") else { return Step::Quit };
+                    for topic in added.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+                        if !topics.iter().any(|t| t.eq_ignore_ascii_case(topic)) {
+                            topics.push(topic.to_string());
+                        }
+                    }
+                }
+                "n" => {
+                    let Some(input) = ctx.prompt("  Note (blank to clear): ") else { return Step::Quit };
+                    note = Some(input);
+                }
+                _ => {
+                    println!("  Unknown choice.");
+                    continue;
+                }
+            }
+            for id in &event.interaction_ids {
+                if let Err(e) = interaction_ops::update_details(&ctx.conn, *id, topics.clone(), note.as_deref()) {
+                    ctx.print_error(&e);
+                    break;
+                }
+            }
+            println!("  Topics: {}", topics.join(", "));
+            println!("  Note:   {}", note.as_deref().filter(|n| !n.is_empty()).unwrap_or("(none)"));
+        }
+
+        if !mark_done(ctx, progress, key_of(&event.interaction_ids[0])) {
+            return Step::Quit;
+        }
+    }
+    Step::Next
+}
+
+/// People whose reminder fell due within the window: log an interaction,
+/// snooze the reminder or change its cadence.
+fn review_overdue(ctx: &CLIContext, progress: &mut ReviewProgress) -> Step {
+    let as_of = progress.started_on;
+    let overdue = match reminder_queries::people_needing_reminder(&ctx.conn, ctx.owner_id(), as_of) {
+        Ok(list) => list,
+        Err(e) => {
+            ctx.print_error(&e);
+            return Step::Quit;
+        }
+    };
+    let newly: Vec<_> = overdue
+        .into_iter()
+        .filter(|s| matches!(s.overdue_status, OverdueStatus::DaysOverdue(d) if d <= WINDOW_DAYS))
+        .filter(|s| !progress.done.contains(&key_of(&s.person.id)))
+        .collect();
+    if newly.is_empty() {
+        println!("Nobody became overdue this week.");
+        return Step::Next;
+    }
+
+    let total = newly.len();
+    for (i, status) in newly.into_iter().enumerate() {
+        let last = status
+            .days_since_last_interaction
+            .map(CLIContext::format_days_ago)
+            .unwrap_or_default();
+        let days = match status.overdue_status {
+            OverdueStatus::DaysOverdue(d) => d,
+            OverdueStatus::NeverContacted => 0,
+        };
+        println!();
+        println!(
+            "[{}/{}] {} - {} day(s) overdue (every {} days, last contact {})",
+            i + 1,
+            total,
+            status.person.name,
+            days,
+            status.reminder_days,
+            last
+        );
+
+        let input = match ctx.prompt("  [l] log, [s] snooze, [c] change cadence, Enter to skip, [q] quit: ") {
+            Some(s) => s.to_lowercase(),
+            None => return Step::Quit,
+        };
+        match input.as_str() {
+            "q" => return Step::Quit,
+            "l" => interaction_commands::log_for_person(ctx, &status.person),
+            "s" => snooze(ctx, &status.person),
+            "c" => person_commands::set_reminder_for(ctx, status.person.id),
+            _ => {}
+        }
+
+        if !mark_done(ctx, progress, key_of(&status.person.id)) {
+            return Step::Quit;
+        }
+    }
+    Step::Next
+}
+
+fn snooze(ctx: &CLIContext, person: &Person) {
+    let input = ctx
+        .prompt(&format!("  Snooze for how many days? [{}]: ", SNOOZE_DAYS))
+        .unwrap_or_default();
+    let days = if input.is_empty() {
+        SNOOZE_DAYS
+    } else {
+        match input.parse::<i64>() {
+            Ok(d) if d > 0 => d,
+            _ => return println!("  Invalid number."),
+        }
+    };
+    let until = CLIContext::today() + Duration::days(days);
+    match relationship_ops::snooze_reminder(&ctx.conn, person.id, until) {
+        Ok(()) => println!("  Snoozed {} until {}.", person.name, until),
+        Err(e) => ctx.print_error(&e),
+    }
+}
+
+/// People added in the window who have no labels, no circles or no reminder.
+fn review_new_people(ctx: &CLIContext, progress: &mut ReviewProgress) -> Step {
+    let since = progress.started_on - Duration::days(WINDOW_DAYS - 1);
+    let people = match person_queries::added_since(&ctx.conn, ctx.owner_id(), since) {
+        Ok(list) => list,
+        Err(e) => {
+            ctx.print_error(&e);
+            return Step::Quit;
+        }
+    };
+    let pending: Vec<Person> = people
+        .into_iter()
+        .filter(|p| !progress.done.contains(&key_of(&p.id)))
+        .filter(|p| !missing(ctx, p).is_empty())
+        .collect();
+    if pending.is_empty() {
+        println!("Everyone added this week is filed.");
+        return Step::Next;
+    }
+
+    let total = pending.len();
+    for (i, person) in pending.into_iter().enumerate() {
+        println!();
+        println!("[{}/{}] {}", i + 1, total, person.name);
+        loop {
+            let gaps = missing(ctx, &person);
+            if gaps.is_empty() {
+                break;
+            }
+            println!("  Missing: {}", gaps.join(", "));
+            let input = match ctx.prompt("  [l] labels, [c] circles, [r] reminder, Enter for next, [q] quit: ") {
+                Some(s) => s.to_lowercase(),
+                None => return Step::Quit,
+            };
+            match input.as_str() {
+                "" => break,
+                "q" => return Step::Quit,
+                "l" => person_commands::select_labels(ctx, person.id),
+                "c" => person_commands::select_circles(ctx, person.id),
+                "r" => person_commands::set_reminder_for(ctx, person.id),
+                _ => println!("  Unknown choice."),
+            }
+        }
+
+        if !mark_done(ctx, progress, key_of(&person.id)) {
+            return Step::Quit;
+        }
+    }
+    Step::Next
+}
+
+/// What a new person still lacks: "labels", "circles", "reminder".
+fn missing(ctx: &CLIContext, person: &Person) -> Vec<&'static str> {
+    let mut gaps = Vec::new();
+    if ctx.labels_for(person.id).is_empty() {
+        gaps.push("labels");
+    }
+    if ctx.circles_for(person.id).is_empty() {
+        gaps.push("circles");
+    }
+    let has_reminder = relationship_queries::get_relationship(&ctx.conn, person.id)
+        .ok()
+        .flatten()
+        .is_some_and(|rel| rel.reminder_days.is_some());
+    if !has_reminder {
+        gaps.push("reminder");
+    }
+    gaps
+}
+
+fn show_birthdays(ctx: &CLIContext, progress: &ReviewProgress) -> Step {
+    let today = progress.started_on;
+    let filter = PersonFilter {
+        terms: vec![Term::BirthdayWithin(BIRTHDAY_DAYS)],
+        sort: SortKey::Birthday,
+        descending: false,
+    };
+    let people = match person_filter::filter_people(&ctx.conn, ctx.owner_id(), &filter, today) {
+        Ok(list) => list,
+        Err(e) => {
+            ctx.print_error(&e);
+            return Step::Quit;
+        }
+    };
+    if people.is_empty() {
+        println!("No birthdays in the next {} days.", BIRTHDAY_DAYS);
+        return Step::Next;
+    }
+
+    for person in &people {
+        if let Some(next) = person.birthday.map(|b| next_birthday(b, today)) {
+            let days = (next - today).num_days();
+            let when = match days {
+                0 => "today".to_string(),
+                1 => "tomorrow".to_string(),
+                d => format!("in {} days", d),
+            };
+            println!("  {}  {} ({})", next.format("%a %b %e"), person.name, when);
+        }
+    }
+    Step::Next
+}
+
+/// The first birthday on or after `today`; 29 February falls on 1 March in
+/// other years.
+fn next_birthday(birthday: NaiveDate, today: NaiveDate) -> NaiveDate {
+    let in_year = |year: i32| {
+        NaiveDate::from_ymd_opt(year, birthday.month(), birthday.day())
+            .or_else(|| NaiveDate::from_ymd_opt(year, 3, 1))
+            .unwrap_or(today)
+    };
+    let this_year = in_year(today.year());
+    if this_year >= today {
+        this_year
+    } else {
+        in_year(today.year() + 1)
+    }
+}
+
+fn key_of<T>(id: &Id<T>) -> String {
+    id.to_string()
+}
+
+fn mark_done(ctx: &CLIContext, progress: &mut ReviewProgress, key: String) -> bool {
+    progress.done.push(key);
+    save(ctx, progress)
+}
+
+fn save(ctx: &CLIContext, progress: &ReviewProgress) -> bool {
+    match review_repo::upsert(&ctx.conn, ctx.owner_id(), progress) {
+        Ok(()) => true,
+        Err(e) => {
+            ctx.print_error(&e);
+            false
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use std::path::Path;
+
+use chrono::NaiveDate;
+use rusqlite::Connection;
+
+use crate::cli::args::{self, ParsedArgs};
+use crate::cli::context::CLIContext;
+use crate::cli::output::OutputFormat;
+use crate::cli::{ask_search_command, circle_commands, interaction_commands, label_commands, person_commands};
+use crate::db::{network_repo, person_repo, schema};
+use crate::error::PrmResult;
+use crate::model::*;
+use crate::ops::interaction_ops;
+use crate::queries::person_filter::{self, PersonFilter};
+
+/// Exit codes for non-interactive subcommands.
+pub const EXIT_OK: i32 = 0;
+pub const EXIT_ERROR: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+pub const EXIT_NOT_FOUND: i32 = 3;
+
+/// Names of the subcommands `main` hands off to `run`.
+pub const COMMANDS: &[&str] = &[
+    "init", "people", "show", "history", "log", "remind", "stats", "circles", "labels",
+];
+
+/// Runs one subcommand against the database and returns the process exit
+/// code. Subcommands never prompt: missing or ambiguous input is an error.
+/// `--json` is accepted after the command name as well as before it.
+pub fn run(db_path: &Path, command: &str, mut raw_args: Vec<String>, mut output: OutputFormat) -> i32 {
+    if let Some(pos) = raw_args.iter().position(|a| a == "--json") {
+        raw_args.remove(pos);
+        output = OutputFormat::Json;
+    }
+    let parsed = args::parse_tokens(raw_args);
+
+    let conn = match open(db_path) {
+        Ok(c) => c,
+        Err(msg) => return fail(EXIT_ERROR, &msg),
+    };
+
+    if command == "init" {
+        return init(conn, &parsed);
+    }
+
+    let ctx = match load(conn) {
+        Ok(ctx) => ctx,
+        Err(msg) => return fail(EXIT_ERROR, &msg),
+    };
+    ctx.set_output(output);
+
+    match command {
+        "people" => match PersonFilter::parse_terms(&parsed.words) {
+            Ok(filter) => listed(person_commands::print_people(&ctx, &filter)),
+            Err(e) => fail(EXIT_USAGE, &format!("{}\nUsage: prm people [{}]", e, person_filter::SYNTAX)),
+        },
+        "show" => with_person(&ctx, &parsed, "show <name>", |p| person_commands::print_person(&ctx, p)),
+        "history" => with_person(&ctx, &parsed, "history <name>", |p| person_commands::print_history(&ctx, p)),
+        "log" => log(&ctx, &parsed),
+        "remind" => listed(interaction_commands::show_reminders(&ctx)),
+        "stats" => listed(interaction_commands::print_stats(&ctx)),
+        "circles" => listed(circle_commands::list(&ctx)),
+        "labels" => listed(label_commands::list(&ctx)),
+        other => fail(EXIT_USAGE, &format!("Unknown command: {}. Use --help for usage.", other)),
+    }
+}
+
+fn init(conn: Connection, parsed: &ParsedArgs) -> i32 {
+    if parsed.positional.trim().is_empty() {
+        return fail(EXIT_USAGE, "Usage: prm init <your name>");
+    }
+    if let Ok(Some(_)) = network_repo::find_first_user(&conn) {
+        return fail(EXIT_ERROR, "A network already exists in this database.");
+    }
+    match super::create_network(conn, &parsed.positional) {
+        Some(_) => {
+            println!("Created network for {}.", parsed.positional.trim());
+            EXIT_OK
+        }
+        None => fail(EXIT_ERROR, "Could not create the network."),
+    }
+}
+
+/// `log <name> [--medium M] --topics a,b [--date YYYY-MM-DD] [--location L]
+/// [--their-location L] [--note N]`
+fn log(ctx: &CLIContext, parsed: &ParsedArgs) -> i32 {
+    const USAGE: &str = "Usage: prm log <name> --topics a,b [--medium in-person|text|phone|video|social|email] \
+                         [--date YYYY-MM-DD] [--location <place>] [--their-location <place>] [--note <text>]";
+
+    let person = match resolve_person(ctx, &parsed.positional, USAGE) {
+        Ok(p) => p,
+        Err(code) => return code,
+    };
+
+    let medium = match parsed.flag("medium") {
+        None => InteractionMedium::InPerson,
+        Some(m) => match InteractionMedium::parse(m) {
+            Some(medium) => medium,
+            None => return fail(EXIT_USAGE, &format!("Unknown medium '{}'.\n{}", m, USAGE)),
+        },
+    };
+
+    let topics: Vec<String> = parsed
+        .flag("topics")
+        .unwrap_or_default()
+        .split(',')
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect();
+    if topics.is_empty() {
+        return fail(EXIT_USAGE, &format!("--topics is required.\n{}", USAGE));
+    }
+
+    let date = match parsed.flag("date") {
+        None => CLIContext::today(),
+        Some(d) => match NaiveDate::parse_from_str(d, "%Y-%m-%d") {
+            Ok(date) => date,
+            Err(_) => return fail(EXIT_USAGE, &format!("Invalid date '{}'. Use YYYY-MM-DD.", d)),
+        },
+    };
+
+    // Remote interactions default to your own location
+    let location = parsed
+        .flag("location")
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.to_string())
+        .or_else(|| {
+            if medium == InteractionMedium::InPerson {
+                return None;
+            }
+            person_repo::find_by_id(&ctx.conn, ctx.self_id)
+                .ok()
+                .flatten()
+                .and_then(|p| p.location)
+        });
+    let location = match location {
+        Some(l) => l,
+        None => return fail(EXIT_USAGE, &format!("--location is required.\n{}", USAGE)),
+    };
+    let note = parsed.flag("note").filter(|n| !n.is_empty());
+
+    let result = if medium == InteractionMedium::InPerson {
+        interaction_ops::log_in_person(&ctx.conn, ctx.owner_id(), person.id, &location, topics, note, date)
+    } else {
+        let their_location = parsed
+            .flag("their-location")
+            .filter(|l| !l.is_empty())
+            .or(person.location.as_deref());
+        interaction_ops::log_remote(
+            &ctx.conn, ctx.owner_id(), person.id, medium, &location, their_location, topics, note, date,
+        )
+    };
+
+    match result {
+        Ok(_) => {
+            println!("Logged {} interaction with {} on {}", medium.display_name(), person.name, date);
+            if let Some(Err(e)) = ask_search_command::update_index(ctx, |_, _| {}) {
+                eprintln!("Search index not updated ({}); ask-search will catch up.", e);
+            }
+            EXIT_OK
+        }
+        Err(e) => fail(EXIT_ERROR, &format!("Error: {}", e)),
+    }
+}
+
+fn with_person(ctx: &CLIContext, parsed: &ParsedArgs, usage: &str, f: impl FnOnce(&Person)) -> i32 {
+    match resolve_person(ctx, &parsed.positional, &format!("Usage: prm {}", usage)) {
+        Ok(person) => {
+            f(&person);
+            EXIT_OK
+        }
+        Err(code) => code,
+    }
+}
+
+/// Resolves exactly one person, or reports why not and returns the exit code.
+fn resolve_person(ctx: &CLIContext, query: &str, usage: &str) -> Result<Person, i32> {
+    if query.trim().is_empty() {
+        return Err(fail(EXIT_USAGE, usage));
+    }
+    let matches = ctx.person_matches(query);
+    match matches.len() {
+        0 => Err(fail(EXIT_NOT_FOUND, &format!("No person found matching '{}'", query.trim()))),
+        1 => Ok(matches.into_iter().next().unwrap()),
+        _ => {
+            let names: Vec<&str> = matches.iter().map(|p| p.name.as_str()).collect();
+            Err(fail(
+                EXIT_NOT_FOUND,
+                &format!("Multiple matches for '{}': {}", query.trim(), names.join(", ")),
+            ))
+        }
+    }
+}
+
+fn open(db_path: &Path) -> Result<Connection, String> {
+    let conn = Connection::open(db_path).map_err(|e| format!("Error opening database: {}", e))?;
+    schema::initialize(&conn).map_err(|e| format!("Error initializing database: {}", e))?;
+    Ok(conn)
+}
+
+fn load(conn: Connection) -> Result<CLIContext, String> {
+    let user = network_repo::find_first_user(&conn)
+        .map_err(|e| format!("Error loading data: {}", e))?
+        .ok_or("No network found. Run 'prm init <your name>' or start 'prm' interactively.")?;
+    let self_id = network_repo::get_self_id(&conn, user.id)
+        .ok()
+        .flatten()
+        .filter(|sid| person_repo::find_by_id(&conn, *sid).ok().flatten().is_some())
+        .ok_or("Network metadata is missing. Start 'prm' interactively to repair it.")?;
+    Ok(CLIContext::new(conn, user, self_id))
+}
+
+/// The exit code of a listing: an error reading the data is a failure, not
+/// an empty list.
+fn listed(result: PrmResult<()>) -> i32 {
+    match result {
+        Ok(()) => EXIT_OK,
+        Err(e) => fail(EXIT_ERROR, &format!("Error: {}", e)),
+    }
+}
+
+fn fail(code: i32, message: &str) -> i32 {
+    eprintln!("{}", message);
+    code
+}
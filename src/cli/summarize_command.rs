@@ -0,0 +1,80 @@
+use chrono::NaiveDate;
+
+use crate::ai::llm_backend::LlmConfig;
+use crate::ai::person_summary::{self, Point};
+use crate::cli::ai_log_command::print_parse_error;
+use crate::cli::args;
+use crate::cli::context::CLIContext;
+
+const USAGE: &str = "Usage: summarize <name> [--refresh]";
+
+/// `summarize <name> [--refresh]`
+pub fn summarize(ctx: &CLIContext, args: &str) {
+    let parsed = args::parse(args);
+    if parsed.positional.trim().is_empty() {
+        println!("{}", USAGE);
+        return;
+    }
+    let Some(person) = ctx.find_person(&parsed.positional) else { return };
+
+    let config = match LlmConfig::load() {
+        Ok(config) => config,
+        Err(e) => return println!("Error: {}", e),
+    };
+
+    // The stored summary needs no model server; only check for one when
+    // a new summary has to be written.
+    let stored = if parsed.has("refresh") {
+        None
+    } else {
+        match person_summary::stored(&ctx.conn, ctx.owner_id(), &config, &person) {
+            Ok(stored) => stored,
+            Err(e) => return println!("Error: {}", e),
+        }
+    };
+    let summary = match stored {
+        Some(summary) => summary,
+        None => {
+            let backend = config.backend();
+            if let Err(err) = backend.health_check() {
+                println!("Error: {}", err);
+                return;
+            }
+            match person_summary::summarize(&ctx.conn, ctx.owner_id(), &*backend, &person, true) {
+                Ok(s) => s,
+                Err(e) => return print_parse_error(&e),
+            }
+        }
+    };
+
+    let briefing = &summary.briefing;
+    println!("{}", person.name);
+    println!("  {}", briefing.summary.trim());
+    print_points("Worth remembering:", &briefing.points);
+    print_points("Open threads:", &briefing.open_threads);
+    println!();
+    if summary.cached {
+        println!("Summary from {} UTC; nothing new since. Use --refresh to write a new one.", summary.created_at);
+    } else {
+        println!("Dates are the interactions each point comes from; 'profile' means their details.");
+    }
+}
+
+fn print_points(heading: &str, points: &[Point]) {
+    if points.is_empty() {
+        return;
+    }
+    println!();
+    println!("{}", heading);
+    for point in points {
+        println!("  - {} ({})", point.text.trim(), sources(&point.dates));
+    }
+}
+
+fn sources(dates: &[NaiveDate]) -> String {
+    if dates.is_empty() {
+        "profile".into()
+    } else {
+        dates.iter().map(|d| d.to_string()).collect::<Vec<_>>().join(", ")
+    }
+}
@@ -0,0 +1,112 @@
+use chrono::{Duration, NaiveDate};
+
+use crate::cli::args;
+use crate::cli::context::CLIContext;
+use crate::model::InteractionMedium;
+use crate::queries::timeline_queries::{self, Period, TimelineFilter};
+
+const USAGE: &str = "Usage: timeline [--from YYYY-MM-DD] [--to YYYY-MM-DD] [--circle <name>] [--label <name>] \
+                     [--medium <medium>] [--by week|month]";
+
+/// Default span when `--from` is not given.
+const DEFAULT_DAYS: i64 = 90;
+
+/// `timeline [--from] [--to] [--circle X] [--label Y] [--medium M] [--by week|month]`
+pub fn timeline(ctx: &CLIContext, args: &str) {
+    let parsed = args::parse(args);
+
+    let to = match parse_date(parsed.flag("to")) {
+        Ok(date) => date.unwrap_or_else(CLIContext::today),
+        Err(e) => return println!("{}\n{}", e, USAGE),
+    };
+    let from = match parse_date(parsed.flag("from")) {
+        Ok(date) => date.unwrap_or(to - Duration::days(DEFAULT_DAYS)),
+        Err(e) => return println!("{}\n{}", e, USAGE),
+    };
+    if from > to {
+        println!("--from must not be after --to.");
+        return;
+    }
+
+    // Weeks for short spans, months for longer ones
+    let period = match parsed.flag("by") {
+        None => {
+            if (to - from).num_days() <= 60 {
+                Period::Week
+            } else {
+                Period::Month
+            }
+        }
+        Some(by) => match Period::parse(by) {
+            Some(p) => p,
+            None => return println!("Unknown grouping '{}'.\n{}", by, USAGE),
+        },
+    };
+
+    let mut filter = TimelineFilter::default();
+    if let Some(query) = parsed.flag("circle") {
+        match ctx.find_circle(query) {
+            Some(circle) => filter.circle = Some(circle.id),
+            None => return,
+        }
+    }
+    if let Some(query) = parsed.flag("label") {
+        match ctx.find_label(query) {
+            Some(label) => filter.label = Some(label.id),
+            None => return,
+        }
+    }
+    if let Some(m) = parsed.flag("medium") {
+        match InteractionMedium::parse(m) {
+            Some(medium) => filter.medium = Some(medium),
+            None => return println!("Unknown medium '{}'.\n{}", m, USAGE),
+        }
+    }
+
+    let periods = match timeline_queries::timeline(&ctx.conn, ctx.owner_id(), from, to, &filter, period) {
+        Ok(p) => p,
+        Err(e) => return ctx.print_error(&e),
+    };
+    if periods.is_empty() {
+        println!("No interactions between {} and {}.", from, to);
+        return;
+    }
+
+    let total: usize = periods.iter().map(|p| p.interaction_count()).sum();
+    println!("Timeline {} to {} ({} interactions):", from, to, total);
+    for p in &periods {
+        println!();
+        println!(
+            "{} - {} interaction(s), {} people",
+            period.label(p.start),
+            p.interaction_count(),
+            p.people_count()
+        );
+        for event in &p.events {
+            let names: Vec<&str> = event.people.iter().map(|person| person.name.as_str()).collect();
+            let topics = if event.topics.is_empty() {
+                String::new()
+            } else {
+                format!(" - {}", event.topics.join(", "))
+            };
+            println!(
+                "  {}  {:<12} {} @ {}{}",
+                event.date.format("%a %b %e"),
+                event.medium.display_name(),
+                names.join(", "),
+                event.my_location,
+                topics
+            );
+        }
+    }
+    println!();
+}
+
+fn parse_date(value: Option<&str>) -> Result<Option<NaiveDate>, String> {
+    match value {
+        None => Ok(None),
+        Some(v) => NaiveDate::parse_from_str(v, "%Y-%m-%d")
+            .map(Some)
+            .map_err(|_| format!("Invalid date '{}'. Use YYYY-MM-DD.", v)),
+    }
+}
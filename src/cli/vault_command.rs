@@ -0,0 +1,55 @@
+use std::path::Path;
+
+use crate::cli::context::CLIContext;
+use crate::sync::vault;
+
+pub fn vault_sync(ctx: &CLIContext, args: &str) {
+    if args.is_empty() {
+        println!("Usage: vault-sync <directory>");
+        println!("Example: vault-sync ~/Notes/People");
+        return;
+    }
+
+    let dir = Path::new(args);
+    println!("Syncing with {}...", dir.display());
+    let report = match vault::sync(&ctx.conn, ctx.owner_id(), ctx.self_id, dir) {
+        Ok(r) => r,
+        Err(e) => {
+            ctx.print_error(&e);
+            return;
+        }
+    };
+
+    if !report.created.is_empty() {
+        println!("Created from files ({}): {}", report.created.len(), report.created.join(", "));
+    }
+    if !report.applied.is_empty() {
+        println!("Updated from files ({}): {}", report.applied.len(), report.applied.join(", "));
+    }
+    if !report.written.is_empty() {
+        println!("Files written ({}): {}", report.written.len(), report.written.join(", "));
+    }
+    if !report.conflicts.is_empty() {
+        println!();
+        println!("Conflicts ({}) — changed in both places, nothing was overwritten:", report.conflicts.len());
+        for c in &report.conflicts {
+            println!("  {} ({}): {}", c.name, c.file_name, c.fields.join(", "));
+        }
+        println!("Resolve by editing either the file or the person, then sync again.");
+    }
+    if !report.errors.is_empty() {
+        println!();
+        println!("Errors ({}):", report.errors.len());
+        for e in &report.errors {
+            println!("  {}", e);
+        }
+    }
+    if report.created.is_empty()
+        && report.applied.is_empty()
+        && report.written.is_empty()
+        && report.conflicts.is_empty()
+        && report.errors.is_empty()
+    {
+        println!("Everything is already in sync.");
+    }
+}
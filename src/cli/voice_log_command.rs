@@ -1,9 +1,9 @@
 use std::path::Path;
 
-use crate::ai::{llm_service::{self, CorrectionExample}, whisper_service};
+use crate::ai::{llm_service, whisper_service};
 use crate::cli::ai_log_command;
 use crate::cli::context::CLIContext;
-use crate::db::correction_repo;
+use crate::db::correction_repo::CorrectionKind;
 use crate::queries::person_queries;
 
 pub fn voice_log(ctx: &CLIContext, args: &str) {
@@ -19,7 +19,7 @@ pub fn voice_log(ctx: &CLIContext, args: &str) {
         return;
     }
 
-    if let Err(err) = llm_service::check_ollama() {
+    if let Err(err) = llm_service::check_backend() {
         println!("Error: {}", err);
         return;
     }
@@ -54,20 +54,11 @@ pub fn voice_log(ctx: &CLIContext, args: &str) {
         .map(|p| p.name)
         .collect();
 
-    let corrections: Vec<CorrectionExample> =
-        correction_repo::recent(&ctx.conn, ctx.owner_id(), 5)
-            .unwrap_or_default()
-            .into_iter()
-            .map(|r| CorrectionExample {
-                original_text: r.original_text,
-                ai_output: r.ai_output,
-                user_output: r.user_output,
-            })
-            .collect();
+    let corrections = ai_log_command::relevant_corrections(ctx, CorrectionKind::Interaction, &text);
 
-    println!("Parsing with AI (local)...");
+    println!("Parsing with AI ({})...", llm_service::server_location());
     match llm_service::parse_interaction(&text, &known_names, &corrections) {
-        Err(err) => println!("Error: {}", err),
+        Err(err) => ai_log_command::print_parse_error(&err),
         Ok(parsed) => ai_log_command::review_and_save(ctx, &text, parsed),
     }
 }
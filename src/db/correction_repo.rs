@@ -1,15 +1,58 @@
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::error::PrmResult;
+use crate::error::{PrmError, PrmResult};
 use crate::model::{Id, User};
 
+const SELECT: &str = "SELECT c.id, c.original_text, c.ai_output, c.user_output, c.created_at,
+                             p.correction_id IS NOT NULL, k.kind
+                      FROM ai_corrections c
+                      LEFT JOIN pinned_corrections p ON p.correction_id = c.id
+                      LEFT JOIN correction_kinds k ON k.correction_id = c.id";
+
+/// What was corrected. Corrections of one kind are only shown to the model
+/// for that kind of request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CorrectionKind {
+    /// An `ai-log` parse of an interaction.
+    #[default]
+    Interaction,
+    /// An `ai-edit` change to a person.
+    PersonEdit,
+}
+
+impl CorrectionKind {
+    pub fn to_db_str(self) -> &'static str {
+        match self {
+            CorrectionKind::Interaction => "interaction",
+            CorrectionKind::PersonEdit => "person_edit",
+        }
+    }
+
+    /// Corrections saved before kinds were recorded are interactions.
+    pub fn from_db_str(s: Option<&str>) -> Self {
+        match s {
+            Some("person_edit") => CorrectionKind::PersonEdit,
+            _ => CorrectionKind::Interaction,
+        }
+    }
+}
+
 pub struct CorrectionRecord {
+    pub id: Uuid,
     pub original_text: String,
     pub ai_output: String,
     pub user_output: String,
+    /// UTC, as `YYYY-MM-DD HH:MM:SS`.
+    pub created_at: String,
+    /// Always included in prompts.
+    pub pinned: bool,
+    pub kind: CorrectionKind,
 }
 
+/// Saves an interaction correction.
 pub fn insert(
     conn: &Connection,
     owner_id: Id<User>,
@@ -17,18 +60,72 @@ pub fn insert(
     ai_output: &str,
     user_output: &str,
 ) -> PrmResult<()> {
+    insert_kind(conn, owner_id, CorrectionKind::Interaction, original_text, ai_output, user_output)
+}
+
+pub fn insert_kind(
+    conn: &Connection,
+    owner_id: Id<User>,
+    kind: CorrectionKind,
+    original_text: &str,
+    ai_output: &str,
+    user_output: &str,
+) -> PrmResult<()> {
+    let id = Uuid::new_v4();
     conn.execute(
         "INSERT INTO ai_corrections (id, owner_id, original_text, ai_output, user_output, created_at)
          VALUES (?1, ?2, ?3, ?4, ?5, datetime('now'))",
         params![
-            Uuid::new_v4().to_string(),
+            id.to_string(),
             owner_id.value.to_string(),
             original_text,
             ai_output,
             user_output,
         ],
     )?;
-    Ok(())
+    set_kind(conn, id, kind)
+}
+
+/// Inserts a correction from elsewhere under a new id, keeping its date and
+/// pin. Returns false, inserting nothing, if an identical one of the same
+/// kind exists.
+pub fn insert_record(conn: &Connection, owner_id: Id<User>, record: &CorrectionRecord) -> PrmResult<bool> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM ai_corrections c
+                       LEFT JOIN correction_kinds k ON k.correction_id = c.id
+                       WHERE c.owner_id = ?1 AND c.original_text = ?2 AND c.ai_output = ?3 AND c.user_output = ?4
+                       AND COALESCE(k.kind, ?5) = ?6)",
+        params![
+            owner_id.value.to_string(),
+            record.original_text,
+            record.ai_output,
+            record.user_output,
+            CorrectionKind::Interaction.to_db_str(),
+            record.kind.to_db_str(),
+        ],
+        |row| row.get(0),
+    )?;
+    if exists {
+        return Ok(false);
+    }
+    let id = Uuid::new_v4();
+    conn.execute(
+        "INSERT INTO ai_corrections (id, owner_id, original_text, ai_output, user_output, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            id.to_string(),
+            owner_id.value.to_string(),
+            record.original_text,
+            record.ai_output,
+            record.user_output,
+            record.created_at,
+        ],
+    )?;
+    set_kind(conn, id, record.kind)?;
+    if record.pinned {
+        set_pinned(conn, id, true)?;
+    }
+    Ok(true)
 }
 
 pub fn recent(
@@ -36,19 +133,99 @@ pub fn recent(
     owner_id: Id<User>,
     limit: usize,
 ) -> PrmResult<Vec<CorrectionRecord>> {
-    let mut stmt = conn.prepare(
-        "SELECT original_text, ai_output, user_output
-         FROM ai_corrections
-         WHERE owner_id = ?1
-         ORDER BY created_at DESC
-         LIMIT ?2",
-    )?;
-    let rows = stmt.query_map(params![owner_id.value.to_string(), limit as i64], |row| {
-        Ok(CorrectionRecord {
-            original_text: row.get(0)?,
-            ai_output: row.get(1)?,
-            user_output: row.get(2)?,
-        })
-    })?;
+    let mut stmt = conn.prepare(&format!(
+        "{} WHERE c.owner_id = ?1 ORDER BY c.created_at DESC LIMIT ?2",
+        SELECT
+    ))?;
+    let rows = stmt.query_map(params![owner_id.value.to_string(), limit as i64], row_to_record)?;
     rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
 }
+
+/// Every correction, oldest first.
+pub fn all(conn: &Connection, owner_id: Id<User>) -> PrmResult<Vec<CorrectionRecord>> {
+    let mut stmt = conn.prepare(&format!(
+        "{} WHERE c.owner_id = ?1 ORDER BY c.created_at, c.rowid",
+        SELECT
+    ))?;
+    let rows = stmt.query_map(params![owner_id.value.to_string()], row_to_record)?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+}
+
+/// Every correction of one kind, oldest first.
+pub fn all_of_kind(conn: &Connection, owner_id: Id<User>, kind: CorrectionKind) -> PrmResult<Vec<CorrectionRecord>> {
+    let mut records = all(conn, owner_id)?;
+    records.retain(|r| r.kind == kind);
+    Ok(records)
+}
+
+pub fn find_by_id(conn: &Connection, id: Uuid) -> PrmResult<Option<CorrectionRecord>> {
+    conn.query_row(&format!("{} WHERE c.id = ?1", SELECT), params![id.to_string()], row_to_record)
+        .optional()
+        .map_err(Into::into)
+}
+
+/// Corrections whose id starts with `prefix`, as shown by `corrections list`.
+pub fn find_by_id_prefix(conn: &Connection, owner_id: Id<User>, prefix: &str) -> PrmResult<Vec<CorrectionRecord>> {
+    let mut stmt = conn.prepare(&format!(
+        "{} WHERE c.owner_id = ?1 AND c.id LIKE ?2 || '%' ORDER BY c.created_at DESC",
+        SELECT
+    ))?;
+    let rows = stmt.query_map(params![owner_id.value.to_string(), prefix.to_lowercase()], row_to_record)?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+}
+
+pub fn delete(conn: &Connection, id: Uuid) -> PrmResult<()> {
+    let deleted = conn.execute("DELETE FROM ai_corrections WHERE id = ?1", params![id.to_string()])?;
+    if deleted == 0 {
+        return Err(not_found(id));
+    }
+    Ok(())
+}
+
+pub fn set_pinned(conn: &Connection, id: Uuid, pinned: bool) -> PrmResult<()> {
+    if find_by_id(conn, id)?.is_none() {
+        return Err(not_found(id));
+    }
+    if pinned {
+        conn.execute(
+            "INSERT OR IGNORE INTO pinned_corrections (correction_id) VALUES (?1)",
+            params![id.to_string()],
+        )?;
+    } else {
+        conn.execute("DELETE FROM pinned_corrections WHERE correction_id = ?1", params![id.to_string()])?;
+    }
+    Ok(())
+}
+
+/// Interaction corrections have no row, so older databases need none.
+fn set_kind(conn: &Connection, id: Uuid, kind: CorrectionKind) -> PrmResult<()> {
+    if kind != CorrectionKind::Interaction {
+        conn.execute(
+            "INSERT OR REPLACE INTO correction_kinds (correction_id, kind) VALUES (?1, ?2)",
+            params![id.to_string(), kind.to_db_str()],
+        )?;
+    }
+    Ok(())
+}
+
+fn not_found(id: Uuid) -> PrmError {
+    PrmError::NotFound {
+        entity_type: "Correction".into(),
+        id: id.to_string(),
+    }
+}
+
+fn row_to_record(row: &rusqlite::Row) -> rusqlite::Result<CorrectionRecord> {
+    let id: String = row.get(0)?;
+    Ok(CorrectionRecord {
+        id: Uuid::parse_str(&id).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
+        })?,
+        original_text: row.get(1)?,
+        ai_output: row.get(2)?,
+        user_output: row.get(3)?,
+        created_at: row.get(4)?,
+        pinned: row.get(5)?,
+        kind: CorrectionKind::from_db_str(row.get::<_, Option<String>>(6)?.as_deref()),
+    })
+}
@@ -0,0 +1,110 @@
+use rusqlite::{params, Connection};
+use uuid::Uuid;
+
+use crate::error::{PrmError, PrmResult};
+use crate::model::{Id, User};
+
+/// What an embedding was computed from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SourceType {
+    /// An interaction's topics and note.
+    Interaction,
+    /// A person's notes.
+    PersonNotes,
+}
+
+impl SourceType {
+    pub fn to_db_str(self) -> &'static str {
+        match self {
+            SourceType::Interaction => "interaction",
+            SourceType::PersonNotes => "person_notes",
+        }
+    }
+
+    pub fn from_db_str(s: &str) -> Option<Self> {
+        match s {
+            "interaction" => Some(SourceType::Interaction),
+            "person_notes" => Some(SourceType::PersonNotes),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredEmbedding {
+    pub source_type: SourceType,
+    pub source_id: Uuid,
+    /// Identifies the text and model the vector came from, so it is only
+    /// recomputed when either changes.
+    pub text_hash: String,
+    pub vector: Vec<f32>,
+}
+
+pub fn upsert(conn: &Connection, owner_id: Id<User>, embedding: &StoredEmbedding) -> PrmResult<()> {
+    conn.execute(
+        "INSERT INTO embeddings (source_type, source_id, owner_id, text_hash, vector)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(source_type, source_id) DO UPDATE SET
+         text_hash = excluded.text_hash, vector = excluded.vector",
+        params![
+            embedding.source_type.to_db_str(),
+            embedding.source_id.to_string(),
+            owner_id.value.to_string(),
+            embedding.text_hash,
+            to_blob(&embedding.vector),
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn find_by_owner(conn: &Connection, owner_id: Id<User>) -> PrmResult<Vec<StoredEmbedding>> {
+    let mut stmt = conn.prepare(
+        "SELECT source_type, source_id, text_hash, vector FROM embeddings WHERE owner_id = ?1",
+    )?;
+    let rows: Vec<(String, String, String, Vec<u8>)> = stmt
+        .query_map(params![owner_id.value.to_string()], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    rows.into_iter()
+        .map(|(type_str, id_str, text_hash, blob)| {
+            Ok(StoredEmbedding {
+                source_type: SourceType::from_db_str(&type_str)
+                    .ok_or_else(|| PrmError::Other(format!("Unknown embedding source: {}", type_str)))?,
+                source_id: Uuid::parse_str(&id_str).map_err(|e| PrmError::Other(format!("Invalid UUID: {}", e)))?,
+                text_hash,
+                vector: from_blob(&blob),
+            })
+        })
+        .collect()
+}
+
+/// Whether anything has been embedded for this network yet.
+pub fn any_for_owner(conn: &Connection, owner_id: Id<User>) -> PrmResult<bool> {
+    let found: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM embeddings WHERE owner_id = ?1)",
+        params![owner_id.value.to_string()],
+        |row| row.get(0),
+    )?;
+    Ok(found)
+}
+
+pub fn delete(conn: &Connection, source_type: SourceType, source_id: Uuid) -> PrmResult<()> {
+    conn.execute(
+        "DELETE FROM embeddings WHERE source_type = ?1 AND source_id = ?2",
+        params![source_type.to_db_str(), source_id.to_string()],
+    )?;
+    Ok(())
+}
+
+/// Little-endian `f32`s.
+fn to_blob(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|f| f.to_le_bytes()).collect()
+}
+
+fn from_blob(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
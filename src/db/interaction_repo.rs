@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use chrono::NaiveDate;
 use rusqlite::{params, Connection};
 use uuid::Uuid;
@@ -79,6 +81,33 @@ pub fn find_by_person(
     Ok(interactions)
 }
 
+/// Replaces an interaction's topics and note.
+pub fn update_details(
+    conn: &Connection,
+    interaction_id: Id<Interaction>,
+    topics: &[String],
+    note: Option<&str>,
+) -> PrmResult<()> {
+    let id = interaction_id.value.to_string();
+    let changed = conn.execute("UPDATE interactions SET note = ?1 WHERE id = ?2", params![note, id])?;
+    if changed == 0 {
+        return Err(PrmError::NotFound {
+            entity_type: "Interaction".into(),
+            id,
+        });
+    }
+
+    conn.execute("DELETE FROM interaction_topics WHERE interaction_id = ?1", params![id])?;
+    for topic in topics {
+        conn.execute(
+            "INSERT OR IGNORE INTO interaction_topics (interaction_id, topic) VALUES (?1, ?2)",
+            params![id, topic],
+        )?;
+    }
+
+    Ok(())
+}
+
 pub fn find_last_interaction_date(
     conn: &Connection,
     person_id: Id<Person>,
@@ -110,33 +139,75 @@ pub fn find_in_date_range(
     from: NaiveDate,
     to: NaiveDate,
 ) -> PrmResult<Vec<(Id<Person>, Interaction)>> {
-    let mut stmt = conn.prepare(
+    find_with_person(
+        conn,
         "SELECT i.id, i.relationship_person_id, i.date, i.medium, i.my_location, i.their_location, i.note
          FROM interactions i
          JOIN relationships r ON i.relationship_person_id = r.person_id
          WHERE r.network_owner_id = ?1 AND i.date >= ?2 AND i.date <= ?3
          ORDER BY i.date DESC",
+        params![owner_id.value.to_string(), from.to_string(), to.to_string()],
+    )
+}
+
+/// When each interaction between `from` and `to` (inclusive) was logged.
+pub fn logged_at_in_date_range(
+    conn: &Connection,
+    owner_id: Id<crate::model::User>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> PrmResult<HashMap<Id<Interaction>, String>> {
+    let mut stmt = conn.prepare(
+        "SELECT i.id, i.created_at
+         FROM interactions i
+         JOIN relationships r ON i.relationship_person_id = r.person_id
+         WHERE r.network_owner_id = ?1 AND i.date >= ?2 AND i.date <= ?3",
     )?;
+    let rows: Vec<(String, String)> = stmt
+        .query_map(params![owner_id.value.to_string(), from.to_string(), to.to_string()], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    rows.into_iter()
+        .map(|(id_str, created_at)| {
+            let id = Uuid::parse_str(&id_str).map_err(|e| PrmError::Other(format!("Invalid UUID: {}", e)))?;
+            Ok((Id::new(id), created_at))
+        })
+        .collect()
+}
 
+/// Every interaction in the network with the person it was logged for,
+/// newest first.
+pub fn find_by_owner(conn: &Connection, owner_id: Id<crate::model::User>) -> PrmResult<Vec<(Id<Person>, Interaction)>> {
+    find_with_person(
+        conn,
+        "SELECT i.id, i.relationship_person_id, i.date, i.medium, i.my_location, i.their_location, i.note
+         FROM interactions i
+         JOIN relationships r ON i.relationship_person_id = r.person_id
+         WHERE r.network_owner_id = ?1
+         ORDER BY i.date DESC",
+        params![owner_id.value.to_string()],
+    )
+}
+
+fn find_with_person(
+    conn: &Connection,
+    sql: &str,
+    params: &[&dyn rusqlite::ToSql],
+) -> PrmResult<Vec<(Id<Person>, Interaction)>> {
+    let mut stmt = conn.prepare(sql)?;
     let rows: Vec<(String, String, String, String, String, Option<String>, Option<String>)> = stmt
-        .query_map(
-            params![
-                owner_id.value.to_string(),
-                from.to_string(),
-                to.to_string()
-            ],
-            |row| {
-                Ok((
-                    row.get(0)?,
-                    row.get(1)?,
-                    row.get(2)?,
-                    row.get(3)?,
-                    row.get(4)?,
-                    row.get(5)?,
-                    row.get(6)?,
-                ))
-            },
-        )?
+        .query_map(params, |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+                row.get(5)?,
+                row.get(6)?,
+            ))
+        })?
         .collect::<Result<Vec<_>, _>>()?;
 
     let mut results = Vec::new();
@@ -6,3 +6,7 @@ pub mod interaction_repo;
 pub mod circle_repo;
 pub mod network_repo;
 pub mod correction_repo;
+pub mod vault_repo;
+pub mod review_repo;
+pub mod embedding_repo;
+pub mod summary_repo;
@@ -1,7 +1,9 @@
+use chrono::NaiveDate;
 use rusqlite::{params, Connection};
 use uuid::Uuid;
 
 use crate::error::PrmResult;
+use crate::matching;
 use crate::model::{Id, Person, User};
 
 pub fn insert(conn: &Connection, owner_id: Id<User>, person: &Person) -> PrmResult<()> {
@@ -112,21 +114,16 @@ pub fn find_archived_by_owner(conn: &Connection, owner_id: Id<User>) -> PrmResul
     Ok(people)
 }
 
-pub fn find_by_name(
-    conn: &Connection,
-    owner_id: Id<User>,
-    query: &str,
-) -> PrmResult<Vec<Person>> {
-    let pattern = format!("%{}%", query.to_lowercase());
+/// Active people (other than yourself) added on or after `since`, oldest first.
+pub fn find_created_since(conn: &Connection, owner_id: Id<User>, since: NaiveDate) -> PrmResult<Vec<Person>> {
     let mut stmt = conn.prepare(
         "SELECT id, name, nickname, how_we_met, birthday, notes, location, is_self, archived
-         FROM people WHERE network_owner_id = ?1
-         AND (LOWER(name) LIKE ?2 OR LOWER(COALESCE(nickname, '')) LIKE ?2)
-         ORDER BY name",
+         FROM people WHERE network_owner_id = ?1 AND archived = 0 AND is_self = 0
+         AND created_at >= ?2 ORDER BY created_at, name",
     )?;
 
     let people = stmt
-        .query_map(params![owner_id.value.to_string(), pattern], |row| {
+        .query_map(params![owner_id.value.to_string(), since.to_string()], |row| {
             Ok(row_to_person(row))
         })?
         .collect::<Result<Vec<_>, _>>()?
@@ -136,6 +133,43 @@ pub fn find_by_name(
     Ok(people)
 }
 
+/// People whose name or nickname fuzzily matches `query`, best match first
+/// (see `matching::rank`).
+pub fn find_by_name(
+    conn: &Connection,
+    owner_id: Id<User>,
+    query: &str,
+) -> PrmResult<Vec<Person>> {
+    let people = find_by_owner(conn, owner_id)?;
+    Ok(matching::rank(query, people, |p| p.names())
+        .into_iter()
+        .map(|(p, _)| p)
+        .collect())
+}
+
+/// Runs `SELECT ... FROM people p WHERE <where_sql> ORDER BY <order_sql>`
+/// with positional string parameters. Used by compiled person filters.
+pub fn find_where(
+    conn: &Connection,
+    where_sql: &str,
+    order_sql: &str,
+    params: &[String],
+) -> PrmResult<Vec<Person>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT p.id, p.name, p.nickname, p.how_we_met, p.birthday, p.notes, p.location, p.is_self, p.archived
+         FROM people p WHERE {} ORDER BY {}",
+        where_sql, order_sql
+    ))?;
+
+    let people = stmt
+        .query_map(rusqlite::params_from_iter(params), |row| Ok(row_to_person(row)))?
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(people)
+}
+
 pub fn find_self(conn: &Connection, owner_id: Id<User>) -> PrmResult<Option<Person>> {
     let mut stmt = conn.prepare(
         "SELECT id, name, nickname, how_we_met, birthday, notes, location, is_self, archived
@@ -1,3 +1,4 @@
+use chrono::NaiveDate;
 use rusqlite::{params, Connection};
 use uuid::Uuid;
 
@@ -104,6 +105,34 @@ pub fn update_reminder(
     Ok(())
 }
 
+/// Holds off reminders for a person until `until`.
+pub fn upsert_snooze(conn: &Connection, person_id: Id<Person>, until: NaiveDate) -> PrmResult<()> {
+    conn.execute(
+        "INSERT INTO reminder_snoozes (person_id, until) VALUES (?1, ?2)
+         ON CONFLICT(person_id) DO UPDATE SET until = excluded.until",
+        params![person_id.value.to_string(), until.to_string()],
+    )?;
+    Ok(())
+}
+
+pub fn find_snooze(conn: &Connection, person_id: Id<Person>) -> PrmResult<Option<NaiveDate>> {
+    let result = conn.query_row(
+        "SELECT until FROM reminder_snoozes WHERE person_id = ?1",
+        params![person_id.value.to_string()],
+        |row| row.get::<_, String>(0),
+    );
+
+    match result {
+        Ok(date_str) => {
+            let date = NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
+                .map_err(|e| PrmError::Other(format!("Invalid date: {}", e)))?;
+            Ok(Some(date))
+        }
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
 fn find_label_ids(conn: &Connection, person_id: Id<Person>) -> PrmResult<Vec<Id<RelationshipLabel>>> {
     let mut stmt = conn.prepare(
         "SELECT label_id FROM relationship_label_assignments WHERE relationship_person_id = ?1",
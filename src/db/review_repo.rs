@@ -0,0 +1,64 @@
+use chrono::NaiveDate;
+use rusqlite::{params, Connection};
+
+use crate::error::{PrmError, PrmResult};
+use crate::model::{Id, User};
+
+/// Where an unfinished weekly review stopped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReviewProgress {
+    /// The day the review began; its seven-day window ends here.
+    pub started_on: NaiveDate,
+    /// Index of the section being worked through.
+    pub section: usize,
+    /// Keys of the items already handled in that section.
+    pub done: Vec<String>,
+}
+
+pub fn upsert(conn: &Connection, owner_id: Id<User>, progress: &ReviewProgress) -> PrmResult<()> {
+    let done = serde_json::to_string(&progress.done).map_err(|e| PrmError::Other(e.to_string()))?;
+    conn.execute(
+        "INSERT INTO review_progress (owner_id, started_on, section, done)
+         VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(owner_id) DO UPDATE SET started_on = excluded.started_on,
+         section = excluded.section, done = excluded.done",
+        params![
+            owner_id.value.to_string(),
+            progress.started_on.to_string(),
+            progress.section as i64,
+            done,
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn find_by_owner(conn: &Connection, owner_id: Id<User>) -> PrmResult<Option<ReviewProgress>> {
+    let result = conn.query_row(
+        "SELECT started_on, section, done FROM review_progress WHERE owner_id = ?1",
+        params![owner_id.value.to_string()],
+        |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, String>(2)?)),
+    );
+
+    match result {
+        Ok((started_str, section, done_json)) => {
+            let started_on = NaiveDate::parse_from_str(&started_str, "%Y-%m-%d")
+                .map_err(|e| PrmError::Other(format!("Invalid date: {}", e)))?;
+            let done = serde_json::from_str(&done_json).map_err(|e| PrmError::Other(e.to_string()))?;
+            Ok(Some(ReviewProgress {
+                started_on,
+                section: section.max(0) as usize,
+                done,
+            }))
+        }
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn delete(conn: &Connection, owner_id: Id<User>) -> PrmResult<()> {
+    conn.execute(
+        "DELETE FROM review_progress WHERE owner_id = ?1",
+        params![owner_id.value.to_string()],
+    )?;
+    Ok(())
+}
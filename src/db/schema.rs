@@ -113,6 +113,52 @@ pub fn initialize(conn: &Connection) -> PrmResult<()> {
             created_at TEXT NOT NULL DEFAULT (datetime('now'))
         );
 
+        CREATE TABLE IF NOT EXISTS pinned_corrections (
+            correction_id TEXT PRIMARY KEY NOT NULL REFERENCES ai_corrections(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS correction_kinds (
+            correction_id TEXT PRIMARY KEY NOT NULL REFERENCES ai_corrections(id) ON DELETE CASCADE,
+            kind TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS embeddings (
+            source_type TEXT NOT NULL,
+            source_id TEXT NOT NULL,
+            owner_id TEXT NOT NULL REFERENCES users(id),
+            text_hash TEXT NOT NULL,
+            vector BLOB NOT NULL,
+            PRIMARY KEY (source_type, source_id)
+        );
+
+        CREATE TABLE IF NOT EXISTS person_summaries (
+            person_id TEXT PRIMARY KEY NOT NULL REFERENCES people(id) ON DELETE CASCADE,
+            source_hash TEXT NOT NULL,
+            summary TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE TABLE IF NOT EXISTS vault_sync_state (
+            person_id TEXT PRIMARY KEY NOT NULL REFERENCES people(id) ON DELETE CASCADE,
+            owner_id TEXT NOT NULL REFERENCES users(id),
+            file_name TEXT NOT NULL,
+            base_record TEXT NOT NULL,
+            base_hash TEXT NOT NULL,
+            synced_at INTEGER NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS reminder_snoozes (
+            person_id TEXT PRIMARY KEY NOT NULL REFERENCES relationships(person_id) ON DELETE CASCADE,
+            until TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS review_progress (
+            owner_id TEXT PRIMARY KEY NOT NULL REFERENCES users(id),
+            started_on TEXT NOT NULL,
+            section INTEGER NOT NULL,
+            done TEXT NOT NULL
+        );
+
         PRAGMA foreign_keys = ON;
         ",
     )?;
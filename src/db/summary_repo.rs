@@ -0,0 +1,45 @@
+use rusqlite::{params, Connection};
+
+use crate::error::PrmResult;
+use crate::model::{Id, Person};
+
+/// A generated summary of a person, kept until what it was made from changes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredSummary {
+    /// Identifies the facts and model the summary came from.
+    pub source_hash: String,
+    /// The summary as JSON.
+    pub summary: String,
+    pub created_at: String,
+}
+
+pub fn upsert(conn: &Connection, person_id: Id<Person>, summary: &StoredSummary) -> PrmResult<()> {
+    conn.execute(
+        "INSERT INTO person_summaries (person_id, source_hash, summary, created_at)
+         VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(person_id) DO UPDATE SET
+         source_hash = excluded.source_hash, summary = excluded.summary, created_at = excluded.created_at",
+        params![person_id.value.to_string(), summary.source_hash, summary.summary, summary.created_at],
+    )?;
+    Ok(())
+}
+
+pub fn find_by_person(conn: &Connection, person_id: Id<Person>) -> PrmResult<Option<StoredSummary>> {
+    let result = conn.query_row(
+        "SELECT source_hash, summary, created_at FROM person_summaries WHERE person_id = ?1",
+        params![person_id.value.to_string()],
+        |row| {
+            Ok(StoredSummary {
+                source_hash: row.get(0)?,
+                summary: row.get(1)?,
+                created_at: row.get(2)?,
+            })
+        },
+    );
+
+    match result {
+        Ok(summary) => Ok(Some(summary)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
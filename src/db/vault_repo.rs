@@ -0,0 +1,62 @@
+use rusqlite::{params, Connection};
+use uuid::Uuid;
+
+use crate::error::{PrmError, PrmResult};
+use crate::model::{Id, Person, User};
+
+/// What a person looked like at the last vault sync, used as the merge base.
+pub struct VaultSyncState {
+    pub person_id: Id<Person>,
+    pub file_name: String,
+    pub base_record: String,
+    pub base_hash: String,
+    /// Unix timestamp (seconds) of the last sync.
+    pub synced_at: i64,
+}
+
+pub fn upsert(conn: &Connection, owner_id: Id<User>, state: &VaultSyncState) -> PrmResult<()> {
+    conn.execute(
+        "INSERT INTO vault_sync_state (person_id, owner_id, file_name, base_record, base_hash, synced_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT(person_id) DO UPDATE SET file_name = excluded.file_name,
+         base_record = excluded.base_record, base_hash = excluded.base_hash,
+         synced_at = excluded.synced_at",
+        params![
+            state.person_id.value.to_string(),
+            owner_id.value.to_string(),
+            state.file_name,
+            state.base_record,
+            state.base_hash,
+            state.synced_at,
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn find_by_owner(conn: &Connection, owner_id: Id<User>) -> PrmResult<Vec<VaultSyncState>> {
+    let mut stmt = conn.prepare(
+        "SELECT person_id, file_name, base_record, base_hash, synced_at
+         FROM vault_sync_state WHERE owner_id = ?1",
+    )?;
+
+    let rows: Vec<(String, String, String, String, i64)> = stmt
+        .query_map(params![owner_id.value.to_string()], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    rows.into_iter()
+        .map(|(pid_str, file_name, base_record, base_hash, synced_at)| {
+            Ok(VaultSyncState {
+                person_id: Id::new(
+                    Uuid::parse_str(&pid_str)
+                        .map_err(|e| PrmError::Other(format!("Invalid UUID: {}", e)))?,
+                ),
+                file_name,
+                base_record,
+                base_hash,
+                synced_at,
+            })
+        })
+        .collect()
+}
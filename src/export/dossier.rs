@@ -0,0 +1,414 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, NaiveDate};
+use rusqlite::Connection;
+
+use crate::db::contact_repo;
+use crate::error::{PrmError, PrmResult};
+use crate::model::{ContactType, ContactValue, Id, Interaction, InteractionMedium, Person, User};
+use crate::queries::{circle_queries, interaction_queries, person_queries, relationship_queries};
+
+/// Output format for a dossier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DossierFormat {
+    Markdown,
+    Html,
+}
+
+impl DossierFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "md" | "markdown" => Some(DossierFormat::Markdown),
+            "html" | "htm" => Some(DossierFormat::Html),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            DossierFormat::Markdown => "md",
+            DossierFormat::Html => "html",
+        }
+    }
+}
+
+/// A contact entry resolved for display (custom type names looked up).
+#[derive(Debug, Clone)]
+pub struct DossierContact {
+    pub kind: String,
+    pub value: String,
+    pub label: Option<String>,
+}
+
+/// Interactions from a single calendar year, newest first.
+#[derive(Debug, Clone)]
+pub struct YearSection {
+    pub year: i32,
+    pub interactions: Vec<Interaction>,
+    /// Topics mentioned that year, most frequent first.
+    pub topic_counts: Vec<(String, usize)>,
+}
+
+/// Everything known about one person, gathered for a printable one-pager.
+#[derive(Debug, Clone)]
+pub struct Dossier {
+    pub person: Person,
+    pub contacts: Vec<DossierContact>,
+    pub labels: Vec<String>,
+    pub circles: Vec<String>,
+    pub reminder_days: Option<i32>,
+    pub days_since_last_contact: Option<i64>,
+    pub total_interactions: usize,
+    pub years: Vec<YearSection>,
+    pub generated_on: NaiveDate,
+}
+
+pub fn build(
+    conn: &Connection,
+    owner_id: Id<User>,
+    person_id: Id<Person>,
+    as_of: NaiveDate,
+) -> PrmResult<Dossier> {
+    let person = person_queries::get_person(conn, person_id)?
+        .ok_or_else(|| PrmError::NotFound {
+            entity_type: "Person".into(),
+            id: person_id.to_string(),
+        })?;
+
+    let custom_types = contact_repo::find_custom_types(conn, owner_id)?;
+    let contacts = contact_repo::find_by_person(conn, person_id)?
+        .into_iter()
+        .map(|entry| {
+            let kind = match &entry.contact_type {
+                ContactType::Phone => "Phone".to_string(),
+                ContactType::Email => "Email".to_string(),
+                ContactType::PhysicalAddress => "Address".to_string(),
+                ContactType::Custom { type_id } => custom_types
+                    .iter()
+                    .find(|t| t.id == *type_id)
+                    .map(|t| t.name.clone())
+                    .unwrap_or_else(|| "Other".to_string()),
+            };
+            let value = match &entry.value {
+                ContactValue::StringValue { value } => value.clone(),
+                ContactValue::AddressValue { value } => [
+                    value.street.as_str(),
+                    value.city.as_str(),
+                    value.state.as_str(),
+                    value.zip.as_str(),
+                    value.country.as_str(),
+                ]
+                .iter()
+                .filter(|s| !s.is_empty())
+                .copied()
+                .collect::<Vec<_>>()
+                .join(", "),
+            };
+            DossierContact {
+                kind,
+                value,
+                label: entry.label,
+            }
+        })
+        .collect();
+
+    let mut labels: Vec<String> = relationship_queries::labels_for(conn, person_id)?
+        .into_iter()
+        .map(|l| l.name)
+        .collect();
+    labels.sort();
+
+    let circles: Vec<String> = circle_queries::circles_for_person(conn, owner_id, person_id)?
+        .into_iter()
+        .map(|c| c.name)
+        .collect();
+
+    let reminder_days = relationship_queries::get_relationship(conn, person_id)?
+        .and_then(|r| r.reminder_days);
+    let days_since_last_contact =
+        interaction_queries::days_since_interaction(conn, person_id, as_of)?;
+
+    let interactions = interaction_queries::interactions_with(conn, person_id)?;
+    let total_interactions = interactions.len();
+
+    Ok(Dossier {
+        person,
+        contacts,
+        labels,
+        circles,
+        reminder_days,
+        days_since_last_contact,
+        total_interactions,
+        years: group_by_year(interactions),
+        generated_on: as_of,
+    })
+}
+
+/// Groups interactions (already newest first) into year sections, newest year first.
+pub fn group_by_year(interactions: Vec<Interaction>) -> Vec<YearSection> {
+    let mut by_year: BTreeMap<i32, Vec<Interaction>> = BTreeMap::new();
+    for interaction in interactions {
+        by_year.entry(interaction.date.year()).or_default().push(interaction);
+    }
+
+    by_year
+        .into_iter()
+        .rev()
+        .map(|(year, interactions)| {
+            let topic_counts = topic_frequency(&interactions);
+            YearSection {
+                year,
+                interactions,
+                topic_counts,
+            }
+        })
+        .collect()
+}
+
+/// Counts topics case-insensitively, most frequent first, ties alphabetical.
+pub fn topic_frequency(interactions: &[Interaction]) -> Vec<(String, usize)> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for interaction in interactions {
+        for topic in &interaction.topics {
+            *counts.entry(topic.to_lowercase()).or_default() += 1;
+        }
+    }
+    let mut sorted: Vec<(String, usize)> = counts.into_iter().collect();
+    sorted.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    sorted
+}
+
+pub fn render(dossier: &Dossier, format: DossierFormat) -> String {
+    match format {
+        DossierFormat::Markdown => render_markdown(dossier),
+        DossierFormat::Html => render_html(dossier),
+    }
+}
+
+/// A filesystem-friendly file stem for a person's dossier (e.g. "alice-smith").
+pub fn file_stem(person: &Person) -> String {
+    let mut stem = String::new();
+    for c in person.name.chars() {
+        if c.is_alphanumeric() {
+            stem.extend(c.to_lowercase());
+        } else if !stem.ends_with('-') && !stem.is_empty() {
+            stem.push('-');
+        }
+    }
+    let stem = stem.trim_end_matches('-').to_string();
+    if stem.is_empty() {
+        person.id.to_string()
+    } else {
+        stem
+    }
+}
+
+fn last_contact_text(dossier: &Dossier) -> String {
+    match dossier.days_since_last_contact {
+        Some(0) => "today".into(),
+        Some(1) => "1 day ago".into(),
+        Some(d) => format!("{} days ago", d),
+        None => "never".into(),
+    }
+}
+
+fn reminder_text(dossier: &Dossier) -> String {
+    match dossier.reminder_days {
+        Some(days) => {
+            let status = match dossier.days_since_last_contact {
+                Some(since) if since > days as i64 => format!(" ({} days overdue)", since - days as i64),
+                Some(since) => format!(" (due in {} days)", days as i64 - since),
+                None => " (never contacted)".into(),
+            };
+            format!("every {} days{}", days, status)
+        }
+        None => "(none)".into(),
+    }
+}
+
+fn location_text(interaction: &Interaction) -> String {
+    if interaction.medium == InteractionMedium::InPerson {
+        interaction.my_location.clone()
+    } else {
+        let their = interaction
+            .their_location
+            .as_deref()
+            .map(|l| format!(" / them: {}", l))
+            .unwrap_or_default();
+        format!("you: {}{}", interaction.my_location, their)
+    }
+}
+
+fn topic_summary(counts: &[(String, usize)]) -> String {
+    counts
+        .iter()
+        .map(|(topic, n)| format!("{} ({})", topic, n))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn render_markdown(d: &Dossier) -> String {
+    let p = &d.person;
+    let mut out = String::new();
+
+    out.push_str(&format!("# {}\n\n", p.name));
+    out.push_str(&format!("_Dossier generated {}_\n\n", d.generated_on));
+
+    out.push_str("## Profile\n\n");
+    let fields = [
+        ("Nickname", p.nickname.clone()),
+        ("Birthday", p.birthday.map(|b| b.to_string())),
+        ("Location", p.location.clone()),
+        ("How we met", p.how_we_met.clone()),
+        ("Notes", p.notes.clone()),
+    ];
+    for (name, value) in fields {
+        if let Some(v) = value {
+            out.push_str(&format!("- **{}:** {}\n", name, v));
+        }
+    }
+    out.push_str(&format!(
+        "- **Labels:** {}\n",
+        if d.labels.is_empty() { "(none)".into() } else { d.labels.join(", ") }
+    ));
+    out.push_str(&format!(
+        "- **Circles:** {}\n",
+        if d.circles.is_empty() { "(none)".into() } else { d.circles.join(", ") }
+    ));
+    out.push_str(&format!("- **Reminder:** {}\n", reminder_text(d)));
+    out.push_str(&format!("- **Last contact:** {}\n\n", last_contact_text(d)));
+
+    out.push_str("## Contact\n\n");
+    if d.contacts.is_empty() {
+        out.push_str("(none)\n\n");
+    } else {
+        for c in &d.contacts {
+            let label = c.label.as_ref().map(|l| format!(" ({})", l)).unwrap_or_default();
+            out.push_str(&format!("- **{}:** {}{}\n", c.kind, c.value, label));
+        }
+        out.push('\n');
+    }
+
+    out.push_str(&format!("## Interactions ({})\n\n", d.total_interactions));
+    if d.years.is_empty() {
+        out.push_str("(none)\n");
+    }
+    for section in &d.years {
+        out.push_str(&format!("### {} ({})\n\n", section.year, section.interactions.len()));
+        if !section.topic_counts.is_empty() {
+            out.push_str(&format!("Topics: {}\n\n", topic_summary(&section.topic_counts)));
+        }
+        for i in &section.interactions {
+            out.push_str(&format!(
+                "- **{}** {} — {} — {}\n",
+                i.date,
+                i.medium.display_name(),
+                location_text(i),
+                i.topics.join(", ")
+            ));
+            if let Some(note) = &i.note {
+                out.push_str(&format!("  - {}\n", note));
+            }
+        }
+        out.push('\n');
+    }
+
+    out
+}
+
+fn render_html(d: &Dossier) -> String {
+    let p = &d.person;
+    let mut out = String::new();
+
+    out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str(&format!("<title>{}</title>\n", escape_html(&p.name)));
+    out.push_str(
+        "<style>body{font-family:sans-serif;max-width:48em;margin:2em auto;}\
+         dt{font-weight:bold;float:left;width:9em;}dd{margin-left:10em;}\
+         li{margin-bottom:.3em;}.meta{color:#666;}</style>\n",
+    );
+    out.push_str("</head>\n<body>\n");
+    out.push_str(&format!("<h1>{}</h1>\n", escape_html(&p.name)));
+    out.push_str(&format!("<p class=\"meta\">Dossier generated {}</p>\n", d.generated_on));
+
+    out.push_str("<h2>Profile</h2>\n<dl>\n");
+    let fields = [
+        ("Nickname", p.nickname.clone()),
+        ("Birthday", p.birthday.map(|b| b.to_string())),
+        ("Location", p.location.clone()),
+        ("How we met", p.how_we_met.clone()),
+        ("Notes", p.notes.clone()),
+        (
+            "Labels",
+            Some(if d.labels.is_empty() { "(none)".into() } else { d.labels.join(", ") }),
+        ),
+        (
+            "Circles",
+            Some(if d.circles.is_empty() { "(none)".into() } else { d.circles.join(", ") }),
+        ),
+        ("Reminder", Some(reminder_text(d))),
+        ("Last contact", Some(last_contact_text(d))),
+    ];
+    for (name, value) in fields {
+        if let Some(v) = value {
+            out.push_str(&format!("<dt>{}</dt><dd>{}</dd>\n", name, escape_html(&v)));
+        }
+    }
+    out.push_str("</dl>\n");
+
+    out.push_str("<h2>Contact</h2>\n");
+    if d.contacts.is_empty() {
+        out.push_str("<p>(none)</p>\n");
+    } else {
+        out.push_str("<ul>\n");
+        for c in &d.contacts {
+            let label = c.label.as_ref().map(|l| format!(" ({})", l)).unwrap_or_default();
+            out.push_str(&format!(
+                "<li><strong>{}:</strong> {}{}</li>\n",
+                escape_html(&c.kind),
+                escape_html(&c.value),
+                escape_html(&label)
+            ));
+        }
+        out.push_str("</ul>\n");
+    }
+
+    out.push_str(&format!("<h2>Interactions ({})</h2>\n", d.total_interactions));
+    if d.years.is_empty() {
+        out.push_str("<p>(none)</p>\n");
+    }
+    for section in &d.years {
+        out.push_str(&format!("<h3>{} ({})</h3>\n", section.year, section.interactions.len()));
+        if !section.topic_counts.is_empty() {
+            out.push_str(&format!(
+                "<p class=\"meta\">Topics: {}</p>\n",
+                escape_html(&topic_summary(&section.topic_counts))
+            ));
+        }
+        out.push_str("<ul>\n");
+        for i in &section.interactions {
+            out.push_str(&format!(
+                "<li><strong>{}</strong> {} — {} — {}",
+                i.date,
+                i.medium.display_name(),
+                escape_html(&location_text(i)),
+                escape_html(&i.topics.join(", "))
+            ));
+            if let Some(note) = &i.note {
+                out.push_str(&format!("<br><em>{}</em>", escape_html(note)));
+            }
+            out.push_str("</li>\n");
+        }
+        out.push_str("</ul>\n");
+    }
+
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
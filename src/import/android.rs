@@ -0,0 +1,484 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime};
+use rusqlite::Connection;
+
+use crate::error::{PrmError, PrmResult};
+use crate::model::{ContactValue, Id, Interaction, InteractionMedium, Person, User};
+use crate::ops::interaction_ops;
+use crate::import::logged_with;
+use crate::queries::{contact_queries, person_queries};
+
+/// Topic used for imported calls and text days, which carry no content.
+pub const CALL_TOPIC: &str = "call";
+pub const TEXT_TOPIC: &str = "chat";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallDirection {
+    Incoming,
+    Outgoing,
+}
+
+#[derive(Debug, Clone)]
+pub struct CallRecord {
+    pub number: String,
+    pub contact_name: Option<String>,
+    pub timestamp: NaiveDateTime,
+    pub duration_secs: u32,
+    pub direction: CallDirection,
+}
+
+impl CallRecord {
+    /// "Outgoing call, 12m 5s"
+    pub fn note(&self) -> String {
+        let direction = match self.direction {
+            CallDirection::Incoming => "Incoming",
+            CallDirection::Outgoing => "Outgoing",
+        };
+        format!("{} call, {}", direction, format_duration(self.duration_secs))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SmsRecord {
+    /// Every other party of the message; group MMS have several.
+    pub numbers: Vec<String>,
+    pub contact_name: Option<String>,
+    pub timestamp: NaiveDateTime,
+    pub sent: bool,
+}
+
+/// The contents of a "SMS Backup & Restore" `calls.xml` and/or `sms.xml`.
+#[derive(Debug, Default)]
+pub struct Backup {
+    pub calls: Vec<CallRecord>,
+    pub messages: Vec<SmsRecord>,
+}
+
+impl Backup {
+    pub fn extend(&mut self, other: Backup) {
+        self.calls.extend(other.calls);
+        self.messages.extend(other.messages);
+    }
+}
+
+/// A number in the backup that no person's phone entry matches.
+#[derive(Debug, Clone)]
+pub struct UnmatchedNumber {
+    pub number: String,
+    pub contact_name: Option<String>,
+    pub calls: usize,
+    pub messages: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub calls: usize,
+    pub text_days: usize,
+    pub skipped: usize,
+    pub unmatched: Vec<UnmatchedNumber>,
+}
+
+/// Parses a backup file. Missed, rejected and zero-length calls are dropped,
+/// as are records without a usable date.
+pub fn parse_backup(xml: &str) -> PrmResult<Backup> {
+    let mut backup = Backup::default();
+    let mut found_root = false;
+
+    for (tag, attrs) in elements(xml) {
+        match tag.as_str() {
+            "calls" | "smses" => found_root = true,
+            "call" => {
+                let Some(timestamp) = attr(&attrs, "date").and_then(parse_millis) else { continue };
+                let duration_secs = attr(&attrs, "duration").and_then(|d| d.parse().ok()).unwrap_or(0);
+                let direction = match attr(&attrs, "type") {
+                    Some("1") => CallDirection::Incoming,
+                    Some("2") => CallDirection::Outgoing,
+                    _ => continue,
+                };
+                if duration_secs == 0 {
+                    continue;
+                }
+                backup.calls.push(CallRecord {
+                    number: attr(&attrs, "number").unwrap_or_default().to_string(),
+                    contact_name: contact_name(&attrs),
+                    timestamp,
+                    duration_secs,
+                    direction,
+                });
+            }
+            "sms" | "mms" => {
+                let Some(timestamp) = attr(&attrs, "date").and_then(parse_millis) else { continue };
+                // SMS use `type`, MMS use `msg_box`: 1 = received, 2 = sent
+                let kind = attr(&attrs, "type").or_else(|| attr(&attrs, "msg_box"));
+                let sent = match kind {
+                    Some("1") => false,
+                    Some("2") => true,
+                    _ => continue,
+                };
+                let numbers: Vec<String> = attr(&attrs, "address")
+                    .unwrap_or_default()
+                    .split('~')
+                    .map(|n| n.trim().to_string())
+                    .filter(|n| !n.is_empty())
+                    .collect();
+                if numbers.is_empty() {
+                    continue;
+                }
+                backup.messages.push(SmsRecord {
+                    numbers,
+                    contact_name: contact_name(&attrs),
+                    timestamp,
+                    sent,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    if !found_root {
+        return Err(PrmError::Other(
+            "Not an SMS Backup & Restore file (expected <calls> or <smses>)".into(),
+        ));
+    }
+    Ok(backup)
+}
+
+/// Reduces a phone number to its digits, dropping a leading international `00`.
+pub fn normalize_phone(number: &str) -> String {
+    let digits: String = number.chars().filter(|c| c.is_ascii_digit()).collect();
+    match digits.strip_prefix("00") {
+        Some(rest) if !number.trim_start().starts_with('+') => rest.to_string(),
+        _ => digits,
+    }
+}
+
+/// Two normalized numbers match when their trailing digits agree, so
+/// "+1 (555) 123-4567" matches "555.123.4567". At least seven digits must overlap.
+pub fn phones_match(a: &str, b: &str) -> bool {
+    let len = a.len().min(b.len()).min(10);
+    len >= 7 && a[a.len() - len..] == b[b.len() - len..]
+}
+
+/// Phone numbers of every active person in the network, normalized for matching.
+pub struct PhoneBook {
+    entries: Vec<(String, Person)>,
+}
+
+impl PhoneBook {
+    pub fn load(conn: &Connection, owner_id: Id<User>) -> PrmResult<Self> {
+        let mut entries = Vec::new();
+        for person in person_queries::active_people(conn, owner_id)? {
+            if person.is_self {
+                continue;
+            }
+            for entry in contact_queries::phones_for(conn, person.id)? {
+                if let ContactValue::StringValue { value } = &entry.value {
+                    let digits = normalize_phone(value);
+                    if !digits.is_empty() {
+                        entries.push((digits, person.clone()));
+                    }
+                }
+            }
+        }
+        Ok(Self { entries })
+    }
+
+    pub fn lookup(&self, number: &str) -> Option<&Person> {
+        let digits = normalize_phone(number);
+        self.entries
+            .iter()
+            .find(|(d, _)| phones_match(d, &digits))
+            .map(|(_, p)| p)
+    }
+}
+
+/// Logs matched calls as phone calls and matched messages as one text
+/// interaction per person per day. Calls already logged with the same note on
+/// the same day (as many times as the backup has them), and days that already
+/// have a text interaction, are skipped.
+pub fn import(
+    conn: &Connection,
+    owner_id: Id<User>,
+    backup: &Backup,
+    my_location: &str,
+    include_counts: bool,
+) -> PrmResult<ImportReport> {
+    let book = PhoneBook::load(conn, owner_id)?;
+    let mut report = ImportReport::default();
+    let mut unmatched: BTreeMap<String, UnmatchedNumber> = BTreeMap::new();
+    let mut logged: HashMap<Id<Person>, Vec<Interaction>> = HashMap::new();
+    // Calls seen so far, by exact time, and how many share each day and note
+    let mut seen: HashSet<(Id<Person>, NaiveDateTime, String)> = HashSet::new();
+    let mut same_day: HashMap<(Id<Person>, NaiveDate, String), usize> = HashMap::new();
+
+    for call in &backup.calls {
+        let Some(person) = book.lookup(&call.number) else {
+            note_unmatched(&mut unmatched, &call.number, &call.contact_name).calls += 1;
+            continue;
+        };
+        let date = call.timestamp.date();
+        let note = call.note();
+        if !seen.insert((person.id, call.timestamp, note.clone())) {
+            report.skipped += 1;
+            continue;
+        }
+        let nth = same_day.entry((person.id, date, note.clone())).or_default();
+        *nth += 1;
+        let already = logged_with(conn, &mut logged, person.id)?
+            .iter()
+            .filter(|i| i.medium == InteractionMedium::PhoneCall && i.date == date && i.note.as_deref() == Some(note.as_str()))
+            .count();
+        if *nth <= already {
+            report.skipped += 1;
+            continue;
+        }
+        interaction_ops::log_remote(
+            conn,
+            owner_id,
+            person.id,
+            InteractionMedium::PhoneCall,
+            my_location,
+            person.location.as_deref(),
+            vec![CALL_TOPIC.to_string()],
+            Some(&note),
+            date,
+        )?;
+        report.calls += 1;
+    }
+
+    // (sent, received) per day per matched person, keyed by index into `people`
+    let mut text_days: BTreeMap<(NaiveDate, usize), (usize, usize)> = BTreeMap::new();
+    let mut people: Vec<&Person> = Vec::new();
+    for sms in &backup.messages {
+        for number in &sms.numbers {
+            let Some(person) = book.lookup(number) else {
+                note_unmatched(&mut unmatched, number, &sms.contact_name).messages += 1;
+                continue;
+            };
+            let idx = match people.iter().position(|p| p.id == person.id) {
+                Some(idx) => idx,
+                None => {
+                    people.push(person);
+                    people.len() - 1
+                }
+            };
+            let counts = text_days.entry((sms.timestamp.date(), idx)).or_default();
+            if sms.sent {
+                counts.0 += 1;
+            } else {
+                counts.1 += 1;
+            }
+        }
+    }
+
+    for ((date, idx), (sent, received)) in text_days {
+        let person = people[idx];
+        let person_id = person.id;
+        let already = logged_with(conn, &mut logged, person_id)?
+            .iter()
+            .any(|i| i.medium == InteractionMedium::Text && i.date == date);
+        if already {
+            report.skipped += 1;
+            continue;
+        }
+        let note = include_counts.then(|| format!("SMS: {} sent, {} received", sent, received));
+        interaction_ops::log_remote(
+            conn,
+            owner_id,
+            person_id,
+            InteractionMedium::Text,
+            my_location,
+            person.location.as_deref(),
+            vec![TEXT_TOPIC.to_string()],
+            note.as_deref(),
+            date,
+        )?;
+        report.text_days += 1;
+    }
+
+    report.unmatched = unmatched.into_values().collect();
+    report
+        .unmatched
+        .sort_by_key(|u| std::cmp::Reverse(u.calls + u.messages));
+    Ok(report)
+}
+
+fn note_unmatched<'a>(
+    unmatched: &'a mut BTreeMap<String, UnmatchedNumber>,
+    number: &str,
+    contact_name: &Option<String>,
+) -> &'a mut UnmatchedNumber {
+    let entry = unmatched
+        .entry(normalize_phone(number))
+        .or_insert_with(|| UnmatchedNumber {
+            number: number.to_string(),
+            contact_name: None,
+            calls: 0,
+            messages: 0,
+        });
+    if entry.contact_name.is_none() {
+        entry.contact_name = contact_name.clone();
+    }
+    entry
+}
+
+/// "1h 2m", "12m 5s", "40s"
+fn format_duration(secs: u32) -> String {
+    let (h, m, s) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+    if h > 0 {
+        format!("{}h {}m", h, m)
+    } else if m > 0 {
+        format!("{}m {}s", m, s)
+    } else {
+        format!("{}s", s)
+    }
+}
+
+fn parse_millis(s: &str) -> Option<NaiveDateTime> {
+    let millis: i64 = s.parse().ok()?;
+    DateTime::from_timestamp_millis(millis).map(|utc| utc.with_timezone(&Local).naive_local())
+}
+
+fn contact_name(attrs: &[(String, String)]) -> Option<String> {
+    attr(attrs, "contact_name")
+        .filter(|n| !n.is_empty() && *n != "(Unknown)")
+        .map(|n| n.to_string())
+}
+
+fn attr<'a>(attrs: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    attrs.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+}
+
+/// Start tags in the document with their attributes. The backup files are
+/// flat lists of attribute-only elements, so this is all the XML we need.
+fn elements(xml: &str) -> Vec<(String, Vec<(String, String)>)> {
+    let mut out = Vec::new();
+    let mut rest = xml;
+
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        if rest.starts_with('?') || rest.starts_with('!') || rest.starts_with('/') {
+            match rest.find('>') {
+                Some(end) => rest = &rest[end + 1..],
+                None => break,
+            }
+            continue;
+        }
+
+        let name_end = rest
+            .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+            .unwrap_or(rest.len());
+        let name = rest[..name_end].to_string();
+        rest = &rest[name_end..];
+
+        let mut attrs = Vec::new();
+        loop {
+            rest = rest.trim_start();
+            if rest.is_empty() || rest.starts_with('>') || rest.starts_with("/>") {
+                break;
+            }
+            let Some(eq) = rest.find('=') else { break };
+            let key = rest[..eq].trim().to_string();
+            rest = rest[eq + 1..].trim_start();
+            let Some(quote) = rest.chars().next().filter(|c| *c == '"' || *c == '\'') else { break };
+            let Some(close) = rest[1..].find(quote) else { break };
+            attrs.push((key, unescape(&rest[1..close + 1])));
+            rest = &rest[close + 2..];
+        }
+        out.push((name, attrs));
+    }
+    out
+}
+
+fn unescape(s: &str) -> String {
+    if !s.contains('&') {
+        return s.to_string();
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let Some(semi) = rest.find(';') else { break };
+        let entity = &rest[1..semi];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .and_then(|h| u32::from_str_radix(h, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|d| d.parse().ok()))
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_and_matches_phone_formats() {
+        assert_eq!(normalize_phone("+1 (555) 123-4567"), "15551234567");
+        assert_eq!(normalize_phone("0030 210 1234567"), "302101234567");
+        assert!(phones_match(&normalize_phone("+1 555-123-4567"), &normalize_phone("555.123.4567")));
+        assert!(!phones_match(&normalize_phone("555-123-4567"), &normalize_phone("555-123-4568")));
+        assert!(!phones_match("4567", "4567"));
+    }
+
+    #[test]
+    fn parses_calls_skipping_missed() {
+        let xml = r#"<?xml version='1.0' encoding='UTF-8' standalone='yes' ?>
+<calls count="3">
+  <call number="+15551234567" duration="725" date="1717243200000" type="2" contact_name="Alice" />
+  <call number="+15551234567" duration="0" date="1717243300000" type="3" contact_name="Alice" />
+  <call number="5559990000" duration="40" date="1717243400000" type="1" contact_name="(Unknown)" />
+</calls>"#;
+        let backup = parse_backup(xml).unwrap();
+        assert_eq!(backup.calls.len(), 2);
+        assert_eq!(backup.calls[0].note(), "Outgoing call, 12m 5s");
+        assert_eq!(backup.calls[0].contact_name.as_deref(), Some("Alice"));
+        assert!(backup.calls[1].contact_name.is_none());
+    }
+
+    #[test]
+    fn parses_sms_and_group_mms() {
+        let xml = r#"<smses count="2">
+  <sms protocol="0" address="+15551234567" date="1717243200000" type="1" body="see you &amp; bye &lt;3" contact_name="Alice" />
+  <mms date="1717243200000" msg_box="2" address="+15551234567~+15559990000" contact_name="Alice, Bob">
+    <parts><part seq="0" ct="text/plain" text="hi all" /></parts>
+  </mms>
+</smses>"#;
+        let backup = parse_backup(xml).unwrap();
+        assert_eq!(backup.messages.len(), 2);
+        assert!(!backup.messages[0].sent);
+        assert_eq!(backup.messages[1].numbers.len(), 2);
+        assert!(backup.messages[1].sent);
+    }
+
+    #[test]
+    fn rejects_other_xml() {
+        assert!(parse_backup("<contacts><contact name=\"x\" /></contacts>").is_err());
+    }
+
+    #[test]
+    fn unescapes_entities() {
+        assert_eq!(unescape("a &amp; b &#39;c&#x27; &bogus"), "a & b 'c' &bogus");
+    }
+}
@@ -0,0 +1,359 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, Utc};
+use rusqlite::Connection;
+
+use crate::error::PrmResult;
+use crate::import::logged_with;
+use crate::model::{ContactValue, Id, Interaction, Person, User};
+use crate::queries::{contact_queries, person_queries};
+
+/// Hosts whose links mark an event as a video call.
+const VIDEO_HOSTS: &[&str] = &[
+    "zoom.us",
+    "meet.google.com",
+    "teams.microsoft.com",
+    "teams.live.com",
+    "webex.com",
+    "whereby.com",
+    "meet.jit.si",
+    "facetime.apple.com",
+];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Attendee {
+    pub email: Option<String>,
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CalendarEvent {
+    pub summary: Option<String>,
+    pub location: Option<String>,
+    pub description: Option<String>,
+    pub date: NaiveDate,
+    pub attendees: Vec<Attendee>,
+    /// Set by calendar apps that attach a meeting link (e.g. `X-GOOGLE-CONFERENCE`).
+    pub has_conference: bool,
+}
+
+impl CalendarEvent {
+    /// Whether the event happened over video rather than in person.
+    pub fn is_video(&self) -> bool {
+        if self.has_conference {
+            return true;
+        }
+        [&self.location, &self.description]
+            .iter()
+            .filter_map(|f| f.as_deref())
+            .any(|text| {
+                let lower = text.to_lowercase();
+                VIDEO_HOSTS.iter().any(|host| lower.contains(host))
+            })
+    }
+
+    /// The location, unless it is just a meeting link.
+    pub fn physical_location(&self) -> Option<&str> {
+        self.location
+            .as_deref()
+            .filter(|l| !l.starts_with("http://") && !l.starts_with("https://"))
+            .filter(|l| !l.trim().is_empty())
+    }
+}
+
+/// An event matched to people in the network.
+#[derive(Debug, Clone)]
+pub struct Proposal {
+    pub event: CalendarEvent,
+    pub people: Vec<Person>,
+}
+
+/// Parses the VEVENTs of an iCalendar file. Cancelled events, events without
+/// a start date and attendees who declined are dropped. Recurring events are
+/// only counted at their first occurrence.
+pub fn parse_calendar(text: &str) -> Vec<CalendarEvent> {
+    let mut events = Vec::new();
+    let mut current: Option<(CalendarEvent, bool)> = None;
+
+    for line in unfold(text) {
+        let Some((name, params, value)) = split_property(&line) else { continue };
+
+        match (name.as_str(), value.as_str()) {
+            ("BEGIN", "VEVENT") => {
+                current = Some((
+                    CalendarEvent {
+                        summary: None,
+                        location: None,
+                        description: None,
+                        date: NaiveDate::MIN,
+                        attendees: Vec::new(),
+                        has_conference: false,
+                    },
+                    false,
+                ));
+                continue;
+            }
+            ("END", "VEVENT") => {
+                if let Some((event, cancelled)) = current.take() {
+                    if !cancelled && event.date != NaiveDate::MIN {
+                        events.push(event);
+                    }
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        let Some((event, cancelled)) = current.as_mut() else { continue };
+        match name.as_str() {
+            "SUMMARY" => event.summary = non_empty(unescape(&value)),
+            "LOCATION" => event.location = non_empty(unescape(&value)),
+            "DESCRIPTION" => event.description = non_empty(unescape(&value)),
+            "DTSTART" => {
+                if let Some(date) = parse_start(&value) {
+                    event.date = date;
+                }
+            }
+            "STATUS" => *cancelled = value.eq_ignore_ascii_case("CANCELLED"),
+            "X-GOOGLE-CONFERENCE" | "X-MICROSOFT-ONLINEMEETINGCONFLINK" => event.has_conference = true,
+            "ATTENDEE" | "ORGANIZER" => {
+                if param(&params, "PARTSTAT").is_some_and(|p| p.eq_ignore_ascii_case("DECLINED")) {
+                    continue;
+                }
+                let email = value
+                    .get(..7)
+                    .filter(|p| p.eq_ignore_ascii_case("mailto:"))
+                    .map(|_| value[7..].trim().to_lowercase())
+                    .or_else(|| param(&params, "EMAIL").map(|e| e.to_lowercase()))
+                    .filter(|e| !e.is_empty());
+                let name = param(&params, "CN").and_then(|n| non_empty(n.to_string()));
+                let attendee = Attendee { email, name };
+                if !event.attendees.contains(&attendee) {
+                    event.attendees.push(attendee);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    events
+}
+
+/// Matches events between `from` and `to` (inclusive) to active people, by
+/// attendee email against their email entries, by attendee name against their
+/// name or nickname, and by their full name appearing in the summary. Events
+/// with no match, and events already logged for every matched person on that
+/// day, are left out.
+pub fn propose(
+    conn: &Connection,
+    owner_id: Id<User>,
+    events: Vec<CalendarEvent>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> PrmResult<Vec<Proposal>> {
+    let people: Vec<Person> = person_queries::active_people(conn, owner_id)?
+        .into_iter()
+        .filter(|p| !p.is_self)
+        .collect();
+
+    let mut emails: Vec<(String, usize)> = Vec::new();
+    for (idx, person) in people.iter().enumerate() {
+        for entry in contact_queries::emails_for(conn, person.id)? {
+            if let ContactValue::StringValue { value } = &entry.value {
+                emails.push((value.trim().to_lowercase(), idx));
+            }
+        }
+    }
+
+    let mut proposals = Vec::new();
+    let mut logged: HashMap<Id<Person>, Vec<Interaction>> = HashMap::new();
+    for event in events.into_iter().filter(|e| e.date >= from && e.date <= to) {
+        let mut matched: Vec<usize> = Vec::new();
+        for attendee in &event.attendees {
+            let by_email = attendee
+                .email
+                .as_ref()
+                .and_then(|e| emails.iter().find(|(addr, _)| addr == e))
+                .map(|(_, idx)| *idx);
+            let by_name = attendee.name.as_ref().and_then(|n| {
+                people.iter().position(|p| {
+                    p.name.eq_ignore_ascii_case(n)
+                        || p.nickname.as_deref().is_some_and(|nick| nick.eq_ignore_ascii_case(n))
+                })
+            });
+            if let Some(idx) = by_email.or(by_name) {
+                if !matched.contains(&idx) {
+                    matched.push(idx);
+                }
+            }
+        }
+        if let Some(summary) = &event.summary {
+            for (idx, person) in people.iter().enumerate() {
+                if !matched.contains(&idx) && contains_name(summary, &person.name) {
+                    matched.push(idx);
+                }
+            }
+        }
+        if matched.is_empty() {
+            continue;
+        }
+
+        let mut all_logged = true;
+        for idx in &matched {
+            all_logged &= logged_with(conn, &mut logged, people[*idx].id)?.iter().any(|i| i.date == event.date);
+        }
+        if all_logged {
+            continue;
+        }
+
+        proposals.push(Proposal {
+            people: matched.iter().map(|idx| people[*idx].clone()).collect(),
+            event,
+        });
+    }
+
+    proposals.sort_by_key(|p| p.event.date);
+    Ok(proposals)
+}
+
+/// Whole-word, case-insensitive search for a name in free text.
+fn contains_name(text: &str, name: &str) -> bool {
+    let text = text.to_lowercase();
+    let name = name.trim().to_lowercase();
+    if name.is_empty() {
+        return false;
+    }
+    text.match_indices(&name).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + name.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+/// Joins folded content lines (continuations start with a space or tab).
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in text.lines() {
+        let raw = raw.trim_end_matches('\r');
+        match raw.strip_prefix([' ', '\t']) {
+            Some(cont) if !lines.is_empty() => lines.last_mut().unwrap().push_str(cont),
+            _ => lines.push(raw.to_string()),
+        }
+    }
+    lines
+}
+
+/// Splits `NAME;PARAM=x;PARAM2="y:z":value` into its parts.
+fn split_property(line: &str) -> Option<(String, Vec<(String, String)>, String)> {
+    let mut in_quotes = false;
+    let colon = line.char_indices().find(|(_, c)| {
+        if *c == '"' {
+            in_quotes = !in_quotes;
+        }
+        *c == ':' && !in_quotes
+    })?.0;
+
+    let head = &line[..colon];
+    let value = line[colon + 1..].to_string();
+    let mut parts = head.split(';');
+    let name = parts.next()?.trim().to_uppercase();
+    let params = parts
+        .filter_map(|p| p.split_once('='))
+        .map(|(k, v)| (k.trim().to_uppercase(), v.trim().trim_matches('"').to_string()))
+        .collect();
+    Some((name, params, value))
+}
+
+fn param<'a>(params: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    params.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+}
+
+/// `20240601`, `20240601T120000` (floating or TZID) or `20240601T120000Z` (UTC,
+/// converted to the local date).
+fn parse_start(value: &str) -> Option<NaiveDate> {
+    let value = value.trim();
+    if let Some(utc) = value.strip_suffix('Z') {
+        let dt = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        let utc: DateTime<Utc> = DateTime::from_naive_utc_and_offset(dt, Utc);
+        return Some(utc.with_timezone(&Local).date_naive());
+    }
+    NaiveDate::parse_from_str(value.get(..8)?, "%Y%m%d").ok()
+}
+
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => out.push('\n'),
+                Some(other) => out.push(other),
+                None => {}
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out.trim().to_string()
+}
+
+fn non_empty(s: String) -> Option<String> {
+    if s.trim().is_empty() {
+        None
+    } else {
+        Some(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CALENDAR: &str = "BEGIN:VCALENDAR\r
+VERSION:2.0\r
+BEGIN:VEVENT\r
+DTSTART;TZID=Europe/Athens:20240601T190000\r
+SUMMARY:Dinner with Alice\\, Bob\r
+LOCATION:Taverna Platanos\\, Athens\r
+ATTENDEE;CN=Bob Jones;PARTSTAT=ACCEPTED:mailto:Bob@Example.com\r
+ATTENDEE;CN=\"Carol: the boss\";PARTSTAT=DECLINED:mailto:carol@example.com\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+DTSTART;VALUE=DATE:20240603\r
+SUMMARY:Sync\r
+DESCRIPTION:Join: https://us02web.zoom.us/j/123\r
+ 456\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+DTSTART:20240604T100000Z\r
+STATUS:CANCELLED\r
+SUMMARY:Cancelled\r
+END:VEVENT\r
+END:VCALENDAR\r
+";
+
+    #[test]
+    fn parses_events_attendees_and_escapes() {
+        let events = parse_calendar(CALENDAR);
+        assert_eq!(events.len(), 2);
+
+        let dinner = &events[0];
+        assert_eq!(dinner.date, NaiveDate::from_ymd_opt(2024, 6, 1).unwrap());
+        assert_eq!(dinner.summary.as_deref(), Some("Dinner with Alice, Bob"));
+        assert_eq!(dinner.physical_location(), Some("Taverna Platanos, Athens"));
+        assert_eq!(dinner.attendees.len(), 1);
+        assert_eq!(dinner.attendees[0].email.as_deref(), Some("bob@example.com"));
+        assert!(!dinner.is_video());
+
+        let sync = &events[1];
+        assert_eq!(sync.description.as_deref(), Some("Join: https://us02web.zoom.us/j/123456"));
+        assert!(sync.is_video());
+    }
+
+    #[test]
+    fn matches_whole_names_only() {
+        assert!(contains_name("Dinner with Alice, Bob", "alice"));
+        assert!(!contains_name("Dinner with Alicea", "Alice"));
+        assert!(contains_name("Lunch w/ Ana María", "ana maría"));
+    }
+}
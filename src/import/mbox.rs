@@ -0,0 +1,516 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Local, NaiveDate};
+use rusqlite::Connection;
+
+use crate::error::PrmResult;
+use crate::model::{ContactValue, Id, Interaction, InteractionMedium, Person, User};
+use crate::ops::interaction_ops;
+use crate::import::logged_with;
+use crate::queries::{contact_queries, person_queries};
+
+/// Address fragments of automated senders that are never interactions.
+pub const DEFAULT_IGNORE: &[&str] = &[
+    "noreply",
+    "no-reply",
+    "donotreply",
+    "do-not-reply",
+    "mailer-daemon",
+    "postmaster@",
+    "notifications@",
+];
+
+/// Topic used when a message has no subject.
+const NO_SUBJECT_TOPIC: &str = "email";
+
+/// The headers of one message that matter for interactions.
+#[derive(Debug, Clone)]
+pub struct MailMessage {
+    pub from: Option<String>,
+    pub to: Vec<String>,
+    pub cc: Vec<String>,
+    pub subject: String,
+    pub date: NaiveDate,
+    /// Root of the thread: the first `References` id, else `In-Reply-To`, else
+    /// the message's own id, else its normalized subject.
+    pub thread: String,
+    /// Sent via a mailing list or marked as bulk/automated mail.
+    pub bulk: bool,
+}
+
+/// Which messages count as personal mail.
+#[derive(Debug, Clone)]
+pub struct MailFilter {
+    /// The user's own addresses, lowercase.
+    pub self_addresses: Vec<String>,
+    /// Substrings of addresses to ignore, lowercase.
+    pub ignore: Vec<String>,
+    pub include_lists: bool,
+}
+
+impl MailFilter {
+    pub fn new(self_addresses: Vec<String>) -> Self {
+        Self {
+            self_addresses: self_addresses.into_iter().map(|a| a.trim().to_lowercase()).collect(),
+            ignore: DEFAULT_IGNORE.iter().map(|s| s.to_string()).collect(),
+            include_lists: false,
+        }
+    }
+
+    pub fn is_self(&self, address: &str) -> bool {
+        self.self_addresses.iter().any(|a| a == address)
+    }
+
+    pub fn is_ignored(&self, address: &str) -> bool {
+        self.ignore.iter().any(|pattern| address.contains(pattern.as_str()))
+    }
+
+    /// The other people in a message worth logging: the recipients of mail
+    /// the user sent, or the sender of mail the user received. Empty for list
+    /// mail, self-sent mail and mail that does not involve the user.
+    pub fn counterparts(&self, message: &MailMessage) -> Vec<String> {
+        if message.bulk && !self.include_lists {
+            return Vec::new();
+        }
+        let Some(from) = &message.from else { return Vec::new() };
+        if self.is_ignored(from) {
+            return Vec::new();
+        }
+
+        let recipients = message.to.iter().chain(&message.cc);
+        if self.is_self(from) {
+            let mut out: Vec<String> = Vec::new();
+            for r in recipients {
+                if !self.is_self(r) && !self.is_ignored(r) && !out.contains(r) {
+                    out.push(r.clone());
+                }
+            }
+            out
+        } else if message.to.iter().chain(&message.cc).any(|r| self.is_self(r)) || self.self_addresses.is_empty() {
+            vec![from.clone()]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub created: usize,
+    pub skipped: usize,
+    pub filtered: usize,
+    /// Counterpart addresses that match nobody, with message counts.
+    pub unmatched: Vec<(String, usize)>,
+}
+
+/// Reads the headers of every message in an mbox file. A `From ` line after a
+/// blank line starts a new message. Messages without a parseable `Date`
+/// header are dropped.
+pub fn parse_mbox(text: &str) -> Vec<MailMessage> {
+    let mut messages = Vec::new();
+    let mut headers: Vec<(String, String)> = Vec::new();
+    let mut in_headers = false;
+    let mut previous_blank = true;
+
+    for line in text.lines() {
+        let line = line.trim_end_matches('\r');
+        let separator = previous_blank && line.starts_with("From ");
+        previous_blank = line.is_empty();
+        if separator {
+            if let Some(m) = build_message(&headers) {
+                messages.push(m);
+            }
+            headers.clear();
+            in_headers = true;
+            continue;
+        }
+        if !in_headers {
+            continue;
+        }
+        if line.is_empty() {
+            in_headers = false;
+        } else if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+    if let Some(m) = build_message(&headers) {
+        messages.push(m);
+    }
+    messages
+}
+
+/// Logs one email interaction per person per thread per day. Threads already
+/// logged for that person on that day (same subject topic) are skipped.
+pub fn import(
+    conn: &Connection,
+    owner_id: Id<User>,
+    messages: &[MailMessage],
+    filter: &MailFilter,
+    my_location: &str,
+    include_counts: bool,
+) -> PrmResult<ImportReport> {
+    let people: Vec<Person> = person_queries::active_people(conn, owner_id)?
+        .into_iter()
+        .filter(|p| !p.is_self)
+        .collect();
+    let mut book: Vec<(String, usize)> = Vec::new();
+    for (idx, person) in people.iter().enumerate() {
+        for entry in contact_queries::emails_for(conn, person.id)? {
+            if let ContactValue::StringValue { value } = &entry.value {
+                book.push((value.trim().to_lowercase(), idx));
+            }
+        }
+    }
+
+    let mut report = ImportReport::default();
+    let mut unmatched: BTreeMap<String, usize> = BTreeMap::new();
+    // (date, person, thread) -> (subject, sent, received)
+    let mut threads: BTreeMap<(NaiveDate, usize, String), (String, usize, usize)> = BTreeMap::new();
+
+    for message in messages {
+        let counterparts = filter.counterparts(message);
+        if counterparts.is_empty() {
+            report.filtered += 1;
+            continue;
+        }
+        let sent = message.from.as_deref().is_some_and(|f| filter.is_self(f));
+        for address in counterparts {
+            let Some((_, idx)) = book.iter().find(|(a, _)| *a == address) else {
+                *unmatched.entry(address).or_default() += 1;
+                continue;
+            };
+            let entry = threads
+                .entry((message.date, *idx, message.thread.clone()))
+                .or_insert_with(|| (message.subject.clone(), 0, 0));
+            if sent {
+                entry.1 += 1;
+            } else {
+                entry.2 += 1;
+            }
+        }
+    }
+
+    let mut logged: HashMap<Id<Person>, Vec<Interaction>> = HashMap::new();
+    for ((date, idx, _), (subject, sent, received)) in threads {
+        let person = &people[idx];
+        let topic = topic_for(&subject);
+        let already = logged_with(conn, &mut logged, person.id)?
+            .iter()
+            .any(|i| i.medium == InteractionMedium::Email && i.date == date && i.topics.contains(&topic));
+        if already {
+            report.skipped += 1;
+            continue;
+        }
+        let note = include_counts.then(|| format!("Email: {} sent, {} received", sent, received));
+        interaction_ops::log_remote(
+            conn,
+            owner_id,
+            person.id,
+            InteractionMedium::Email,
+            my_location,
+            person.location.as_deref(),
+            vec![topic],
+            note.as_deref(),
+            date,
+        )?;
+        report.created += 1;
+    }
+
+    report.unmatched = unmatched.into_iter().collect();
+    report.unmatched.sort_by_key(|(_, n)| std::cmp::Reverse(*n));
+    Ok(report)
+}
+
+/// The subject without reply/forward prefixes, used as the interaction topic.
+pub fn topic_for(subject: &str) -> String {
+    let cleaned = strip_reply_prefixes(subject);
+    if cleaned.is_empty() {
+        NO_SUBJECT_TOPIC.to_string()
+    } else {
+        cleaned
+    }
+}
+
+fn build_message(headers: &[(String, String)]) -> Option<MailMessage> {
+    let get = |name: &str| headers.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str());
+
+    let date = parse_date(get("date")?)?;
+    let subject = get("subject").map(decode_words).unwrap_or_default();
+    let from = get("from").and_then(|f| addresses(f).into_iter().next());
+    let to = get("to").map(addresses).unwrap_or_default();
+    let cc = get("cc").map(addresses).unwrap_or_default();
+
+    let first_id = |v: &str| v.split_whitespace().next().map(|s| s.trim().to_string());
+    let thread = get("references")
+        .and_then(first_id)
+        .or_else(|| get("in-reply-to").and_then(first_id))
+        .or_else(|| get("message-id").and_then(first_id))
+        .unwrap_or_else(|| strip_reply_prefixes(&subject).to_lowercase());
+
+    let precedence = get("precedence").unwrap_or_default().to_lowercase();
+    let auto_submitted = get("auto-submitted").unwrap_or("no").to_lowercase();
+    let bulk = get("list-id").is_some()
+        || get("list-unsubscribe").is_some()
+        || matches!(precedence.as_str(), "bulk" | "list" | "junk")
+        || auto_submitted != "no";
+
+    Some(MailMessage { from, to, cc, subject, date, thread, bulk })
+}
+
+/// RFC 2822 date, ignoring trailing comments like "(UTC)", as a local date.
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    let value = match value.find('(') {
+        Some(idx) => value[..idx].trim(),
+        None => value.trim(),
+    };
+    DateTime::parse_from_rfc2822(value)
+        .ok()
+        .map(|dt| dt.with_timezone(&Local).date_naive())
+}
+
+/// Lowercase addresses from an address list such as
+/// `"Doe, Jane" <jane@example.com>, bob@example.com`.
+fn addresses(value: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    // Text outside quotes and angle brackets, and the text inside them
+    let mut current = String::new();
+    let mut angle: Option<String> = None;
+    let mut in_quotes = false;
+    let mut in_angle = false;
+
+    for c in value.chars().chain(std::iter::once(',')) {
+        match c {
+            '"' if !in_angle => in_quotes = !in_quotes,
+            '<' if !in_quotes && !in_angle => {
+                in_angle = true;
+                angle = Some(String::new());
+            }
+            '>' if in_angle => in_angle = false,
+            ',' if !in_quotes && !in_angle => {
+                // Without angle brackets, the bare address is the last word with an @
+                let addr = match angle.take() {
+                    Some(addr) => addr,
+                    None => current.split_whitespace().rfind(|w| w.contains('@')).unwrap_or_default().to_string(),
+                };
+                let addr = addr.trim().to_lowercase();
+                if addr.contains('@') && !out.contains(&addr) {
+                    out.push(addr);
+                }
+                current.clear();
+            }
+            c if in_angle => angle.get_or_insert_with(String::new).push(c),
+            c if !in_quotes => current.push(c),
+            _ => {}
+        }
+    }
+    out
+}
+
+fn strip_reply_prefixes(subject: &str) -> String {
+    let mut s = subject.trim();
+    loop {
+        let lower = s.to_lowercase();
+        let prefix = ["re:", "fwd:", "fw:", "aw:", "sv:", "wg:", "tr:"]
+            .iter()
+            .find(|p| lower.starts_with(*p));
+        match prefix {
+            Some(p) => s = s[p.len()..].trim_start(),
+            None => break,
+        }
+    }
+    s.trim().to_string()
+}
+
+/// Decodes RFC 2047 encoded words (`=?UTF-8?B?...?=` and `=?...?Q?...?=`).
+fn decode_words(value: &str) -> String {
+    let mut out = String::new();
+    let mut rest = value;
+    let mut last_was_word = false;
+
+    while let Some(start) = rest.find("=?") {
+        let Some(decoded_len) = decode_word(&rest[start..]) else {
+            out.push_str(&rest[..start + 2]);
+            rest = &rest[start + 2..];
+            last_was_word = false;
+            continue;
+        };
+        let (decoded, len) = decoded_len;
+        let between = &rest[..start];
+        // Whitespace between adjacent encoded words is dropped
+        if !(last_was_word && between.trim().is_empty()) {
+            out.push_str(between);
+        }
+        out.push_str(&decoded);
+        rest = &rest[start + len..];
+        last_was_word = true;
+    }
+    out.push_str(rest);
+    out.trim().to_string()
+}
+
+/// Decodes one encoded word at the start of `s`, returning it and its length.
+fn decode_word(s: &str) -> Option<(String, usize)> {
+    let body = &s[2..];
+    let q1 = body.find('?')?;
+    let charset = body[..q1].to_lowercase();
+    let encoding = body.get(q1 + 1..q1 + 2)?.to_lowercase();
+    if body.get(q1 + 2..q1 + 3)? != "?" {
+        return None;
+    }
+    let text_start = q1 + 3;
+    let end = body[text_start..].find("?=")? + text_start;
+    let text = &body[text_start..end];
+
+    let bytes = match encoding.as_str() {
+        "b" => base64_decode(text)?,
+        "q" => q_decode(text),
+        _ => return None,
+    };
+    let decoded = if charset.starts_with("iso-8859-1") || charset == "latin1" || charset == "windows-1252" {
+        bytes.iter().map(|b| *b as char).collect()
+    } else {
+        String::from_utf8_lossy(&bytes).into_owned()
+    };
+    Some((decoded, 2 + end + 2))
+}
+
+fn q_decode(text: &str) -> Vec<u8> {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'_' => out.push(b' '),
+            b'=' => {
+                let hex = bytes
+                    .get(i + 1..i + 3)
+                    .and_then(|h| std::str::from_utf8(h).ok())
+                    .and_then(|h| u8::from_str_radix(h, 16).ok());
+                match hex {
+                    Some(b) => {
+                        out.push(b);
+                        i += 2;
+                    }
+                    None => out.push(b'='),
+                }
+            }
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    out
+}
+
+fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in text.bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => break,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_address_lists() {
+        assert_eq!(
+            addresses("\"Doe, Jane\" <Jane@Example.com>, bob@example.com, Carol <carol@example.com>"),
+            vec!["jane@example.com", "bob@example.com", "carol@example.com"]
+        );
+        assert!(addresses("undisclosed-recipients:;").is_empty());
+        assert_eq!(addresses("a@x.com , b@y.com "), vec!["a@x.com", "b@y.com"]);
+        assert_eq!(addresses("jane@example.com (Jane Doe)"), vec!["jane@example.com"]);
+    }
+
+    #[test]
+    fn decodes_encoded_subjects() {
+        assert_eq!(decode_words("=?UTF-8?B?Q2Fmw6k=?= plans"), "Café plans");
+        assert_eq!(decode_words("=?iso-8859-1?Q?Caf=E9_plans?="), "Café plans");
+        assert_eq!(decode_words("=?UTF-8?Q?a?= =?UTF-8?Q?b?="), "ab");
+        assert_eq!(decode_words("plain"), "plain");
+    }
+
+    #[test]
+    fn strips_reply_prefixes() {
+        assert_eq!(topic_for("Re: Fwd: RE: Dinner"), "Dinner");
+        assert_eq!(topic_for("Re:"), "email");
+    }
+
+    #[test]
+    fn threads_and_filters_messages() {
+        let mbox = "From alice@example.com Sat Jun  1 10:00:00 2024\n\
+From: Alice <alice@example.com>\n\
+To: me@example.com\n\
+Subject: Dinner\n\
+Message-ID: <root@example.com>\n\
+Date: Sat, 1 Jun 2024 12:00:00 +0000 (UTC)\n\
+\n\
+body\n\
+\n\
+From me@example.com Sat Jun  1 11:00:00 2024\n\
+From: me@example.com\n\
+To: alice@example.com,\n\
+\tbob@example.com\n\
+Subject: Re: Dinner\n\
+References: <root@example.com>\n\
+Date: Sat, 1 Jun 2024 13:00:00 +0000\n\
+\n\
+body\n\
+\n\
+From news@list.example.com Sat Jun  1 11:00:00 2024\n\
+From: news@list.example.com\n\
+To: me@example.com\n\
+List-Id: <news.list.example.com>\n\
+Date: Sat, 1 Jun 2024 13:00:00 +0000\n\
+\n";
+        let messages = parse_mbox(mbox);
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0].thread, messages[1].thread);
+        assert_eq!(messages[1].to, vec!["alice@example.com", "bob@example.com"]);
+        assert!(messages[2].bulk);
+
+        let filter = MailFilter::new(vec!["Me@example.com".into()]);
+        assert_eq!(filter.counterparts(&messages[0]), vec!["alice@example.com"]);
+        assert_eq!(filter.counterparts(&messages[1]), vec!["alice@example.com", "bob@example.com"]);
+        assert!(filter.counterparts(&messages[2]).is_empty());
+    }
+
+    #[test]
+    fn self_sent_mail_has_no_counterparts() {
+        let message = MailMessage {
+            from: Some("me@example.com".into()),
+            to: vec!["me@example.com".into()],
+            cc: vec![],
+            subject: "note to self".into(),
+            date: NaiveDate::from_ymd_opt(2024, 6, 1).unwrap(),
+            thread: "x".into(),
+            bulk: false,
+        };
+        let filter = MailFilter::new(vec!["me@example.com".into()]);
+        assert!(filter.counterparts(&message).is_empty());
+    }
+}
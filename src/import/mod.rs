@@ -0,0 +1,25 @@
+pub mod android;
+pub mod ics;
+pub mod mbox;
+pub mod whatsapp;
+
+use std::collections::hash_map::{Entry, HashMap};
+
+use rusqlite::Connection;
+
+use crate::error::PrmResult;
+use crate::model::{Id, Interaction, Person};
+use crate::queries::interaction_queries;
+
+/// Interactions already logged with a person, loaded once per person into
+/// `logged`, so an import doesn't query them for every record.
+fn logged_with<'a>(
+    conn: &Connection,
+    logged: &'a mut HashMap<Id<Person>, Vec<Interaction>>,
+    person_id: Id<Person>,
+) -> PrmResult<&'a [Interaction]> {
+    Ok(match logged.entry(person_id) {
+        Entry::Occupied(e) => e.into_mut(),
+        Entry::Vacant(e) => e.insert(interaction_queries::interactions_with(conn, person_id)?),
+    })
+}
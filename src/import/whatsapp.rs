@@ -0,0 +1,358 @@
+use std::collections::BTreeMap;
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use rusqlite::Connection;
+
+use crate::error::PrmResult;
+use crate::model::{Id, InteractionMedium, Person, User};
+use crate::ops::interaction_ops;
+use crate::queries::interaction_queries;
+
+/// Order of the day and month in a numeric export date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateOrder {
+    DayFirst,
+    MonthFirst,
+}
+
+impl DateOrder {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "dmy" | "dm" | "day" => Some(DateOrder::DayFirst),
+            "mdy" | "md" | "month" => Some(DateOrder::MonthFirst),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ChatMessage {
+    pub timestamp: NaiveDateTime,
+    /// `None` for system lines such as "Messages are end-to-end encrypted".
+    pub sender: Option<String>,
+    pub text: String,
+}
+
+/// All messages exchanged on one calendar day.
+#[derive(Debug, Clone)]
+pub struct ChatDay {
+    pub date: NaiveDate,
+    pub messages: Vec<ChatMessage>,
+}
+
+impl ChatDay {
+    pub fn message_count(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// Message counts per sender, in order of first appearance.
+    pub fn senders(&self) -> Vec<(String, usize)> {
+        let mut counts: Vec<(String, usize)> = Vec::new();
+        for m in &self.messages {
+            let sender = m.sender.as_deref().unwrap_or_default();
+            match counts.iter_mut().find(|(s, _)| s == sender) {
+                Some((_, n)) => *n += 1,
+                None => counts.push((sender.to_string(), 1)),
+            }
+        }
+        counts
+    }
+
+    /// "12 messages (Alice 7, Bob 5)"
+    pub fn count_note(&self) -> String {
+        let senders: Vec<String> = self
+            .senders()
+            .iter()
+            .map(|(s, n)| format!("{} {}", s, n))
+            .collect();
+        let plural = if self.message_count() == 1 { "" } else { "s" };
+        format!(
+            "WhatsApp: {} message{} ({})",
+            self.message_count(),
+            plural,
+            senders.join(", ")
+        )
+    }
+
+    /// The day's conversation as "Sender: text" lines, for summarization.
+    pub fn transcript(&self) -> String {
+        self.messages
+            .iter()
+            .map(|m| format!("{}: {}", m.sender.as_deref().unwrap_or_default(), m.text))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// A message header before the date order is known.
+struct RawLine {
+    date_parts: [u32; 3],
+    time: NaiveTime,
+    sender: Option<String>,
+    text: String,
+}
+
+/// Parses a WhatsApp "Export chat" text file.
+///
+/// Handles the Android (`31/12/2020, 22:15 - Name: text`) and iOS
+/// (`[31/12/20, 22:15:03] Name: text`) layouts, `/`, `.` and `-` date
+/// separators, 12- and 24-hour clocks and multi-line messages. When `order`
+/// is `None`, day/month order is detected from the file, falling back to
+/// month-first for 12-hour exports and day-first otherwise.
+pub fn parse_chat(text: &str, order: Option<DateOrder>) -> Vec<ChatMessage> {
+    let mut raw: Vec<RawLine> = Vec::new();
+    let mut twelve_hour = false;
+
+    for line in text.lines() {
+        let line = strip_marks(line);
+        match parse_line(&line) {
+            Some((parsed, is_12h)) => {
+                twelve_hour |= is_12h;
+                raw.push(parsed);
+            }
+            None => {
+                // Continuation of a multi-line message
+                if let Some(last) = raw.last_mut() {
+                    if !line.trim().is_empty() {
+                        last.text.push('\n');
+                        last.text.push_str(line.trim_end());
+                    }
+                }
+            }
+        }
+    }
+
+    let order = order.unwrap_or_else(|| detect_order(&raw, twelve_hour));
+
+    raw.into_iter()
+        .filter_map(|r| {
+            let date = to_date(r.date_parts, order)?;
+            Some(ChatMessage {
+                timestamp: date.and_time(r.time),
+                sender: r.sender,
+                text: r.text,
+            })
+        })
+        .collect()
+}
+
+/// Groups sender messages by calendar day, oldest first. System lines are dropped.
+pub fn group_by_day(messages: &[ChatMessage]) -> Vec<ChatDay> {
+    let mut days: BTreeMap<NaiveDate, Vec<ChatMessage>> = BTreeMap::new();
+    for m in messages.iter().filter(|m| m.sender.is_some()) {
+        days.entry(m.timestamp.date()).or_default().push(m.clone());
+    }
+    days.into_iter()
+        .map(|(date, messages)| ChatDay { date, messages })
+        .collect()
+}
+
+/// Days that do not already have a text interaction with the person.
+pub fn new_days(
+    conn: &Connection,
+    person_id: Id<Person>,
+    days: Vec<ChatDay>,
+) -> PrmResult<(Vec<ChatDay>, usize)> {
+    let logged: Vec<NaiveDate> = interaction_queries::interactions_with(conn, person_id)?
+        .into_iter()
+        .filter(|i| i.medium == InteractionMedium::Text)
+        .map(|i| i.date)
+        .collect();
+
+    let total = days.len();
+    let fresh: Vec<ChatDay> = days
+        .into_iter()
+        .filter(|d| !logged.contains(&d.date))
+        .collect();
+    let skipped = total - fresh.len();
+    Ok((fresh, skipped))
+}
+
+/// Logs one text interaction per day. `topics_for` supplies each day's topics.
+pub fn import_days(
+    conn: &Connection,
+    owner_id: Id<User>,
+    person_id: Id<Person>,
+    days: &[ChatDay],
+    my_location: &str,
+    their_location: Option<&str>,
+    include_counts: bool,
+    mut topics_for: impl FnMut(&ChatDay) -> Vec<String>,
+) -> PrmResult<usize> {
+    let mut created = 0;
+    for day in days {
+        let note = include_counts.then(|| day.count_note());
+        interaction_ops::log_remote(
+            conn,
+            owner_id,
+            person_id,
+            InteractionMedium::Text,
+            my_location,
+            their_location,
+            topics_for(day),
+            note.as_deref(),
+            day.date,
+        )?;
+        created += 1;
+    }
+    Ok(created)
+}
+
+/// Removes the invisible direction marks and BOM WhatsApp sprinkles into exports.
+fn strip_marks(line: &str) -> String {
+    line.chars()
+        .filter(|c| !matches!(c, '\u{200e}' | '\u{200f}' | '\u{feff}'))
+        .map(|c| if matches!(c, '\u{202f}' | '\u{a0}') { ' ' } else { c })
+        .collect()
+}
+
+fn parse_line(line: &str) -> Option<(RawLine, bool)> {
+    let (header, body) = if let Some(rest) = line.strip_prefix('[') {
+        let end = rest.find(']')?;
+        (&rest[..end], rest[end + 1..].trim_start())
+    } else {
+        let sep = line.find(" - ")?;
+        (&line[..sep], &line[sep + 3..])
+    };
+
+    let (date_str, time_str) = header
+        .split_once(", ")
+        .or_else(|| header.split_once(' '))?;
+    let date_parts = parse_date_parts(date_str.trim())?;
+    let (time, is_12h) = parse_time(time_str.trim())?;
+
+    let (sender, text) = match body.split_once(": ") {
+        Some((s, t)) if !s.trim().is_empty() => (Some(s.trim().to_string()), t.to_string()),
+        _ => (None, body.to_string()),
+    };
+
+    Some((RawLine { date_parts, time, sender, text }, is_12h))
+}
+
+fn parse_date_parts(s: &str) -> Option<[u32; 3]> {
+    let parts: Vec<&str> = s.split(['/', '.', '-']).collect();
+    if parts.len() != 3 || parts.iter().any(|p| p.is_empty() || !p.chars().all(|c| c.is_ascii_digit())) {
+        return None;
+    }
+    Some([
+        parts[0].parse().ok()?,
+        parts[1].parse().ok()?,
+        parts[2].parse().ok()?,
+    ])
+}
+
+fn parse_time(s: &str) -> Option<(NaiveTime, bool)> {
+    let lower = s.to_lowercase().replace('.', "");
+    let (clock, meridiem) = if let Some(c) = lower.strip_suffix("am").or_else(|| lower.strip_suffix("a m")) {
+        (c.trim(), Some(false))
+    } else if let Some(c) = lower.strip_suffix("pm").or_else(|| lower.strip_suffix("p m")) {
+        (c.trim(), Some(true))
+    } else {
+        (lower.trim(), None)
+    };
+
+    let fields: Vec<u32> = clock
+        .split(':')
+        .map(|p| p.parse().ok())
+        .collect::<Option<Vec<_>>>()?;
+    if fields.len() < 2 || fields.len() > 3 {
+        return None;
+    }
+    let mut hour = fields[0];
+    if let Some(pm) = meridiem {
+        if hour == 0 || hour > 12 {
+            return None;
+        }
+        hour = match (pm, hour) {
+            (false, 12) => 0,
+            (true, 12) => 12,
+            (true, h) => h + 12,
+            (false, h) => h,
+        };
+    }
+    let time = NaiveTime::from_hms_opt(hour, fields[1], fields.get(2).copied().unwrap_or(0))?;
+    Some((time, meridiem.is_some()))
+}
+
+fn detect_order(raw: &[RawLine], twelve_hour: bool) -> DateOrder {
+    for r in raw.iter().filter(|r| r.date_parts[0] < 1000) {
+        if r.date_parts[0] > 12 {
+            return DateOrder::DayFirst;
+        }
+        if r.date_parts[1] > 12 {
+            return DateOrder::MonthFirst;
+        }
+    }
+    if twelve_hour {
+        DateOrder::MonthFirst
+    } else {
+        DateOrder::DayFirst
+    }
+}
+
+fn to_date(parts: [u32; 3], order: DateOrder) -> Option<NaiveDate> {
+    // ISO-style exports put a four-digit year first
+    if parts[0] >= 1000 {
+        return NaiveDate::from_ymd_opt(parts[0] as i32, parts[1], parts[2]);
+    }
+    let (day, month) = match order {
+        DateOrder::DayFirst => (parts[0], parts[1]),
+        DateOrder::MonthFirst => (parts[1], parts[0]),
+    };
+    let year = if parts[2] < 100 { 2000 + parts[2] } else { parts[2] };
+    NaiveDate::from_ymd_opt(year as i32, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ymd(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn parses_android_day_first() {
+        let text = "31/12/2023, 22:15 - Alice: Happy new year!\n01/01/2024, 09:00 - Bob: You too";
+        let msgs = parse_chat(text, None);
+        assert_eq!(msgs.len(), 2);
+        assert_eq!(msgs[0].timestamp.date(), ymd(2023, 12, 31));
+        assert_eq!(msgs[1].sender.as_deref(), Some("Bob"));
+    }
+
+    #[test]
+    fn parses_ios_twelve_hour_month_first() {
+        let text = "[3/4/24, 9:05:11\u{202f}PM] Alice: hi\n[3/4/24, 12:01:00 AM] Bob: late";
+        let msgs = parse_chat(text, None);
+        assert_eq!(msgs[0].timestamp, ymd(2024, 3, 4).and_hms_opt(21, 5, 11).unwrap());
+        assert_eq!(msgs[1].timestamp.time(), NaiveTime::from_hms_opt(0, 1, 0).unwrap());
+    }
+
+    #[test]
+    fn parses_dotted_dates_and_multiline_messages() {
+        let text = "\u{200e}05.06.24, 18:30 - Alice: first line\nsecond line\n05.06.24, 18:31 - Messages are end-to-end encrypted.";
+        let msgs = parse_chat(text, None);
+        assert_eq!(msgs.len(), 2);
+        assert_eq!(msgs[0].text, "first line\nsecond line");
+        assert_eq!(msgs[0].timestamp.date(), ymd(2024, 6, 5));
+        assert!(msgs[1].sender.is_none());
+    }
+
+    #[test]
+    fn explicit_order_overrides_detection() {
+        let text = "02/03/24, 10:00 - Alice: hi";
+        let msgs = parse_chat(text, Some(DateOrder::MonthFirst));
+        assert_eq!(msgs[0].timestamp.date(), ymd(2024, 2, 3));
+    }
+
+    #[test]
+    fn groups_by_day_without_system_lines() {
+        let text = "01/02/2024, 10:00 - Alice: a\n01/02/2024, 10:01 - Bob: b\n01/02/2024, 10:02 - Alice: c\n\
+                    02/02/2024, 08:00 - Alice changed the group icon\n03/02/2024, 11:00 - Bob: d";
+        let days = group_by_day(&parse_chat(text, None));
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0].message_count(), 3);
+        assert_eq!(days[0].senders(), vec![("Alice".to_string(), 2), ("Bob".to_string(), 1)]);
+        assert_eq!(days[0].count_note(), "WhatsApp: 3 messages (Alice 2, Bob 1)");
+    }
+}
@@ -2,10 +2,15 @@
 
 pub mod error;
 pub mod validation;
+pub mod util;
+pub mod matching;
 pub mod model;
 pub mod db;
 pub mod ops;
 pub mod queries;
 pub mod ai;
 pub mod migrate;
+pub mod export;
+pub mod sync;
+pub mod import;
 pub mod cli;
@@ -4,6 +4,8 @@ fn main() {
     let mut args = std::env::args().skip(1);
     let mut db_path: Option<PathBuf> = None;
     let mut import_path: Option<PathBuf> = None;
+    let mut subcommand: Option<(String, Vec<String>)> = None;
+    let mut output = prm::cli::output::OutputFormat::Text;
 
     while let Some(arg) = args.next() {
         match arg.as_str() {
@@ -21,17 +23,38 @@ fn main() {
                     std::process::exit(1);
                 }
             }
+            "--json" => output = prm::cli::output::OutputFormat::Json,
             "--help" | "-h" => {
                 println!("PRM - Personal Relationship Manager");
                 println!();
-                println!("Usage: prm [OPTIONS]");
+                println!("Usage: prm [OPTIONS] [COMMAND]");
+                println!();
+                println!("Without a command, starts the interactive shell.");
                 println!();
                 println!("Options:");
                 println!("  -f, --file <PATH>      Database file path (default: .data/prm.db)");
                 println!("  --import <JSON_PATH>   Import data from Scala PRM JSON file");
+                println!("  --json                 Print people, show, history, remind, stats,");
+                println!("                         circles and labels as JSON");
                 println!("  -h, --help             Show this help");
+                println!();
+                println!("Commands (never prompt; exit 0 ok, 1 error, 2 usage, 3 person not found):");
+                println!("  init <your name>       Create a new network");
+                println!("  people [filter]        List people, e.g. people label:friend last>60d sort:last-contact");
+                println!("  show <name>            Show person details");
+                println!("  history <name>         Show all interactions with a person");
+                println!("  log <name> --topics a,b [--medium M] [--date YYYY-MM-DD]");
+                println!("             [--location L] [--their-location L] [--note N]");
+                println!("                         Log an interaction");
+                println!("  remind                 Show overdue and upcoming reminders");
+                println!("  stats                  Show statistics");
+                println!("  circles                List circles");
+                println!("  labels                 List labels");
                 return;
             }
+            command if prm::cli::subcommand::COMMANDS.contains(&command) => {
+                subcommand = Some((command.to_string(), args.by_ref().collect()));
+            }
             other => {
                 eprintln!("Unknown argument: {}", other);
                 eprintln!("Use --help for usage information.");
@@ -73,5 +96,9 @@ fn main() {
         return;
     }
 
-    prm::cli::run(&db_path);
+    if let Some((command, command_args)) = subcommand {
+        std::process::exit(prm::cli::subcommand::run(&db_path, &command, command_args, output));
+    }
+
+    prm::cli::run(&db_path, output);
 }
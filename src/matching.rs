@@ -0,0 +1,251 @@
+//! Fuzzy name matching: accent- and case-insensitive, tolerant of typos and
+//! of nicknames (Bill for William).
+
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// Scores at or above this come from a substring, a whole word or a nickname.
+/// Typo-only matches score below it and are dropped when a strong match exists.
+pub const STRONG_MATCH: f64 = 0.85;
+
+/// Groups of interchangeable first names, folded to lowercase ASCII.
+const NICKNAMES: &[&[&str]] = &[
+    &["alexander", "alex", "alec", "sasha", "sandy"],
+    &["alexandra", "alex", "alexa", "sasha", "sandra", "sandy"],
+    &["andrew", "andy", "drew"],
+    &["anthony", "tony"],
+    &["barbara", "barb", "babs"],
+    &["benjamin", "ben", "benny"],
+    &["charles", "charlie", "chuck", "chas"],
+    &["christopher", "chris", "kit"],
+    &["christine", "christina", "chris", "chrissy", "tina"],
+    &["daniel", "dan", "danny"],
+    &["david", "dave", "davey"],
+    &["deborah", "debra", "deb", "debbie"],
+    &["dorothy", "dot", "dottie"],
+    &["edward", "ed", "eddie", "ted", "ned"],
+    &["elizabeth", "liz", "lizzie", "beth", "betty", "eliza", "libby"],
+    &["frances", "fran", "frankie"],
+    &["francis", "frank", "frankie"],
+    &["gregory", "greg"],
+    &["henry", "hank", "harry"],
+    &["jacob", "jake"],
+    &["james", "jim", "jimmy", "jamie"],
+    &["jennifer", "jen", "jenny"],
+    &["john", "johnny", "jack", "jon"],
+    &["jonathan", "jon", "jonny"],
+    &["joseph", "joe", "joey", "jose"],
+    &["josephine", "jo", "josie"],
+    &["katherine", "catherine", "kathryn", "katharine", "kate", "katie", "kathy", "cathy", "kat", "kay"],
+    &["lawrence", "laurence", "larry"],
+    &["margaret", "maggie", "meg", "peggy", "marge", "greta"],
+    &["matthew", "matt"],
+    &["michael", "mike", "mikey", "mick"],
+    &["nicholas", "nick", "nicky", "nico"],
+    &["patricia", "pat", "patty", "trish"],
+    &["patrick", "pat", "paddy"],
+    &["peter", "pete"],
+    &["petros", "peter", "pete"],
+    &["rebecca", "becky", "becca"],
+    &["richard", "rick", "ricky", "rich", "dick"],
+    &["robert", "rob", "robbie", "bob", "bobby", "bert"],
+    &["ronald", "ron", "ronnie"],
+    &["samuel", "sam", "sammy"],
+    &["samantha", "sam", "sammy"],
+    &["stephen", "steven", "steve"],
+    &["susan", "sue", "suzy", "susie"],
+    &["thomas", "tom", "tommy"],
+    &["timothy", "tim", "timmy"],
+    &["victoria", "vicky", "tori"],
+    &["william", "will", "bill", "billy", "willy", "liam"],
+];
+
+/// Lowercases, decomposes and strips diacritics: "José Ñúñez" -> "jose nunez".
+pub fn fold(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.nfkd() {
+        if is_combining_mark(c) {
+            continue;
+        }
+        // Letters that don't decompose into a base letter plus a mark
+        match c {
+            'ß' => out.push_str("ss"),
+            'æ' | 'Æ' => out.push_str("ae"),
+            'œ' | 'Œ' => out.push_str("oe"),
+            'ø' | 'Ø' => out.push('o'),
+            'ł' | 'Ł' => out.push('l'),
+            'đ' | 'Đ' | 'ð' | 'Ð' => out.push('d'),
+            'þ' | 'Þ' => out.push_str("th"),
+            'ı' => out.push('i'),
+            _ => out.extend(c.to_lowercase()),
+        }
+    }
+    out
+}
+
+/// Edit distance counting insertions, deletions, substitutions and swaps of
+/// adjacent characters (optimal string alignment).
+pub fn damerau_levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// Whether two folded words are listed as the same name.
+pub fn same_name(a: &str, b: &str) -> bool {
+    NICKNAMES.iter().any(|group| group.contains(&a) && group.contains(&b))
+}
+
+/// How well `query` matches `candidate`, from 0 to 1, or `None` for no match.
+///
+/// 1.0 is an exact match (ignoring case and accents), then come prefix and
+/// substring matches. Otherwise every query word must match some word of the
+/// candidate, exactly, as a prefix, as a nickname, or within a typo or two.
+pub fn score(query: &str, candidate: &str) -> Option<f64> {
+    let q = fold(query.trim());
+    let c = fold(candidate.trim());
+    if q.is_empty() || c.is_empty() {
+        return None;
+    }
+    if c == q {
+        return Some(1.0);
+    }
+    if c.starts_with(&q) {
+        return Some(0.95);
+    }
+    if c.contains(&q) {
+        return Some(0.9);
+    }
+
+    let candidate_words = words(&c);
+    let query_words = words(&q);
+    if query_words.is_empty() {
+        return None;
+    }
+    let mut total = 0.0;
+    for qw in &query_words {
+        let best = candidate_words
+            .iter()
+            .filter_map(|cw| word_score(qw, cw))
+            .fold(None, |best: Option<f64>, s| Some(best.map_or(s, |b| b.max(s))))?;
+        total += best;
+    }
+    Some(total / query_words.len() as f64 * 0.95)
+}
+
+/// The best score of `query` against any of a record's names (e.g. name and
+/// nickname).
+pub fn best_score<S: AsRef<str>>(query: &str, names: &[S]) -> Option<f64> {
+    names
+        .iter()
+        .filter_map(|n| score(query, n.as_ref()))
+        .fold(None, |best, s| Some(best.map_or(s, |b: f64| b.max(s))))
+}
+
+/// Keeps the items that match `query`, best first. When anything matches
+/// strongly, typo-only matches are dropped.
+pub fn rank<T>(query: &str, items: Vec<T>, names: impl Fn(&T) -> Vec<String>) -> Vec<(T, f64)> {
+    let mut scored: Vec<(T, f64)> = items
+        .into_iter()
+        .filter_map(|item| {
+            let s = best_score(query, &names(&item))?;
+            Some((item, s))
+        })
+        .collect();
+    if scored.iter().any(|(_, s)| *s >= STRONG_MATCH) {
+        scored.retain(|(_, s)| *s >= STRONG_MATCH);
+    }
+    // Stable sort keeps the incoming (usually alphabetical) order among ties
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+    scored
+}
+
+fn words(s: &str) -> Vec<&str> {
+    s.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()).collect()
+}
+
+fn word_score(query: &str, word: &str) -> Option<f64> {
+    if query == word {
+        return Some(1.0);
+    }
+    if word.starts_with(query) {
+        return Some(0.95);
+    }
+    if same_name(query, word) {
+        return Some(0.9);
+    }
+    let allowed = match query.chars().count() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    };
+    match damerau_levenshtein(query, word) {
+        0 => Some(1.0),
+        d if d <= allowed => Some(0.85 - 0.1 * d as f64),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folds_case_and_diacritics() {
+        assert_eq!(fold("José Ñúñez"), "jose nunez");
+        assert_eq!(fold("Łukasz Strauß"), "lukasz strauss");
+        assert_eq!(fold("ZOË"), "zoe");
+    }
+
+    #[test]
+    fn counts_transpositions_as_one_edit() {
+        assert_eq!(damerau_levenshtein("alcie", "alice"), 1);
+        assert_eq!(damerau_levenshtein("kitten", "sitting"), 3);
+        assert_eq!(damerau_levenshtein("", "abc"), 3);
+    }
+
+    #[test]
+    fn scores_exact_prefix_nickname_and_typo_in_order() {
+        let exact = score("alice smith", "Alice Smith").unwrap();
+        let prefix = score("ali", "Alice Smith").unwrap();
+        let nickname = score("bill", "William Gates").unwrap();
+        let typo = score("alcie", "Alice Smith").unwrap();
+        assert!(exact > prefix && prefix > nickname && nickname > typo);
+        assert!(nickname >= STRONG_MATCH && typo < STRONG_MATCH);
+
+        assert_eq!(score("jose", "José García"), Some(0.95));
+        assert!(score("katherine", "Catherine Zeta").is_some());
+        assert!(score("smith alice", "Alice Smith").is_some());
+        assert_eq!(score("bob", "Alice Smith"), None);
+        // Short words need to match exactly
+        assert_eq!(score("ann", "Ian"), None);
+    }
+
+    #[test]
+    fn rank_drops_typos_when_something_matches_strongly() {
+        let names = vec!["John Smith".to_string(), "Joan Doe".to_string(), "Johnny Cash".to_string()];
+        let ranked = rank("john", names.clone(), |n| vec![n.clone()]);
+        let found: Vec<&str> = ranked.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(found, vec!["John Smith", "Johnny Cash"]);
+
+        let ranked = rank("jhon", names, |n| vec![n.clone()]);
+        assert_eq!(ranked.len(), 1);
+        assert_eq!(ranked[0].0, "John Smith");
+    }
+}
@@ -276,6 +276,7 @@ fn parse_interaction(val: &Value) -> PrmResult<Interaction> {
         "PhoneCall" => InteractionMedium::PhoneCall,
         "VideoCall" => InteractionMedium::VideoCall,
         "SocialMedia" => InteractionMedium::SocialMedia,
+        "Email" => InteractionMedium::Email,
         _ => InteractionMedium::InPerson,
     };
 
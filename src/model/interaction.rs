@@ -11,6 +11,7 @@ pub enum InteractionMedium {
     PhoneCall,
     VideoCall,
     SocialMedia,
+    Email,
 }
 
 impl InteractionMedium {
@@ -20,6 +21,7 @@ impl InteractionMedium {
         InteractionMedium::PhoneCall,
         InteractionMedium::VideoCall,
         InteractionMedium::SocialMedia,
+        InteractionMedium::Email,
     ];
 
     pub fn display_name(&self) -> &'static str {
@@ -29,6 +31,25 @@ impl InteractionMedium {
             InteractionMedium::PhoneCall => "Phone Call",
             InteractionMedium::VideoCall => "Video Call",
             InteractionMedium::SocialMedia => "Social Media",
+            InteractionMedium::Email => "Email",
+        }
+    }
+
+    /// Parse a user-typed medium such as "text", "in-person", "phone" or "video".
+    pub fn parse(s: &str) -> Option<Self> {
+        let key: String = s
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .collect::<String>()
+            .to_lowercase();
+        match key.as_str() {
+            "inperson" | "person" | "irl" => Some(InteractionMedium::InPerson),
+            "text" | "sms" | "message" => Some(InteractionMedium::Text),
+            "phonecall" | "phone" | "call" => Some(InteractionMedium::PhoneCall),
+            "videocall" | "video" => Some(InteractionMedium::VideoCall),
+            "socialmedia" | "social" => Some(InteractionMedium::SocialMedia),
+            "email" | "mail" => Some(InteractionMedium::Email),
+            _ => None,
         }
     }
 
@@ -40,6 +61,7 @@ impl InteractionMedium {
             "PhoneCall" => Some(InteractionMedium::PhoneCall),
             "VideoCall" => Some(InteractionMedium::VideoCall),
             "SocialMedia" => Some(InteractionMedium::SocialMedia),
+            "Email" => Some(InteractionMedium::Email),
             _ => None,
         }
     }
@@ -52,6 +74,7 @@ impl InteractionMedium {
             InteractionMedium::PhoneCall => "PhoneCall",
             InteractionMedium::VideoCall => "VideoCall",
             InteractionMedium::SocialMedia => "SocialMedia",
+            InteractionMedium::Email => "Email",
         }
     }
 }
@@ -128,4 +128,11 @@ impl Person {
         p.is_self = true;
         p
     }
+
+    /// The names a person can be looked up by: their name and any nickname.
+    pub fn names(&self) -> Vec<String> {
+        let mut names = vec![self.name.clone()];
+        names.extend(self.nickname.clone().filter(|n| !n.trim().is_empty()));
+        names
+    }
 }
@@ -0,0 +1,137 @@
+use std::collections::{BTreeSet, HashSet};
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::db::correction_repo::{self, CorrectionKind, CorrectionRecord};
+use crate::error::{PrmError, PrmResult};
+use crate::model::{Id, User};
+use crate::validation;
+
+/// A correction as written to a shared JSON file. The parses are kept as
+/// JSON objects so the file can be read and curated by hand.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedCorrection {
+    pub original_text: String,
+    pub ai_output: Value,
+    pub user_output: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    #[serde(default)]
+    pub pinned: bool,
+    /// Missing in files exported before edits were corrected.
+    #[serde(default)]
+    pub kind: CorrectionKind,
+}
+
+/// What an import did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ImportSummary {
+    pub added: usize,
+    /// Already present, word for word.
+    pub skipped: usize,
+}
+
+/// Every correction as a pretty-printed JSON array, oldest first.
+pub fn export(conn: &Connection, owner_id: Id<User>) -> PrmResult<String> {
+    let shared: Vec<SharedCorrection> = correction_repo::all(conn, owner_id)?
+        .into_iter()
+        .map(|r| SharedCorrection {
+            ai_output: as_json(&r.ai_output),
+            user_output: as_json(&r.user_output),
+            original_text: r.original_text,
+            created_at: Some(r.created_at),
+            pinned: r.pinned,
+            kind: r.kind,
+        })
+        .collect();
+    Ok(serde_json::to_string_pretty(&shared)?)
+}
+
+/// Adds the corrections in an exported JSON array, skipping ones already
+/// present. Nothing is added if any entry is invalid.
+pub fn import(conn: &Connection, owner_id: Id<User>, json: &str) -> PrmResult<ImportSummary> {
+    let shared: Vec<SharedCorrection> = serde_json::from_str(json)?;
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let mut records = Vec::new();
+    for s in shared {
+        records.push(CorrectionRecord {
+            id: uuid::Uuid::nil(),
+            original_text: validation::non_blank(&s.original_text, "original_text")?,
+            ai_output: stored_form(&s.ai_output),
+            user_output: stored_form(&s.user_output),
+            created_at: s.created_at.unwrap_or_else(|| now.clone()),
+            pinned: s.pinned,
+            kind: s.kind,
+        });
+    }
+
+    let mut known: HashSet<CorrectionKey> = correction_repo::all(conn, owner_id)?.iter().map(key).collect();
+    let tx = conn.unchecked_transaction()?;
+    let mut summary = ImportSummary::default();
+    for record in &records {
+        if known.insert(key(record)) && correction_repo::insert_record(&tx, owner_id, record)? {
+            summary.added += 1;
+        } else {
+            summary.skipped += 1;
+        }
+    }
+    tx.commit()?;
+    Ok(summary)
+}
+
+/// Fields whose value differs between the model's parse and the saved one,
+/// with both values, by field name.
+pub fn changed_fields(record: &CorrectionRecord) -> Vec<(String, Value, Value)> {
+    let (Value::Object(ai), Value::Object(user)) = (as_json(&record.ai_output), as_json(&record.user_output))
+    else {
+        return vec![("output".into(), as_json(&record.ai_output), as_json(&record.user_output))];
+    };
+    let names: BTreeSet<&String> = ai.keys().chain(user.keys()).collect();
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let before = ai.get(name).cloned().unwrap_or(Value::Null);
+            let after = user.get(name).cloned().unwrap_or(Value::Null);
+            (before != after).then(|| (name.clone(), before, after))
+        })
+        .collect()
+}
+
+pub fn find_by_prefix(conn: &Connection, owner_id: Id<User>, prefix: &str) -> PrmResult<Vec<CorrectionRecord>> {
+    let prefix = validation::non_blank(prefix, "correction id")?;
+    if !prefix.chars().all(|c| c.is_ascii_hexdigit() || c == '-') {
+        return Err(PrmError::NotFound {
+            entity_type: "Correction".into(),
+            id: prefix,
+        });
+    }
+    correction_repo::find_by_id_prefix(conn, owner_id, &prefix)
+}
+
+fn as_json(text: &str) -> Value {
+    serde_json::from_str(text).unwrap_or_else(|_| Value::String(text.to_string()))
+}
+
+/// The text stored for a parse: the JSON as written in the file, or the
+/// string itself for outputs that weren't JSON.
+fn stored_form(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// What makes two corrections the same: their kind, input, and outputs
+/// compared as JSON, so key order and spacing don't matter.
+type CorrectionKey = (CorrectionKind, String, String, String);
+
+fn key(record: &CorrectionRecord) -> CorrectionKey {
+    (
+        record.kind,
+        record.original_text.clone(),
+        as_json(&record.ai_output).to_string(),
+        as_json(&record.user_output).to_string(),
+    )
+}
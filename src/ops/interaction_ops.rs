@@ -87,6 +87,24 @@ pub fn log_remote(
     Ok(interaction)
 }
 
+/// Replaces the topics and note of a logged interaction. Topics are trimmed
+/// and at least one is required, as when logging.
+pub fn update_details(
+    conn: &Connection,
+    interaction_id: Id<Interaction>,
+    topics: Vec<String>,
+    note: Option<&str>,
+) -> PrmResult<()> {
+    let valid_topics: Vec<String> = topics
+        .into_iter()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect();
+    validation::non_empty_set(&valid_topics, "topics")?;
+
+    interaction_repo::update_details(conn, interaction_id, &valid_topics, trim_optional(note).as_deref())
+}
+
 fn ensure_relationship(
     conn: &Connection,
     owner_id: Id<User>,
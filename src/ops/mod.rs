@@ -4,3 +4,4 @@ pub mod relationship_ops;
 pub mod interaction_ops;
 pub mod circle_ops;
 pub mod label_ops;
+pub mod correction_ops;
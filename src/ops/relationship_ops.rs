@@ -1,3 +1,4 @@
+use chrono::NaiveDate;
 use rusqlite::Connection;
 
 use crate::db::{person_repo, relationship_repo};
@@ -105,3 +106,15 @@ pub fn set_reminder(
 
     relationship_repo::update_reminder(conn, person_id, days)
 }
+
+/// Holds off reminders for a person until `until`; they count as overdue
+/// again from that day if still out of touch.
+pub fn snooze_reminder(conn: &Connection, person_id: Id<Person>, until: NaiveDate) -> PrmResult<()> {
+    relationship_repo::find_by_person(conn, person_id)?
+        .ok_or_else(|| PrmError::NotFound {
+            entity_type: "Relationship".into(),
+            id: person_id.to_string(),
+        })?;
+
+    relationship_repo::upsert_snooze(conn, person_id, until)
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use chrono::NaiveDate;
 use rusqlite::Connection;
 
@@ -54,6 +56,23 @@ pub fn interactions_in_range(
     Ok(results)
 }
 
+/// When each interaction between `from` and `to` (inclusive) was logged, as
+/// stored (to the second).
+pub fn logged_at_in_range(
+    conn: &Connection,
+    owner_id: Id<User>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> PrmResult<HashMap<Id<Interaction>, String>> {
+    interaction_repo::logged_at_in_date_range(conn, owner_id, from, to)
+}
+
+/// Every interaction in the network with the id of the person it was logged
+/// for, newest first. A group interaction appears once per person.
+pub fn all_interactions(conn: &Connection, owner_id: Id<User>) -> PrmResult<Vec<(Id<Person>, Interaction)>> {
+    interaction_repo::find_by_owner(conn, owner_id)
+}
+
 /// Gets people you haven't interacted with in a given number of days.
 /// Unlike reminders, this checks ALL relationships, not just those with reminders set.
 pub fn not_contacted_in(
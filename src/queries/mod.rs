@@ -1,7 +1,9 @@
 pub mod person_queries;
+pub mod person_filter;
 pub mod relationship_queries;
 pub mod interaction_queries;
 pub mod circle_queries;
 pub mod contact_queries;
 pub mod reminder_queries;
 pub mod stats_queries;
+pub mod timeline_queries;
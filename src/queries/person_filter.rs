@@ -0,0 +1,361 @@
+//! A small query language for narrowing down the people list, e.g.
+//! `label:friend circle:"book club" last>60d has:email sort:last-contact`.
+//!
+//! Terms are ANDed; a leading `-` negates a term. Words without a `key:` are
+//! matched against names and nicknames. Name and location text ignores case
+//! and accents.
+
+use chrono::{Datelike, Duration, NaiveDate};
+use rusqlite::Connection;
+
+use crate::db::person_repo;
+use crate::error::PrmResult;
+use crate::matching;
+use crate::model::{Id, InteractionMedium, Person, User};
+use crate::queries::person_queries;
+use crate::util;
+
+/// Filter keys, for usage messages.
+pub const SYNTAX: &str = "label:<name> circle:<name> last>30d last<2w last:never medium:<medium> \
+                          location:<text> has:email|phone|address|birthday|reminder|nickname|notes \
+                          birthday:next30d sort:name|last-contact|birthday (prefix a term with - to negate)";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Term {
+    /// Name or nickname contains the text, ignoring case and accents.
+    Name(String),
+    Label(String),
+    Circle(String),
+    /// No interaction in the last N days (including never).
+    NotContactedWithin(i64),
+    /// At least one interaction in the last N days.
+    ContactedWithin(i64),
+    NeverContacted,
+    /// Any interaction, ever, over this medium.
+    Medium(InteractionMedium),
+    /// Location contains the text, ignoring case and accents.
+    Location(String),
+    Has(Attribute),
+    /// Birthday falls within the next N days (today included).
+    BirthdayWithin(i64),
+    Not(Box<Term>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Attribute {
+    Email,
+    Phone,
+    Address,
+    Birthday,
+    Reminder,
+    Nickname,
+    Notes,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortKey {
+    #[default]
+    Name,
+    /// Longest without contact first, never-contacted at the top.
+    LastContact,
+    /// Next upcoming birthday first; people without one last.
+    Birthday,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PersonFilter {
+    pub terms: Vec<Term>,
+    pub sort: SortKey,
+    pub descending: bool,
+}
+
+impl PersonFilter {
+    /// Parses a filter expression. Values with spaces go in double quotes.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        Self::parse_terms(util::tokenize(input))
+    }
+
+    /// Parses terms that are already split (e.g. by the shell).
+    pub fn parse_terms<S: AsRef<str>>(tokens: impl IntoIterator<Item = S>) -> Result<Self, String> {
+        let mut filter = PersonFilter::default();
+        for token in tokens {
+            let token = token.as_ref().trim();
+            if token.is_empty() {
+                continue;
+            }
+            if let Some(sort) = token.strip_prefix("sort:") {
+                let (key, descending) = match sort.strip_prefix('-') {
+                    Some(key) => (key, true),
+                    None => (sort, false),
+                };
+                filter.sort = match key {
+                    "name" => SortKey::Name,
+                    "last-contact" | "last" => SortKey::LastContact,
+                    "birthday" => SortKey::Birthday,
+                    other => return Err(format!("Unknown sort '{}'. Use name, last-contact or birthday.", other)),
+                };
+                filter.descending = descending;
+                continue;
+            }
+            let term = match token.strip_prefix('-').filter(|rest| !rest.is_empty()) {
+                Some(rest) => Term::Not(Box::new(parse_term(rest)?)),
+                None => parse_term(token)?,
+            };
+            filter.terms.push(term);
+        }
+        Ok(filter)
+    }
+
+    /// Compiles to a WHERE clause and ORDER BY over `people p`, with `?N`
+    /// placeholders for the returned parameters. Archived people and yourself
+    /// are excluded. Name and location terms are matched against `people`
+    /// here, since SQLite can't fold accents.
+    pub fn to_sql(&self, owner_id: Id<User>, today: NaiveDate, people: &[Person]) -> (String, String, Vec<String>) {
+        let mut params = vec![owner_id.value.to_string()];
+        let mut clauses = vec!["p.network_owner_id = ?1".to_string(), "p.archived = 0".into(), "p.is_self = 0".into()];
+        for term in &self.terms {
+            clauses.push(term_sql(term, today, people, &mut params));
+        }
+
+        let direction = if self.descending { "DESC" } else { "ASC" };
+        let order = match self.sort {
+            SortKey::Name => format!("p.name COLLATE NOCASE {}", direction),
+            SortKey::LastContact => format!(
+                "(SELECT MAX(i.date) FROM interactions i WHERE i.relationship_person_id = p.id) {} NULLS {}, \
+                 p.name COLLATE NOCASE",
+                direction,
+                if self.descending { "LAST" } else { "FIRST" },
+            ),
+            SortKey::Birthday => {
+                params.push(today.format("%m-%d").to_string());
+                let today_param = params.len();
+                format!(
+                    "p.birthday IS NULL, (substr(p.birthday, 6, 5) < ?{n}) {d}, substr(p.birthday, 6, 5) {d}, \
+                     p.name COLLATE NOCASE",
+                    n = today_param,
+                    d = direction,
+                )
+            }
+        };
+        (clauses.join(" AND "), order, params)
+    }
+}
+
+/// Active people (excluding yourself) that match the filter, in its order.
+pub fn filter_people(
+    conn: &Connection,
+    owner_id: Id<User>,
+    filter: &PersonFilter,
+    today: NaiveDate,
+) -> PrmResult<Vec<Person>> {
+    let people = if filter.terms.iter().any(matches_text) {
+        person_queries::active_people(conn, owner_id)?
+    } else {
+        Vec::new()
+    };
+    let (where_sql, order_sql, params) = filter.to_sql(owner_id, today, &people);
+    person_repo::find_where(conn, &where_sql, &order_sql, &params)
+}
+
+/// Whether the term is matched against people's text in Rust.
+fn matches_text(term: &Term) -> bool {
+    match term {
+        Term::Name(_) | Term::Location(_) => true,
+        Term::Not(inner) => matches_text(inner),
+        _ => false,
+    }
+}
+
+fn parse_term(token: &str) -> Result<Term, String> {
+    if let Some(rest) = token.strip_prefix("last") {
+        if let Some(span) = rest.strip_prefix('>') {
+            return Ok(Term::NotContactedWithin(parse_days(span)?));
+        }
+        if let Some(span) = rest.strip_prefix('<') {
+            return Ok(Term::ContactedWithin(parse_days(span)?));
+        }
+        if rest == ":never" {
+            return Ok(Term::NeverContacted);
+        }
+    }
+
+    let Some((key, value)) = token.split_once(':') else {
+        return Ok(Term::Name(token.to_string()));
+    };
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(format!("'{}' needs a value.", token));
+    }
+    match key.to_lowercase().as_str() {
+        "label" => Ok(Term::Label(value.to_string())),
+        "circle" => Ok(Term::Circle(value.to_string())),
+        "location" | "loc" => Ok(Term::Location(value.to_string())),
+        "medium" => InteractionMedium::parse(value)
+            .map(Term::Medium)
+            .ok_or_else(|| format!("Unknown medium '{}'.", value)),
+        "has" => {
+            let attribute = match value.to_lowercase().as_str() {
+                "email" => Attribute::Email,
+                "phone" => Attribute::Phone,
+                "address" => Attribute::Address,
+                "birthday" => Attribute::Birthday,
+                "reminder" => Attribute::Reminder,
+                "nickname" => Attribute::Nickname,
+                "notes" => Attribute::Notes,
+                other => return Err(format!("Unknown attribute 'has:{}'.", other)),
+            };
+            Ok(Term::Has(attribute))
+        }
+        "birthday" => match value.strip_prefix("next") {
+            Some(span) => Ok(Term::BirthdayWithin(parse_days(span)?)),
+            None => Err(format!("Use birthday:next<N>d, not birthday:{}.", value)),
+        },
+        other => Err(format!("Unknown filter '{}:'.", other)),
+    }
+}
+
+/// `60d`, `8w`, `3m` (30 days) or `1y` (365 days); a bare number is days.
+fn parse_days(span: &str) -> Result<i64, String> {
+    let span = span.trim();
+    let (number, unit) = match span.char_indices().last() {
+        Some((idx, c)) if c.is_ascii_alphabetic() => (&span[..idx], c.to_ascii_lowercase()),
+        _ => (span, 'd'),
+    };
+    let n: i64 = number.parse().map_err(|_| format!("Invalid duration '{}'. Use e.g. 60d, 8w, 3m.", span))?;
+    match unit {
+        'd' => Ok(n),
+        'w' => Ok(n * 7),
+        'm' => Ok(n * 30),
+        'y' => Ok(n * 365),
+        _ => Err(format!("Invalid duration '{}'. Use e.g. 60d, 8w, 3m.", span)),
+    }
+}
+
+fn term_sql(term: &Term, today: NaiveDate, people: &[Person], params: &mut Vec<String>) -> String {
+    let mut bind = |value: String| {
+        params.push(value);
+        format!("?{}", params.len())
+    };
+    match term {
+        Term::Name(text) => {
+            let text = matching::fold(text);
+            let ids: Vec<String> = people
+                .iter()
+                .filter(|p| {
+                    matching::fold(&p.name).contains(&text)
+                        || p.nickname.as_deref().is_some_and(|n| matching::fold(n).contains(&text))
+                })
+                .map(|p| bind(p.id.value.to_string()))
+                .collect();
+            format!("p.id IN ({})", ids.join(", "))
+        }
+        Term::Location(text) => {
+            let text = matching::fold(text);
+            let ids: Vec<String> = people
+                .iter()
+                .filter(|p| p.location.as_deref().is_some_and(|l| matching::fold(l).contains(&text)))
+                .map(|p| bind(p.id.value.to_string()))
+                .collect();
+            format!("p.id IN ({})", ids.join(", "))
+        }
+        Term::Label(name) => format!(
+            "EXISTS (SELECT 1 FROM relationship_label_assignments a JOIN relationship_labels l ON l.id = a.label_id \
+             WHERE a.relationship_person_id = p.id AND l.name = {} COLLATE NOCASE)",
+            bind(name.clone())
+        ),
+        Term::Circle(name) => format!(
+            "EXISTS (SELECT 1 FROM circle_members m JOIN circles c ON c.id = m.circle_id \
+             WHERE m.person_id = p.id AND c.archived = 0 AND c.name = {} COLLATE NOCASE)",
+            bind(name.clone())
+        ),
+        Term::NotContactedWithin(days) => format!(
+            "NOT EXISTS (SELECT 1 FROM interactions i WHERE i.relationship_person_id = p.id AND i.date > {})",
+            bind(date_param(today - Duration::days(*days)))
+        ),
+        Term::ContactedWithin(days) => format!(
+            "EXISTS (SELECT 1 FROM interactions i WHERE i.relationship_person_id = p.id AND i.date >= {})",
+            bind(date_param(today - Duration::days(*days)))
+        ),
+        Term::NeverContacted => {
+            "NOT EXISTS (SELECT 1 FROM interactions i WHERE i.relationship_person_id = p.id)".to_string()
+        }
+        Term::Medium(medium) => format!(
+            "EXISTS (SELECT 1 FROM interactions i WHERE i.relationship_person_id = p.id AND i.medium = {})",
+            bind(medium.to_db_str().to_string())
+        ),
+        Term::Has(attribute) => match attribute {
+            Attribute::Email | Attribute::Phone | Attribute::Address => format!(
+                "EXISTS (SELECT 1 FROM contact_entries e WHERE e.person_id = p.id AND e.contact_type = {})",
+                bind(match attribute {
+                    Attribute::Email => "Email",
+                    Attribute::Phone => "Phone",
+                    _ => "PhysicalAddress",
+                }.to_string())
+            ),
+            Attribute::Birthday => "p.birthday IS NOT NULL".to_string(),
+            Attribute::Reminder => "EXISTS (SELECT 1 FROM relationships r \
+                                    WHERE r.person_id = p.id AND r.reminder_days IS NOT NULL)"
+                .to_string(),
+            Attribute::Nickname => "COALESCE(p.nickname, '') <> ''".to_string(),
+            Attribute::Notes => "COALESCE(p.notes, '') <> ''".to_string(),
+        },
+        Term::BirthdayWithin(days) => {
+            // Month-day strings of the coming days; birthdays are stored as YYYY-MM-DD
+            let days = (*days).clamp(0, 365);
+            let placeholders: Vec<String> = (0..=days)
+                .map(|d| today + Duration::days(d))
+                .map(|date| bind(format!("{:02}-{:02}", date.month(), date.day())))
+                .collect();
+            format!("substr(p.birthday, 6, 5) IN ({})", placeholders.join(", "))
+        }
+        Term::Not(inner) => format!("NOT ({})", term_sql(inner, today, people, params)),
+    }
+}
+
+fn date_param(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_terms_quotes_and_sort() {
+        let filter = PersonFilter::parse(
+            r#"label:friend circle:"book club" last>60d medium:inperson -has:email birthday:next2w sort:-last-contact ann"#,
+        )
+        .unwrap();
+        assert_eq!(
+            filter.terms,
+            vec![
+                Term::Label("friend".into()),
+                Term::Circle("book club".into()),
+                Term::NotContactedWithin(60),
+                Term::Medium(InteractionMedium::InPerson),
+                Term::Not(Box::new(Term::Has(Attribute::Email))),
+                Term::BirthdayWithin(14),
+                Term::Name("ann".into()),
+            ]
+        );
+        assert_eq!(filter.sort, SortKey::LastContact);
+        assert!(filter.descending);
+    }
+
+    #[test]
+    fn rejects_unknown_filters() {
+        assert!(PersonFilter::parse("colour:red").is_err());
+        assert!(PersonFilter::parse("last>soon").is_err());
+        assert!(PersonFilter::parse("has:pets").is_err());
+        assert!(PersonFilter::parse("sort:age").is_err());
+        assert!(PersonFilter::parse("label:").is_err());
+    }
+
+    #[test]
+    fn durations_accept_units() {
+        assert_eq!(parse_days("10"), Ok(10));
+        assert_eq!(parse_days("2w"), Ok(14));
+        assert_eq!(parse_days("3m"), Ok(90));
+        assert_eq!(parse_days("1y"), Ok(365));
+    }
+}
@@ -1,3 +1,4 @@
+use chrono::NaiveDate;
 use rusqlite::Connection;
 
 use crate::db::person_repo;
@@ -23,3 +24,8 @@ pub fn find_by_name(conn: &Connection, owner_id: Id<User>, query: &str) -> PrmRe
 pub fn get_person(conn: &Connection, person_id: Id<Person>) -> PrmResult<Option<Person>> {
     person_repo::find_by_id(conn, person_id)
 }
+
+/// Active people added on or after `since`, oldest first, excluding yourself.
+pub fn added_since(conn: &Connection, owner_id: Id<User>, since: NaiveDate) -> PrmResult<Vec<Person>> {
+    person_repo::find_created_since(conn, owner_id, since)
+}
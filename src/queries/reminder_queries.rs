@@ -1,12 +1,15 @@
 use chrono::NaiveDate;
 use rusqlite::Connection;
+use serde::Serialize;
 
 use crate::db::{interaction_repo, person_repo, relationship_repo};
 use crate::error::PrmResult;
 use crate::model::{Id, Person, Relationship, User};
 
 /// Whether someone is overdue for contact and by how much.
-#[derive(Debug, Clone)]
+/// Serializes as `{"status": "days_overdue", "days": 3}` or `{"status": "never_contacted"}`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", content = "days", rename_all = "snake_case")]
 pub enum OverdueStatus {
     /// Person has been contacted before; days is positive if overdue, negative if not yet due.
     DaysOverdue(i64),
@@ -15,7 +18,7 @@ pub enum OverdueStatus {
 }
 
 /// Information about a relationship's reminder status.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ReminderStatus {
     pub person: Person,
     pub relationship: Relationship,
@@ -76,7 +79,7 @@ pub fn people_needing_reminder(
                 OverdueStatus::DaysOverdue(days) => *days > 0,
             };
 
-            if is_overdue && !status.person.archived {
+            if is_overdue && !status.person.archived && !is_snoozed(conn, rel.person_id, as_of)? {
                 results.push(status);
             }
         }
@@ -107,7 +110,7 @@ pub fn all_reminders(
 
     for rel in rels {
         if let Some(status) = reminder_status(conn, rel.person_id, as_of)? {
-            if !status.person.archived {
+            if !status.person.archived && !is_snoozed(conn, rel.person_id, as_of)? {
                 results.push(status);
             }
         }
@@ -127,3 +130,38 @@ pub fn all_reminders(
 
     Ok(results)
 }
+
+/// Whether reminders for the person are snoozed past `as_of`.
+pub fn is_snoozed(conn: &Connection, person_id: Id<Person>, as_of: NaiveDate) -> PrmResult<bool> {
+    Ok(relationship_repo::find_snooze(conn, person_id)?.is_some_and(|until| until > as_of))
+}
+
+/// Reminder intervals offered as suggestions, in days.
+pub const CADENCES: &[i32] = &[7, 14, 30, 60, 90, 180, 365];
+
+/// Suggests a reminder interval for a person from how often you've been in
+/// touch: the median gap between days with an interaction, rounded to the
+/// nearest of `CADENCES`. With fewer than two such days it's 30.
+pub fn suggested_cadence(conn: &Connection, person_id: Id<Person>) -> PrmResult<i32> {
+    let dates: Vec<NaiveDate> = interaction_repo::find_by_person(conn, person_id)?
+        .iter()
+        .map(|i| i.date)
+        .collect();
+    Ok(cadence_from_dates(&dates))
+}
+
+pub fn cadence_from_dates(dates: &[NaiveDate]) -> i32 {
+    let mut dates = dates.to_vec();
+    dates.sort();
+    dates.dedup();
+    let mut gaps: Vec<i64> = dates.windows(2).map(|w| (w[1] - w[0]).num_days()).collect();
+    if gaps.is_empty() {
+        return 30;
+    }
+    gaps.sort();
+    let median = gaps[gaps.len() / 2];
+    *CADENCES
+        .iter()
+        .min_by_key(|c| (**c as i64 - median).abs())
+        .unwrap_or(&30)
+}
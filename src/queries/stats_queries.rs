@@ -1,12 +1,14 @@
 use chrono::Local;
 use rusqlite::Connection;
+use serde::Serialize;
 
 use crate::db::{circle_repo, contact_repo, interaction_repo, person_repo, relationship_repo};
 use crate::error::PrmResult;
 use crate::model::{Id, Person, User};
 use crate::queries::reminder_queries;
 
-#[derive(Debug, Clone)]
+/// `longest_gap` serializes as `[name, days]`.
+#[derive(Debug, Clone, Serialize)]
 pub struct NetworkStats {
     pub total_people: usize,
     pub active_people: usize,
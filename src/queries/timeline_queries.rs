@@ -0,0 +1,177 @@
+use chrono::{Datelike, Duration, NaiveDate};
+use rusqlite::Connection;
+
+use crate::error::PrmResult;
+use crate::model::{Circle, Id, Interaction, InteractionMedium, Person, RelationshipLabel, User};
+use crate::queries::{circle_queries, interaction_queries, relationship_queries};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    /// Weeks starting on Monday.
+    Week,
+    Month,
+}
+
+impl Period {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "week" | "weekly" | "w" => Some(Period::Week),
+            "month" | "monthly" | "m" => Some(Period::Month),
+            _ => None,
+        }
+    }
+
+    /// The first day of the period containing `date`.
+    pub fn start_of(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Week => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+            Period::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    /// "Week of Mon 4 Mar 2024" or "March 2024".
+    pub fn label(self, start: NaiveDate) -> String {
+        match self {
+            Period::Week => start.format("Week of %a %-d %b %Y").to_string(),
+            Period::Month => start.format("%B %Y").to_string(),
+        }
+    }
+}
+
+/// Interactions logged together with several people (at the same time, with
+/// the same day, medium, place, topics and note), shown once.
+#[derive(Debug, Clone)]
+pub struct TimelineEvent {
+    pub date: NaiveDate,
+    pub medium: InteractionMedium,
+    pub my_location: String,
+    pub topics: Vec<String>,
+    pub note: Option<String>,
+    pub people: Vec<Person>,
+    /// One interaction per person, in the same order as `people`.
+    pub interaction_ids: Vec<Id<Interaction>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TimelinePeriod {
+    pub start: NaiveDate,
+    pub events: Vec<TimelineEvent>,
+}
+
+impl TimelinePeriod {
+    /// Number of individual interactions, counting each person in a group event.
+    pub fn interaction_count(&self) -> usize {
+        self.events.iter().map(|e| e.people.len()).sum()
+    }
+
+    /// Number of different people seen in the period.
+    pub fn people_count(&self) -> usize {
+        let mut ids: Vec<Id<Person>> = Vec::new();
+        for person in self.events.iter().flat_map(|e| &e.people) {
+            if !ids.contains(&person.id) {
+                ids.push(person.id);
+            }
+        }
+        ids.len()
+    }
+}
+
+/// Narrows the timeline to events involving at least one person in the
+/// circle / with the label, and to one medium.
+#[derive(Debug, Clone, Default)]
+pub struct TimelineFilter {
+    pub circle: Option<Id<Circle>>,
+    pub label: Option<Id<RelationshipLabel>>,
+    pub medium: Option<InteractionMedium>,
+}
+
+/// Interactions between `from` and `to` (inclusive), oldest first, with group
+/// events collapsed and bucketed by `period`.
+pub fn timeline(
+    conn: &Connection,
+    owner_id: Id<User>,
+    from: NaiveDate,
+    to: NaiveDate,
+    filter: &TimelineFilter,
+    period: Period,
+) -> PrmResult<Vec<TimelinePeriod>> {
+    let mut rows = interaction_queries::interactions_in_range(conn, owner_id, from, to)?;
+    let logged_at = interaction_queries::logged_at_in_range(conn, owner_id, from, to)?;
+    if let Some(medium) = filter.medium {
+        rows.retain(|(_, i)| i.medium == medium);
+    }
+    rows.sort_by(|(pa, a), (pb, b)| a.date.cmp(&b.date).then_with(|| pa.name.cmp(&pb.name)));
+
+    // Each event with when it was logged
+    let mut events: Vec<(TimelineEvent, Option<&String>)> = Vec::new();
+    for (person, interaction) in rows {
+        let mut topics = interaction.topics.clone();
+        topics.sort();
+        let logged = logged_at.get(&interaction.id);
+        let existing = events.iter_mut().find(|(e, at)| {
+            *at == logged
+                && e.date == interaction.date
+                && e.medium == interaction.medium
+                && e.my_location == interaction.my_location
+                && e.note == interaction.note
+                && e.topics == topics
+                && !e.people.iter().any(|p| p.id == person.id)
+        });
+        match existing {
+            Some((event, _)) => {
+                event.people.push(person);
+                event.interaction_ids.push(interaction.id);
+            }
+            None => events.push((TimelineEvent {
+                date: interaction.date,
+                medium: interaction.medium,
+                my_location: interaction.my_location,
+                topics,
+                note: interaction.note,
+                people: vec![person],
+                interaction_ids: vec![interaction.id],
+            }, logged)),
+        }
+    }
+
+    let mut kept = Vec::new();
+    for (event, _) in events {
+        if involves(conn, owner_id, &event, filter)? {
+            kept.push(event);
+        }
+    }
+
+    let mut periods: Vec<TimelinePeriod> = Vec::new();
+    for event in kept {
+        let start = period.start_of(event.date);
+        match periods.last_mut() {
+            Some(p) if p.start == start => p.events.push(event),
+            _ => periods.push(TimelinePeriod { start, events: vec![event] }),
+        }
+    }
+    Ok(periods)
+}
+
+fn involves(conn: &Connection, owner_id: Id<User>, event: &TimelineEvent, filter: &TimelineFilter) -> PrmResult<bool> {
+    if filter.circle.is_none() && filter.label.is_none() {
+        return Ok(true);
+    }
+    for person in &event.people {
+        let in_circle = match filter.circle {
+            None => true,
+            Some(circle_id) => circle_queries::circles_for_person(conn, owner_id, person.id)?
+                .iter()
+                .any(|c| c.id == circle_id),
+        };
+        let has_label = match filter.label {
+            None => true,
+            Some(label_id) => relationship_queries::labels_for(conn, person.id)?
+                .iter()
+                .any(|l| l.id == label_id),
+        };
+        if in_circle && has_label {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
@@ -0,0 +1,300 @@
+//! A small YAML subset for vault frontmatter: scalars, flow lists (`[a, b]`),
+//! block lists (`- a`) and lists of flat maps (`- type: phone`). Enough to
+//! round-trip what we write and survive hand edits in a notes app.
+
+use crate::error::{PrmError, PrmResult};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum YamlValue {
+    Scalar(Option<String>),
+    List(Vec<YamlValue>),
+    Map(Vec<(String, YamlValue)>),
+}
+
+impl YamlValue {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            YamlValue::Scalar(Some(s)) => Some(s.as_str()),
+            _ => None,
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&YamlValue> {
+        match self {
+            YamlValue::Map(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// List items as strings; a lone scalar counts as a one-item list.
+    pub fn as_string_list(&self) -> Vec<String> {
+        match self {
+            YamlValue::List(items) => items
+                .iter()
+                .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                .collect(),
+            YamlValue::Scalar(Some(s)) => vec![s.clone()],
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Splits a document into its frontmatter block and body.
+/// Returns `None` for the frontmatter if the document does not start with `---`.
+pub fn split_document(text: &str) -> (Option<&str>, &str) {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let rest = match text.strip_prefix("---\n").or_else(|| text.strip_prefix("---\r\n")) {
+        Some(r) => r,
+        None => return (None, text),
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            let body = &rest[offset + line.len()..];
+            return (Some(&rest[..offset]), body);
+        }
+        offset += line.len();
+    }
+    (None, text)
+}
+
+pub fn parse(block: &str) -> PrmResult<Vec<(String, YamlValue)>> {
+    let lines: Vec<(usize, &str)> = block
+        .lines()
+        .map(|l| l.trim_end())
+        .filter(|l| !l.trim().is_empty() && !l.trim_start().starts_with('#'))
+        .map(|l| (l.len() - l.trim_start().len(), l.trim_start()))
+        .collect();
+
+    let mut entries = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let (indent, line) = lines[i];
+        if indent != 0 {
+            return Err(PrmError::Other(format!("Unexpected indentation: '{}'", line)));
+        }
+        let (key, value) = split_key_value(line)?;
+        i += 1;
+
+        if !value.is_empty() {
+            entries.push((key, parse_inline(value)));
+            continue;
+        }
+
+        // Block list under this key
+        let mut items = Vec::new();
+        while i < lines.len() && lines[i].0 > 0 {
+            let (item_indent, item_line) = lines[i];
+            let item = match item_line.strip_prefix('-') {
+                Some(rest) => rest.trim_start(),
+                None => {
+                    return Err(PrmError::Other(format!("Expected list item: '{}'", item_line)));
+                }
+            };
+            i += 1;
+
+            if is_map_entry(item) {
+                let mut map = Vec::new();
+                let (k, v) = split_key_value(item)?;
+                map.push((k, parse_inline(v)));
+                while i < lines.len() && lines[i].0 > item_indent && !lines[i].1.starts_with('-') {
+                    let (k, v) = split_key_value(lines[i].1)?;
+                    map.push((k, parse_inline(v)));
+                    i += 1;
+                }
+                items.push(YamlValue::Map(map));
+            } else {
+                items.push(parse_inline(item));
+            }
+        }
+
+        if items.is_empty() {
+            entries.push((key, YamlValue::Scalar(None)));
+        } else {
+            entries.push((key, YamlValue::List(items)));
+        }
+    }
+
+    Ok(entries)
+}
+
+fn is_map_entry(item: &str) -> bool {
+    if item.starts_with('"') || item.starts_with('\'') {
+        return false;
+    }
+    match item.find(':') {
+        Some(pos) => {
+            let key = &item[..pos];
+            !key.is_empty()
+                && key.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+                && item[pos + 1..].chars().next().is_none_or(|c| c == ' ')
+        }
+        None => false,
+    }
+}
+
+fn split_key_value(line: &str) -> PrmResult<(String, &str)> {
+    match line.find(':') {
+        Some(pos) => Ok((line[..pos].trim().to_string(), line[pos + 1..].trim())),
+        None => Err(PrmError::Other(format!("Expected 'key: value': '{}'", line))),
+    }
+}
+
+fn parse_inline(value: &str) -> YamlValue {
+    let value = value.trim();
+    if let Some(inner) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+        return YamlValue::List(
+            split_flow_items(inner)
+                .into_iter()
+                .map(|item| YamlValue::Scalar(parse_scalar(&item)))
+                .filter(|v| *v != YamlValue::Scalar(None))
+                .collect(),
+        );
+    }
+    YamlValue::Scalar(parse_scalar(value))
+}
+
+fn split_flow_items(inner: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+
+    for c in inner.chars() {
+        match quote {
+            Some(q) => {
+                current.push(c);
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' && q == '"' {
+                    escaped = true;
+                } else if c == q {
+                    quote = None;
+                }
+            }
+            None => match c {
+                '"' | '\'' => {
+                    quote = Some(c);
+                    current.push(c);
+                }
+                ',' => items.push(std::mem::take(&mut current)),
+                c => current.push(c),
+            },
+        }
+    }
+    items.push(current);
+    items.into_iter().map(|s| s.trim().to_string()).collect()
+}
+
+fn parse_scalar(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    if let Some(inner) = raw.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        let mut out = String::new();
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            if c == '\\' {
+                match chars.next() {
+                    Some('n') => out.push('\n'),
+                    Some('t') => out.push('\t'),
+                    Some(other) => out.push(other),
+                    None => out.push('\\'),
+                }
+            } else {
+                out.push(c);
+            }
+        }
+        return Some(out);
+    }
+    if let Some(inner) = raw.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+        return Some(inner.replace("''", "'"));
+    }
+
+    // Strip trailing comments on plain scalars
+    let plain = match raw.find(" #") {
+        Some(pos) => raw[..pos].trim_end(),
+        None => raw,
+    };
+    match plain {
+        "" | "~" | "null" | "Null" | "NULL" => None,
+        s => Some(s.to_string()),
+    }
+}
+
+/// Formats a string as a YAML scalar, quoting only when needed.
+pub fn scalar(value: &str) -> String {
+    let needs_quotes = value.is_empty()
+        || value != value.trim()
+        || matches!(
+            value.to_lowercase().as_str(),
+            "null" | "~" | "true" | "false" | "yes" | "no"
+        )
+        || value.starts_with(|c: char| !c.is_alphabetic())
+        || value.contains(": ")
+        || value.contains(" #")
+        || value.ends_with(':')
+        || value.contains(['"', '\'', ',', '[', ']', '{', '}', '\n', '\t']);
+
+    if needs_quotes {
+        let escaped = value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+            .replace('\t', "\\t");
+        format!("\"{}\"", escaped)
+    } else {
+        value.to_string()
+    }
+}
+
+/// Formats a list of strings as a YAML flow list.
+pub fn flow_list(values: &[String]) -> String {
+    format!(
+        "[{}]",
+        values.iter().map(|v| scalar(v)).collect::<Vec<_>>().join(", ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scalar_roundtrips_through_parse() {
+        for value in ["plain", "555-1234", "a: b", "quote \"me\"", "null", " padded ", "line\nbreak"] {
+            let parsed = parse(&format!("key: {}\n", scalar(value))).unwrap();
+            assert_eq!(parsed[0].1.as_str(), Some(value));
+        }
+    }
+
+    #[test]
+    fn parses_flow_and_block_lists() {
+        let parsed = parse("labels: [friend, \"book, club\"]\ncircles:\n  - Work\n  - 'Rock climbing'\n").unwrap();
+        assert_eq!(parsed[0].1.as_string_list(), vec!["friend", "book, club"]);
+        assert_eq!(parsed[1].1.as_string_list(), vec!["Work", "Rock climbing"]);
+    }
+
+    #[test]
+    fn parses_list_of_maps() {
+        let parsed = parse("contacts:\n  - type: phone\n    value: \"555\"\n  - type: email\n    value: a@b.c\n").unwrap();
+        match &parsed[0].1 {
+            YamlValue::List(items) => {
+                assert_eq!(items.len(), 2);
+                assert_eq!(items[0].get("value").and_then(|v| v.as_str()), Some("555"));
+                assert_eq!(items[1].get("type").and_then(|v| v.as_str()), Some("email"));
+            }
+            other => panic!("expected list, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn split_document_separates_frontmatter() {
+        let (fm, body) = split_document("---\nname: A\n---\nHello\n");
+        assert_eq!(fm, Some("name: A\n"));
+        assert_eq!(body, "Hello\n");
+
+        let (fm, body) = split_document("No frontmatter");
+        assert!(fm.is_none());
+        assert_eq!(body, "No frontmatter");
+    }
+}
@@ -0,0 +1,822 @@
+//! Two-way sync between the network and a directory of `<name>.md` files
+//! (one per person, YAML frontmatter plus notes), e.g. an Obsidian vault.
+//!
+//! The record each person had at the last sync is stored in
+//! `vault_sync_state` and used as the base of a per-field three-way merge,
+//! so edits on either side are applied and only fields changed on both
+//! sides to different values are reported as conflicts.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chrono::NaiveDate;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::db::{contact_repo, person_repo, relationship_repo, vault_repo};
+use crate::db::vault_repo::VaultSyncState;
+use crate::error::{PrmError, PrmResult};
+use crate::matching;
+use crate::model::*;
+use crate::ops::{circle_ops, contact_ops, label_ops, person_ops, relationship_ops};
+use crate::queries::{circle_queries, interaction_queries, relationship_queries};
+use crate::sync::frontmatter::{self, YamlValue};
+use crate::util;
+
+/// Marks the start of the generated interaction log; anything after it is ignored on import.
+const LOG_MARKER: &str = "<!-- prm:interactions (generated; edits below this line are ignored) -->";
+
+/// A contact entry as it appears in a vault file.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct VaultContact {
+    /// `phone`, `email`, `address`, or the name of a custom contact type.
+    pub kind: String,
+    pub value: String,
+    pub label: Option<String>,
+}
+
+/// The syncable part of a person: profile fields, notes, labels, circles,
+/// contacts and reminder cadence.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VaultRecord {
+    pub name: String,
+    pub nickname: Option<String>,
+    pub birthday: Option<NaiveDate>,
+    pub location: Option<String>,
+    pub how_we_met: Option<String>,
+    pub notes: Option<String>,
+    pub archived: bool,
+    pub reminder_days: Option<i32>,
+    pub labels: Vec<String>,
+    pub circles: Vec<String>,
+    pub contacts: Vec<VaultContact>,
+}
+
+impl VaultRecord {
+    fn normalize(mut self) -> Self {
+        self.labels.sort_by_key(|l| l.to_lowercase());
+        self.labels.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+        self.circles.sort_by_key(|c| c.to_lowercase());
+        self.circles.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+        self.contacts.sort();
+        self.contacts.dedup();
+        self
+    }
+
+    pub fn hash(&self) -> String {
+        let json = serde_json::to_string(self).unwrap_or_default();
+        util::stable_hash(&json)
+    }
+}
+
+/// A person whose file and database record both changed the same fields.
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    pub name: String,
+    pub file_name: String,
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct SyncReport {
+    /// Files written or refreshed from the database.
+    pub written: Vec<String>,
+    /// People updated from edits made in their files.
+    pub applied: Vec<String>,
+    /// People created from new files.
+    pub created: Vec<String>,
+    pub conflicts: Vec<Conflict>,
+    pub errors: Vec<String>,
+}
+
+struct VaultFile {
+    file_name: String,
+    id: Option<Id<Person>>,
+    record: VaultRecord,
+    content: String,
+}
+
+/// Syncs every non-self person with `<dir>/<name>.md`.
+pub fn sync(
+    conn: &Connection,
+    owner_id: Id<User>,
+    self_id: Id<Person>,
+    dir: &Path,
+) -> PrmResult<SyncReport> {
+    std::fs::create_dir_all(dir)?;
+    let mut report = SyncReport::default();
+    let now = unix_now();
+
+    let states: HashMap<Id<Person>, VaultSyncState> = vault_repo::find_by_owner(conn, owner_id)?
+        .into_iter()
+        .map(|s| (s.person_id, s))
+        .collect();
+
+    let (files, broken) = read_vault(dir, &mut report)?;
+    let mut files_by_name: HashMap<String, VaultFile> =
+        files.into_iter().map(|f| (f.file_name.clone(), f)).collect();
+
+    // Which file names already belong to which person, so a new person never
+    // overwrites someone else's file. Files that couldn't be read or have no
+    // id belong to nobody and are never written over.
+    let mut claimed: HashMap<String, Option<Id<Person>>> = HashMap::new();
+    for state in states.values() {
+        claimed.insert(state.file_name.clone(), Some(state.person_id));
+    }
+    for file in files_by_name.values() {
+        match file.id {
+            Some(id) => {
+                claimed.insert(file.file_name.clone(), Some(id));
+            }
+            None => {
+                claimed.entry(file.file_name.clone()).or_insert(None);
+            }
+        }
+    }
+    for name in &broken {
+        claimed.entry(name.clone()).or_insert(None);
+    }
+
+    let people: Vec<Person> = person_repo::find_by_owner(conn, owner_id)?
+        .into_iter()
+        .filter(|p| p.id != self_id)
+        .collect();
+
+    for person in &people {
+        let state = states.get(&person.id);
+
+        let file_name = files_by_name
+            .values()
+            .find(|f| f.id == Some(person.id))
+            .map(|f| f.file_name.clone())
+            .or_else(|| state.map(|s| s.file_name.clone()).filter(|n| files_by_name.contains_key(n)));
+
+        // A file without an id is theirs if it has their name and they are
+        // the only active person called that.
+        let file_name = file_name.or_else(|| {
+            let same_name = |name: &str| matching::fold(name) == matching::fold(&person.name);
+            if person.archived || people.iter().filter(|p| !p.archived && same_name(&p.name)).count() != 1 {
+                return None;
+            }
+            let adopted = files_by_name
+                .values()
+                .find(|f| f.id.is_none() && claimed.get(&f.file_name) == Some(&None) && same_name(&f.record.name))
+                .map(|f| f.file_name.clone())?;
+            claimed.insert(adopted.clone(), Some(person.id));
+            Some(adopted)
+        });
+
+        if let Some(name) = &file_name {
+            if broken.contains(name) {
+                continue;
+            }
+        }
+        let file = file_name.and_then(|n| files_by_name.remove(&n));
+
+        if let Err(e) = sync_person(conn, owner_id, person, state, file, dir, now, &mut claimed, &mut report) {
+            report.errors.push(format!("{}: {}", person.name, e));
+        }
+    }
+
+    // Whatever is left describes people we don't have yet.
+    let mut remaining: Vec<VaultFile> = files_by_name.into_values().collect();
+    remaining.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+    for file in remaining {
+        if let Err(e) = create_from_file(conn, owner_id, file, dir, now, &mut claimed, &mut report) {
+            report.errors.push(e.to_string());
+        }
+    }
+
+    Ok(report)
+}
+
+fn sync_person(
+    conn: &Connection,
+    owner_id: Id<User>,
+    person: &Person,
+    state: Option<&VaultSyncState>,
+    file: Option<VaultFile>,
+    dir: &Path,
+    now: i64,
+    claimed: &mut HashMap<String, Option<Id<Person>>>,
+    report: &mut SyncReport,
+) -> PrmResult<()> {
+    let db_record = record_from_db(conn, owner_id, person)?;
+    let base: Option<VaultRecord> = state.and_then(|s| serde_json::from_str(&s.base_record).ok());
+
+    let file = match file {
+        Some(f) => f,
+        None => {
+            // New person, or the file was deleted: the database is authoritative.
+            write_person(conn, owner_id, person.id, &db_record, None, dir, now, claimed)?;
+            report.written.push(person.name.clone());
+            return Ok(());
+        }
+    };
+
+    let (state, base) = match (state, base) {
+        (Some(s), Some(b)) => (s, b),
+        _ => {
+            // A file that was never synced: only accept it if it agrees with the database.
+            let fields = differing_fields(&db_record, &file.record);
+            if fields.is_empty() {
+                write_person(conn, owner_id, person.id, &db_record, Some(&file), dir, now, claimed)?;
+            } else {
+                report.conflicts.push(Conflict {
+                    name: person.name.clone(),
+                    file_name: file.file_name.clone(),
+                    fields,
+                });
+            }
+            return Ok(());
+        }
+    };
+
+    // Content, not modification time: files restored by git, rsync or
+    // another machine can carry an older time than the last sync.
+    let file_changed = file.record.hash() != state.base_hash;
+    let db_changed = db_record.hash() != state.base_hash;
+
+    let merged = match (file_changed, db_changed) {
+        (false, _) => db_record.clone(),
+        (true, false) => file.record.clone(),
+        (true, true) => match merge(&base, &db_record, &file.record) {
+            Ok(m) => m,
+            Err(fields) => {
+                report.conflicts.push(Conflict {
+                    name: person.name.clone(),
+                    file_name: file.file_name.clone(),
+                    fields,
+                });
+                return Ok(());
+            }
+        },
+    };
+
+    if merged != db_record {
+        apply_record(conn, owner_id, person.id, &db_record, &merged)?;
+        report.applied.push(merged.name.clone());
+    }
+
+    let final_record = match person_repo::find_by_id(conn, person.id)? {
+        Some(p) => record_from_db(conn, owner_id, &p)?,
+        None => merged,
+    };
+    if write_person(conn, owner_id, person.id, &final_record, Some(&file), dir, now, claimed)? {
+        report.written.push(final_record.name.clone());
+    }
+    Ok(())
+}
+
+fn create_from_file(
+    conn: &Connection,
+    owner_id: Id<User>,
+    file: VaultFile,
+    dir: &Path,
+    now: i64,
+    claimed: &mut HashMap<String, Option<Id<Person>>>,
+    report: &mut SyncReport,
+) -> PrmResult<()> {
+    let record = &file.record;
+    // Never a second person for a file that names someone already here.
+    let existing = person_repo::find_by_owner(conn, owner_id)?
+        .into_iter()
+        .find(|p| !p.archived && matching::fold(&p.name) == matching::fold(&record.name));
+    if let Some(existing) = existing {
+        report.errors.push(format!(
+            "{}: {} is already in your network; add \"id: {}\" to the file to sync it with them",
+            file.file_name, existing.name, existing.id
+        ));
+        return Ok(());
+    }
+    let person = person_ops::add_person(
+        conn,
+        owner_id,
+        &record.name,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )?;
+    let empty = record_from_db(conn, owner_id, &person)?;
+    apply_record(conn, owner_id, person.id, &empty, record)?;
+
+    claimed.insert(file.file_name.clone(), Some(person.id));
+    let final_record = match person_repo::find_by_id(conn, person.id)? {
+        Some(p) => record_from_db(conn, owner_id, &p)?,
+        None => record.clone(),
+    };
+    write_person(conn, owner_id, person.id, &final_record, Some(&file), dir, now, claimed)?;
+    report.created.push(person.name);
+    Ok(())
+}
+
+/// Three-way merge per field. Returns the names of conflicting fields on failure.
+pub fn merge(
+    base: &VaultRecord,
+    ours: &VaultRecord,
+    theirs: &VaultRecord,
+) -> Result<VaultRecord, Vec<String>> {
+    let to_map = |r: &VaultRecord| match serde_json::to_value(r) {
+        Ok(serde_json::Value::Object(m)) => m,
+        _ => serde_json::Map::new(),
+    };
+    let base_map = to_map(base);
+    let ours_map = to_map(ours);
+    let theirs_map = to_map(theirs);
+
+    let mut merged = serde_json::Map::new();
+    let mut conflicts = Vec::new();
+    for (key, ours_val) in &ours_map {
+        let base_val = base_map.get(key);
+        let theirs_val = theirs_map.get(key).unwrap_or(ours_val);
+        let value = if theirs_val == ours_val || base_val == Some(theirs_val) {
+            ours_val.clone()
+        } else if base_val == Some(ours_val) {
+            theirs_val.clone()
+        } else {
+            conflicts.push(key.clone());
+            ours_val.clone()
+        };
+        merged.insert(key.clone(), value);
+    }
+
+    if !conflicts.is_empty() {
+        return Err(conflicts);
+    }
+    serde_json::from_value(serde_json::Value::Object(merged)).map_err(|e| vec![e.to_string()])
+}
+
+fn differing_fields(a: &VaultRecord, b: &VaultRecord) -> Vec<String> {
+    let (a, b) = match (serde_json::to_value(a), serde_json::to_value(b)) {
+        (Ok(serde_json::Value::Object(a)), Ok(serde_json::Value::Object(b))) => (a, b),
+        _ => return Vec::new(),
+    };
+    a.iter()
+        .filter(|(k, v)| b.get(*k) != Some(v))
+        .map(|(k, _)| k.clone())
+        .collect()
+}
+
+// --- Database side ---
+
+pub fn record_from_db(conn: &Connection, owner_id: Id<User>, person: &Person) -> PrmResult<VaultRecord> {
+    let labels = relationship_queries::labels_for(conn, person.id)?
+        .into_iter()
+        .map(|l| l.name)
+        .collect();
+    let circles = circle_queries::circles_for_person(conn, owner_id, person.id)?
+        .into_iter()
+        .map(|c| c.name)
+        .collect();
+    let reminder_days = relationship_queries::get_relationship(conn, person.id)?
+        .and_then(|r| r.reminder_days);
+
+    let custom_types = contact_repo::find_custom_types(conn, owner_id)?;
+    let contacts = contact_repo::find_by_person(conn, person.id)?
+        .iter()
+        .map(|e| to_vault_contact(e, &custom_types))
+        .collect();
+
+    Ok(VaultRecord {
+        name: person.name.clone(),
+        nickname: person.nickname.clone(),
+        birthday: person.birthday,
+        location: person.location.clone(),
+        how_we_met: person.how_we_met.clone(),
+        notes: person.notes.clone(),
+        archived: person.archived,
+        reminder_days,
+        labels,
+        circles,
+        contacts,
+    }
+    .normalize())
+}
+
+fn to_vault_contact(entry: &ContactEntry, custom_types: &[CustomContactType]) -> VaultContact {
+    let kind = match &entry.contact_type {
+        ContactType::Phone => "phone".to_string(),
+        ContactType::Email => "email".to_string(),
+        ContactType::PhysicalAddress => "address".to_string(),
+        ContactType::Custom { type_id } => custom_types
+            .iter()
+            .find(|t| t.id == *type_id)
+            .map(|t| t.name.clone())
+            .unwrap_or_else(|| "custom".to_string()),
+    };
+    let value = match &entry.value {
+        ContactValue::StringValue { value } => value.clone(),
+        ContactValue::AddressValue { value } => format_address(value),
+    };
+    VaultContact {
+        kind,
+        value,
+        label: entry.label.clone(),
+    }
+}
+
+/// Addresses are written as `street; city; state; zip; country`.
+fn format_address(a: &Address) -> String {
+    [&a.street, &a.city, &a.state, &a.zip, &a.country]
+        .iter()
+        .map(|s| s.as_str())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+fn parse_address(value: &str) -> PrmResult<Address> {
+    let parts: Vec<&str> = value.split(';').map(|s| s.trim()).collect();
+    if parts.len() != 5 {
+        return Err(PrmError::Other(format!(
+            "Address must be 'street; city; state; zip; country': {}",
+            value
+        )));
+    }
+    Ok(Address {
+        street: parts[0].to_string(),
+        city: parts[1].to_string(),
+        state: parts[2].to_string(),
+        zip: parts[3].to_string(),
+        country: parts[4].to_string(),
+    })
+}
+
+/// Brings the person's database state from `current` to `target`.
+fn apply_record(
+    conn: &Connection,
+    owner_id: Id<User>,
+    person_id: Id<Person>,
+    current: &VaultRecord,
+    target: &VaultRecord,
+) -> PrmResult<()> {
+    fn changed<T: PartialEq + Clone>(from: &T, to: &T) -> Option<T> {
+        if from != to { Some(to.clone()) } else { None }
+    }
+
+    let name = changed(&current.name, &target.name);
+    let nickname = changed(&current.nickname, &target.nickname);
+    let how_we_met = changed(&current.how_we_met, &target.how_we_met);
+    let birthday = changed(&current.birthday, &target.birthday);
+    let notes = changed(&current.notes, &target.notes);
+    let location = changed(&current.location, &target.location);
+
+    person_ops::update_person(
+        conn,
+        person_id,
+        name.as_deref(),
+        nickname.as_ref().map(|v| v.as_deref()),
+        how_we_met.as_ref().map(|v| v.as_deref()),
+        birthday,
+        notes.as_ref().map(|v| v.as_deref()),
+        location.as_ref().map(|v| v.as_deref()),
+    )?;
+
+    if current.archived != target.archived {
+        if target.archived {
+            person_ops::archive_person(conn, person_id)?;
+        } else {
+            person_ops::unarchive_person(conn, person_id)?;
+        }
+    }
+
+    if current.labels != target.labels {
+        let mut label_ids = Vec::new();
+        for name in &target.labels {
+            let label = match relationship_repo::find_label_by_name(conn, owner_id, name)? {
+                Some(l) => l,
+                None => label_ops::add_label(conn, owner_id, name)?,
+            };
+            label_ids.push(label.id);
+        }
+        relationship_ops::set_labels(conn, owner_id, person_id, label_ids)?;
+    }
+
+    if current.reminder_days != target.reminder_days {
+        relationship_ops::set_reminder(conn, person_id, target.reminder_days)?;
+    }
+
+    if current.circles != target.circles {
+        let has = |list: &[String], name: &str| list.iter().any(|c| c.eq_ignore_ascii_case(name));
+        for name in target.circles.iter().filter(|c| !has(&current.circles, c)) {
+            match circle_queries::find_circle_by_name(conn, owner_id, name)? {
+                Some(circle) => {
+                    circle_ops::add_members(conn, circle.id, vec![person_id])?;
+                }
+                None => {
+                    circle_ops::create_circle(conn, owner_id, name, None, vec![person_id])?;
+                }
+            }
+        }
+        for name in current.circles.iter().filter(|c| !has(&target.circles, c)) {
+            if let Some(circle) = circle_queries::find_circle_by_name(conn, owner_id, name)? {
+                circle_ops::remove_members(conn, circle.id, vec![person_id])?;
+            }
+        }
+    }
+
+    if current.contacts != target.contacts {
+        let custom_types = contact_repo::find_custom_types(conn, owner_id)?;
+        for entry in contact_repo::find_by_person(conn, person_id)? {
+            if !target.contacts.contains(&to_vault_contact(&entry, &custom_types)) {
+                contact_ops::remove_contact(conn, entry.id)?;
+            }
+        }
+        for contact in target.contacts.iter().filter(|c| !current.contacts.contains(c)) {
+            add_contact(conn, owner_id, person_id, contact)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn add_contact(
+    conn: &Connection,
+    owner_id: Id<User>,
+    person_id: Id<Person>,
+    contact: &VaultContact,
+) -> PrmResult<()> {
+    let label = contact.label.as_deref();
+    match contact.kind.to_lowercase().as_str() {
+        "phone" => {
+            contact_ops::add_phone(conn, person_id, &contact.value, label)?;
+        }
+        "email" => {
+            contact_ops::add_email(conn, person_id, &contact.value, label)?;
+        }
+        "address" => {
+            let a = parse_address(&contact.value)?;
+            contact_ops::add_address(conn, person_id, &a.street, &a.city, &a.state, &a.zip, &a.country, label)?;
+        }
+        _ => {
+            let ct = match contact_repo::find_custom_type_by_name(conn, owner_id, &contact.kind)? {
+                Some(ct) => ct,
+                None => contact_ops::create_custom_contact_type(conn, owner_id, &contact.kind)?,
+            };
+            contact_ops::add_custom_contact(conn, person_id, ct.id, &contact.value, label)?;
+        }
+    }
+    Ok(())
+}
+
+// --- File side ---
+
+fn read_vault(dir: &Path, report: &mut SyncReport) -> PrmResult<(Vec<VaultFile>, HashSet<String>)> {
+    let mut files = Vec::new();
+    let mut broken = HashSet::new();
+
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("md") {
+            continue;
+        }
+        let file_name = match path.file_name().and_then(|n| n.to_str()) {
+            Some(n) => n.to_string(),
+            None => continue,
+        };
+        let content = std::fs::read_to_string(&path)?;
+
+        match parse_file(&content) {
+            Ok((id, record)) => files.push(VaultFile {
+                file_name,
+                id,
+                record,
+                content,
+            }),
+            Err(e) => {
+                report.errors.push(format!("{}: {}", file_name, e));
+                broken.insert(file_name);
+            }
+        }
+    }
+
+    Ok((files, broken))
+}
+
+/// Parses a vault file into the person id (if present) and record.
+pub fn parse_file(content: &str) -> PrmResult<(Option<Id<Person>>, VaultRecord)> {
+    let (block, body) = frontmatter::split_document(content);
+    let block = block.ok_or_else(|| PrmError::Other("Missing YAML frontmatter".into()))?;
+    let entries = frontmatter::parse(block)?;
+    let get = |key: &str| entries.iter().find(|(k, _)| k == key).map(|(_, v)| v);
+    let get_str = |key: &str| get(key).and_then(|v| v.as_str()).map(|s| s.to_string());
+
+    let id = match get_str("id") {
+        Some(s) => Some(
+            Id::parse(&s).map_err(|e| PrmError::Other(format!("Invalid id '{}': {}", s, e)))?,
+        ),
+        None => None,
+    };
+
+    let name = get_str("name")
+        .filter(|s| !s.trim().is_empty())
+        .ok_or_else(|| PrmError::BlankField { field: "name".into() })?;
+
+    let birthday = match get_str("birthday") {
+        Some(s) => Some(
+            NaiveDate::parse_from_str(&s, "%Y-%m-%d")
+                .map_err(|_| PrmError::Other(format!("Invalid birthday '{}' (use YYYY-MM-DD)", s)))?,
+        ),
+        None => None,
+    };
+
+    let archived = match get_str("archived").map(|s| s.to_lowercase()) {
+        None => false,
+        Some(s) if s == "true" || s == "yes" => true,
+        Some(s) if s == "false" || s == "no" => false,
+        Some(s) => return Err(PrmError::Other(format!("Invalid archived value '{}'", s))),
+    };
+
+    let reminder_days = match get_str("reminder_days") {
+        Some(s) => Some(
+            s.parse::<i32>()
+                .map_err(|_| PrmError::Other(format!("Invalid reminder_days '{}'", s)))?,
+        ),
+        None => None,
+    };
+
+    let mut contacts = Vec::new();
+    if let Some(YamlValue::List(items)) = get("contacts") {
+        for item in items {
+            let kind = item.get("type").and_then(|v| v.as_str());
+            let value = item.get("value").and_then(|v| v.as_str());
+            match (kind, value) {
+                (Some(kind), Some(value)) => contacts.push(VaultContact {
+                    kind: kind.to_string(),
+                    value: value.to_string(),
+                    label: item.get("label").and_then(|v| v.as_str()).map(|s| s.to_string()),
+                }),
+                _ => return Err(PrmError::Other("Each contact needs a type and a value".into())),
+            }
+        }
+    }
+
+    let notes_text = match body.find(LOG_MARKER) {
+        Some(pos) => &body[..pos],
+        None => body,
+    };
+    let notes = Some(notes_text.trim().to_string()).filter(|s| !s.is_empty());
+
+    let record = VaultRecord {
+        name,
+        nickname: get_str("nickname"),
+        birthday,
+        location: get_str("location"),
+        how_we_met: get_str("how_we_met"),
+        notes,
+        archived,
+        reminder_days,
+        labels: get("labels").map(|v| v.as_string_list()).unwrap_or_default(),
+        circles: get("circles").map(|v| v.as_string_list()).unwrap_or_default(),
+        contacts,
+    };
+    Ok((id, record.normalize()))
+}
+
+/// Renders a person's vault file.
+pub fn render_file(person_id: Id<Person>, record: &VaultRecord, interactions: &[Interaction]) -> String {
+    let line = |key: &str, value: String| {
+        if value.is_empty() {
+            format!("{}:\n", key)
+        } else {
+            format!("{}: {}\n", key, value)
+        }
+    };
+    let opt = |v: &Option<String>| v.as_deref().map(frontmatter::scalar).unwrap_or_default();
+
+    let mut out = String::from("---\n");
+    out.push_str(&line("id", person_id.to_string()));
+    out.push_str(&line("name", frontmatter::scalar(&record.name)));
+    out.push_str(&line("nickname", opt(&record.nickname)));
+    out.push_str(&line("birthday", record.birthday.map(|d| d.to_string()).unwrap_or_default()));
+    out.push_str(&line("location", opt(&record.location)));
+    out.push_str(&line("how_we_met", opt(&record.how_we_met)));
+    out.push_str(&line("archived", record.archived.to_string()));
+    out.push_str(&line("reminder_days", record.reminder_days.map(|d| d.to_string()).unwrap_or_default()));
+    out.push_str(&line("labels", frontmatter::flow_list(&record.labels)));
+    out.push_str(&line("circles", frontmatter::flow_list(&record.circles)));
+    if record.contacts.is_empty() {
+        out.push_str("contacts: []\n");
+    } else {
+        out.push_str("contacts:\n");
+        for c in &record.contacts {
+            out.push_str(&format!("  - type: {}\n", frontmatter::scalar(&c.kind)));
+            out.push_str(&format!("    value: {}\n", frontmatter::scalar(&c.value)));
+            if let Some(label) = &c.label {
+                out.push_str(&format!("    label: {}\n", frontmatter::scalar(label)));
+            }
+        }
+    }
+    out.push_str("---\n\n");
+
+    if let Some(notes) = &record.notes {
+        out.push_str(notes.trim());
+        out.push_str("\n\n");
+    }
+
+    out.push_str(LOG_MARKER);
+    out.push_str("\n## Interactions\n\n");
+    if interactions.is_empty() {
+        out.push_str("_None yet._\n");
+    }
+    for i in interactions {
+        out.push_str(&format!(
+            "- {} {} ({}) — {}\n",
+            i.date,
+            i.medium.display_name(),
+            i.my_location,
+            i.topics.join(", ")
+        ));
+        if let Some(note) = &i.note {
+            out.push_str(&format!("  - {}\n", note));
+        }
+    }
+    out
+}
+
+/// Writes the person's file (renaming it if their name changed) and records
+/// the synced state. Returns whether the file content changed.
+fn write_person(
+    conn: &Connection,
+    owner_id: Id<User>,
+    person_id: Id<Person>,
+    record: &VaultRecord,
+    existing: Option<&VaultFile>,
+    dir: &Path,
+    now: i64,
+    claimed: &mut HashMap<String, Option<Id<Person>>>,
+) -> PrmResult<bool> {
+    let file_name = choose_file_name(&record.name, person_id, existing.map(|f| f.file_name.as_str()), claimed);
+    let interactions = interaction_queries::interactions_with(conn, person_id)?;
+    let content = render_file(person_id, record, &interactions);
+
+    let mut changed = true;
+    match existing {
+        Some(f) if f.file_name == file_name && f.content == content => changed = false,
+        Some(f) if f.file_name != file_name => {
+            std::fs::write(dir.join(&file_name), &content)?;
+            std::fs::remove_file(dir.join(&f.file_name))?;
+            if claimed.get(&f.file_name) == Some(&Some(person_id)) {
+                claimed.remove(&f.file_name);
+            }
+        }
+        _ => std::fs::write(dir.join(&file_name), &content)?,
+    }
+    claimed.insert(file_name.clone(), Some(person_id));
+
+    vault_repo::upsert(
+        conn,
+        owner_id,
+        &VaultSyncState {
+            person_id,
+            file_name,
+            base_record: serde_json::to_string(record)?,
+            base_hash: record.hash(),
+            // Stamp after the write so our own write doesn't count as an edit.
+            synced_at: now.max(unix_now()),
+        },
+    )?;
+    Ok(changed)
+}
+
+fn choose_file_name(
+    name: &str,
+    person_id: Id<Person>,
+    current: Option<&str>,
+    claimed: &HashMap<String, Option<Id<Person>>>,
+) -> String {
+    let stem: String = name
+        .chars()
+        .filter(|c| !matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') && !c.is_control())
+        .collect::<String>()
+        .trim()
+        .trim_matches('.')
+        .to_string();
+    let stem = if stem.is_empty() { person_id.to_string() } else { stem };
+
+    let mut n = 1;
+    loop {
+        let candidate = if n == 1 {
+            format!("{}.md", stem)
+        } else {
+            format!("{} ({}).md", stem, n)
+        };
+        let free = match claimed.get(&candidate) {
+            None => true,
+            Some(owner) => *owner == Some(person_id),
+        };
+        if free || current == Some(candidate.as_str()) {
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
@@ -0,0 +1,49 @@
+//! Small helpers shared across layers.
+
+/// 64-bit FNV-1a of `s`, in hex. Stable across builds and platforms, unlike
+/// `DefaultHasher`, so it can be stored or used in file names.
+pub fn stable_hash(s: &str) -> String {
+    let hash = s
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, b| (hash ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3));
+    format!("{:016x}", hash)
+}
+
+/// Splits on whitespace, keeping double-quoted text together (quotes removed).
+pub fn tokenize(input: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    for c in input.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stable_hash_is_fnv1a() {
+        assert_eq!(stable_hash(""), "cbf29ce484222325");
+        assert_eq!(stable_hash("a"), "af63dc4c8601ec8c");
+    }
+
+    #[test]
+    fn tokenize_keeps_quoted_text_together() {
+        assert_eq!(tokenize(r#"circle:"book club"  last>60d"#), vec!["circle:book club", "last>60d"]);
+        assert!(tokenize("   ").is_empty());
+    }
+}
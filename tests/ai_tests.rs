@@ -0,0 +1,529 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use prm::ai::eval::{self, EvalCase};
+use prm::ai::llm_backend::{BackendKind, LlmConfig};
+use prm::ai::llm_fixtures::{self, StandInServer};
+use prm::ai::llm_service::{self, LlmError};
+use prm::ai::person_summary;
+use prm::ai::semantic_search::{self, Hit};
+use prm::db::*;
+use prm::ops::*;
+
+fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/llm")
+}
+
+fn stand_in() -> StandInServer {
+    StandInServer::start(llm_fixtures::load_dir(&fixtures_dir()).unwrap()).unwrap()
+}
+
+/// `prm -f <db>` talking to `server`, with its output discarded.
+fn prm(db: &Path, server: &StandInServer) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_prm"));
+    command
+        .arg("-f")
+        .arg(db)
+        .env("PRM_LLM_BACKEND", "ollama")
+        .env("PRM_LLM_URL", server.url())
+        .env("PRM_LLM_CONFIG", db.with_file_name("no-llm.json"))
+        .env_remove("PRM_LLM_RECORD")
+        .stdout(Stdio::null());
+    command
+}
+
+/// A temporary directory holding a database with a network for Petros, made
+/// by `prm init`. Returns the directory and the database.
+fn new_network(server: &StandInServer) -> (PathBuf, PathBuf) {
+    let dir = std::env::temp_dir().join(format!("prm-ai-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let db = dir.join("prm.db");
+    assert!(prm(&db, server).args(["init", "Petros"]).status().unwrap().success());
+    (dir, db)
+}
+
+/// Runs the shell on `db` with `script` as its input, talking to `server`.
+fn run_shell(db: &Path, server: &StandInServer, script: &str) -> String {
+    let mut child = prm(db, server)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(script.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+// ==========================================================================
+// PARSING AGAINST RECORDED RESPONSES
+// ==========================================================================
+
+#[test]
+fn parse_interaction_replays_recorded_fixtures() {
+    let server = stand_in();
+    let mut config = LlmConfig::new(BackendKind::Ollama);
+    config.url = server.url().to_string();
+    let backend = config.backend();
+    backend.health_check().unwrap();
+
+    let names = vec!["Alice".to_string()];
+    let parsed = llm_service::parse_interaction_with(
+        &*backend,
+        "Video call with Alice and Bob from home yesterday about the trip",
+        &names,
+        &[],
+    )
+    .unwrap();
+    assert_eq!(parsed.person_names, vec!["Alice", "Bob"]);
+    assert_eq!(parsed.medium, "VideoCall");
+    assert_eq!(parsed.topics, vec!["trip"]);
+    assert_eq!(parsed.date.as_deref(), Some("2024-06-14"));
+
+    // An invalid reply is sent back with what's wrong, and the fix accepted
+    let parsed = llm_service::parse_interaction_with(&*backend, "Lunch with Alice at Tartine", &names, &[]).unwrap();
+    assert_eq!(parsed.medium, "InPerson");
+    assert_eq!(parsed.location, "Tartine");
+
+    // The model never gives topics: the error keeps its last reply
+    match llm_service::parse_interaction_with(&*backend, "Saw someone", &names, &[]) {
+        Err(LlmError::InvalidResponse { problems, raw, attempts }) => {
+            assert_eq!(problems, vec!["\"topics\" must list at least one non-empty string"]);
+            assert!(raw.contains("\"someone\""));
+            assert_eq!(attempts, 3);
+        }
+        other => panic!("expected an invalid response, got {:?}", other),
+    }
+
+    // Nothing recorded for this input
+    let err = llm_service::parse_interaction_with(&*backend, "Lunch with Carol", &names, &[]).unwrap_err();
+    assert!(matches!(err, LlmError::Backend(ref m) if m.contains("HTTP 404")), "{}", err);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1 + 2 + 3 + 1);
+    assert_eq!(requests[0].0, "/api/chat");
+    assert!(requests[0].1["messages"][0]["content"].as_str().unwrap().contains("Known contacts: [Alice]"));
+    assert_eq!(requests[0].1["format"]["required"].as_array().unwrap().len(), 7);
+    // The repair request carries the whole conversation so far
+    let repair = &requests[2].1["messages"];
+    assert_eq!(repair.as_array().unwrap().len(), 4);
+    assert_eq!(repair[2]["role"], "assistant");
+    assert!(repair[3]["content"].as_str().unwrap().contains("\"medium\" must be one of"));
+}
+
+#[test]
+fn recording_saves_a_replayable_fixture() {
+    let server = stand_in();
+    let dir = std::env::temp_dir().join(format!("prm-fixtures-{}", uuid::Uuid::new_v4()));
+    let mut config = LlmConfig::new(BackendKind::Ollama);
+    config.url = server.url().to_string();
+    config.record_dir = Some(dir.clone());
+
+    let input = "Had coffee with Alice at Blue Bottle, talked about her new job";
+    let reply = config.backend().chat("Respond with JSON.", input).unwrap();
+    assert!(reply.contains("Blue Bottle"));
+
+    let recorded = llm_fixtures::load_dir(&dir).unwrap();
+    assert_eq!(recorded.len(), 1);
+    assert_eq!(recorded[0].endpoint, "/api/chat");
+    assert_eq!(recorded[0].input, input);
+    assert_eq!(recorded[0].request["messages"][0]["content"], "Respond with JSON.");
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+// ==========================================================================
+// SEMANTIC SEARCH
+// ==========================================================================
+
+#[test]
+fn ask_search_finds_entries_by_meaning_and_embeds_only_changes() {
+    let server = stand_in();
+    let mut config = LlmConfig::new(BackendKind::Ollama);
+    config.url = server.url().to_string();
+    let backend = config.backend();
+
+    let conn = schema::test_connection();
+    let user = prm::model::User::create("Petros".into(), "petros@example.com".into());
+    network_repo::insert_user(&conn, &user).unwrap();
+    let add = |name: &str, notes: Option<&str>| {
+        person_ops::add_person(&conn, user.id, name, None, None, None, notes, None).unwrap()
+    };
+    let alice = add("Alice", None);
+    let bob = add("Bob", None);
+    let carol = add("Carol", Some("Loves Lisbon and coffee"));
+    let date = chrono::NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+    for person in [&alice, &bob] {
+        let topics = vec!["stress at work".to_string()];
+        interaction_ops::log_in_person(&conn, user.id, person.id, "Cafe", topics, Some("Thinking of quitting"), date)
+            .unwrap();
+    }
+    let trip = interaction_ops::log_in_person(&conn, user.id, carol.id, "Park", vec!["trip planning".into()], None, date)
+        .unwrap();
+
+    // The group interaction's text is embedded once and shared
+    let summary = semantic_search::refresh(&conn, user.id, &*backend, |_, _| {}).unwrap();
+    assert_eq!((summary.embedded, summary.reused, summary.removed), (3, 1, 0));
+
+    let results = semantic_search::search(&conn, user.id, &*backend, "who did I talk to about burnout", 2).unwrap();
+    assert_eq!(results.len(), 2);
+    match &results[0].hit {
+        Hit::Interaction { names, interaction } => {
+            let mut names = names.clone();
+            names.sort();
+            assert_eq!(names, vec!["Alice", "Bob"]);
+            assert_eq!(interaction.topics, vec!["stress at work"]);
+        }
+        other => panic!("expected the interaction, got {:?}", other),
+    }
+    assert!(results[0].score > results[1].score);
+
+    // Nothing changed: nothing is sent
+    let summary = semantic_search::refresh(&conn, user.id, &*backend, |_, _| {}).unwrap();
+    assert_eq!(summary, semantic_search::RefreshSummary::default());
+
+    // An edit re-embeds that entry; archiving drops the person's entries
+    interaction_ops::update_details(&conn, trip.id, vec!["travel to Lisbon".into()], None).unwrap();
+    let summary = semantic_search::refresh(&conn, user.id, &*backend, |_, _| {}).unwrap();
+    assert_eq!(summary.embedded, 1);
+    let results = semantic_search::search(&conn, user.id, &*backend, "any trips coming up", 1).unwrap();
+    assert!(matches!(&results[0].hit, Hit::Interaction { names, .. } if names == &["Carol"]));
+
+    person_ops::archive_person(&conn, carol.id).unwrap();
+    let summary = semantic_search::refresh(&conn, user.id, &*backend, |_, _| {}).unwrap();
+    assert_eq!(summary.removed, 2);
+
+    let embedded: Vec<String> = server.requests().iter().map(|(_, body)| body["prompt"].as_str().unwrap().to_string()).collect();
+    assert_eq!(embedded.len(), 3 + 1 + 1 + 1);
+    assert!(server.requests().iter().all(|(path, body)| path == "/api/embeddings" && body["model"] == "nomic-embed-text"));
+}
+
+#[test]
+fn writes_after_the_first_search_are_embedded_right_away() {
+    let server = stand_in();
+    let (dir, db) = new_network(&server);
+    let (user, carol) = {
+        let conn = rusqlite::Connection::open(&db).unwrap();
+        schema::initialize(&conn).unwrap();
+        let user = network_repo::find_first_user(&conn).unwrap().unwrap();
+        let carol = person_ops::add_person(&conn, user.id, "Carol", None, None, None, None, None).unwrap();
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+        interaction_ops::log_in_person(&conn, user.id, carol.id, "Park", vec!["trip planning".into()], None, date)
+            .unwrap();
+        (user, carol)
+    };
+
+    // Before the first search there is no index to keep up to date
+    run_shell(&db, &server, "edit-location Carol\nLisbon\nexit\n");
+    assert!(server.requests().is_empty());
+
+    run_shell(&db, &server, "ask-search any trips coming up\nexit\n");
+    assert_eq!(server.requests().len(), 2);
+
+    // New notes and a new interaction are embedded when they are written
+    run_shell(&db, &server, "edit-notes Carol\nLoves Lisbon and coffee\nexit\n");
+    let logged = prm(&db, &server)
+        .args(["log", "Carol", "--topics", "travel to Lisbon", "--location", "Park"])
+        .status()
+        .unwrap();
+    assert!(logged.success());
+
+    let prompts: Vec<String> =
+        server.requests().iter().map(|(_, body)| body["prompt"].as_str().unwrap().to_string()).collect();
+    assert_eq!(prompts[2..], ["Loves Lisbon and coffee", "Talked about: travel to Lisbon."]);
+    let conn = rusqlite::Connection::open(&db).unwrap();
+    let stored = embedding_repo::find_by_owner(&conn, user.id).unwrap();
+    assert_eq!(stored.len(), 3);
+    assert!(stored.iter().any(|e| e.source_type == embedding_repo::SourceType::PersonNotes && e.source_id == carol.id.value));
+
+    // With the model server down, writes still succeed without waiting on it
+    let down = prm(&db, &server)
+        .env("PRM_LLM_URL", "http://127.0.0.1:9")
+        .args(["log", "Carol", "--topics", "chess", "--location", "Park"])
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+    assert!(down.status.success());
+    assert!(down.stderr.is_empty(), "{}", String::from_utf8_lossy(&down.stderr));
+    assert_eq!(embedding_repo::find_by_owner(&conn, user.id).unwrap().len(), 3);
+
+    drop(conn);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+// ==========================================================================
+// PERSON SUMMARIES
+// ==========================================================================
+
+#[test]
+fn summarize_cites_interactions_and_is_cached_until_something_changes() {
+    let server = stand_in();
+    let mut config = LlmConfig::new(BackendKind::Ollama);
+    config.url = server.url().to_string();
+    let backend = config.backend();
+
+    let conn = schema::test_connection();
+    let user = prm::model::User::create("Petros".into(), "petros@example.com".into());
+    network_repo::insert_user(&conn, &user).unwrap();
+    let alice =
+        person_ops::add_person(&conn, user.id, "Alice", None, Some("RustConf"), None, None, Some("Berlin")).unwrap();
+    let friend = label_ops::add_label(&conn, user.id, "friend").unwrap();
+    relationship_ops::set_labels(&conn, user.id, alice.id, vec![friend.id]).unwrap();
+    let date = |m, d| chrono::NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+    interaction_ops::log_remote(
+        &conn,
+        user.id,
+        alice.id,
+        prm::model::InteractionMedium::PhoneCall,
+        "Home",
+        None,
+        vec!["job search".into()],
+        Some("Interviewing at Acme"),
+        date(3, 2),
+    )
+    .unwrap();
+    interaction_ops::log_in_person(&conn, user.id, alice.id, "Cafe", vec!["move to Berlin".into()], None, date(5, 10))
+        .unwrap();
+
+    let facts = person_summary::facts(&conn, user.id, &alice).unwrap();
+    assert!(facts.text.contains("Labels: friend\n"), "{}", facts.text);
+    assert!(facts.text.contains("- 2024-03-02 (Phone Call): job search. Note: Interviewing at Acme\n"), "{}", facts.text);
+
+    // The first reply cites a date with no interaction and is sent back
+    let summary = person_summary::summarize(&conn, user.id, &*backend, &alice, false).unwrap();
+    assert!(!summary.cached);
+    assert_eq!(summary.briefing.open_threads[0].dates, vec![date(3, 2)]);
+    assert!(summary.briefing.points.iter().any(|p| p.dates.is_empty()));
+    assert_eq!(server.requests().len(), 2);
+    assert!(server.requests()[1].1["messages"][3]["content"].as_str().unwrap().contains("2024-03-09"));
+
+    // Nothing new: the stored summary is shown without asking
+    let again = person_summary::summarize(&conn, user.id, &*backend, &alice, false).unwrap();
+    assert!(again.cached);
+    assert_eq!(again.briefing, summary.briefing);
+    assert_eq!(server.requests().len(), 2);
+
+    // ...and can be read without a model server
+    let mut offline = config.clone();
+    offline.url = "http://127.0.0.1:9".into();
+    let stored = person_summary::stored(&conn, user.id, &offline, &alice).unwrap().unwrap();
+    assert_eq!(stored.briefing, summary.briefing);
+
+    // A new interaction makes it stale; --refresh asks regardless
+    interaction_ops::log_in_person(&conn, user.id, alice.id, "Cafe", vec!["new job at Acme".into()], None, date(6, 1))
+        .unwrap();
+    let updated = person_summary::summarize(&conn, user.id, &*backend, &alice, false).unwrap();
+    assert!(!updated.cached);
+    assert_eq!(updated.briefing.open_threads[0].dates, vec![date(6, 1)]);
+    assert_eq!(server.requests().len(), 3);
+    assert!(!person_summary::summarize(&conn, user.id, &*backend, &alice, true).unwrap().cached);
+    assert_eq!(server.requests().len(), 4);
+}
+
+// ==========================================================================
+// EVALUATION
+// ==========================================================================
+
+#[test]
+fn eval_scores_recorded_replies() {
+    let server = stand_in();
+    let mut config = LlmConfig::new(BackendKind::Ollama);
+    config.url = server.url().to_string();
+
+    let case = |input: &str, expected: serde_json::Value| EvalCase {
+        input: input.to_string(),
+        known_names: vec!["Alice".to_string()],
+        today: chrono::NaiveDate::from_ymd_opt(2024, 6, 15),
+        expected: serde_json::from_value(expected).unwrap(),
+    };
+    let cases = vec![
+        case(
+            "Video call with Alice and Bob from home yesterday about the trip",
+            serde_json::json!({ "person_names": ["Bob", "alice"], "medium": "VideoCall", "location": "home",
+                                "their_location": null, "topics": ["trip"], "note": null, "date": "2024-06-14" }),
+        ),
+        case(
+            "Lunch with Alice at Tartine",
+            serde_json::json!({ "person_names": ["Alice"], "medium": "InPerson", "location": "Tartine",
+                                "their_location": null, "topics": ["lunch", "her move"], "note": null, "date": null }),
+        ),
+        case(
+            "Saw someone",
+            serde_json::json!({ "person_names": ["someone"], "medium": "InPerson", "location": "",
+                                "their_location": null, "topics": ["catching up"], "note": null, "date": null }),
+        ),
+    ];
+
+    let report = eval::run(&*config.backend(), llm_service::INTERACTION_PROMPT, &cases);
+    assert_eq!(report.cases, 3);
+    assert_eq!(report.failures, 1);
+    assert_eq!(report.names_correct, 2);
+    assert_eq!(report.medium_correct, 2);
+    assert_eq!(report.latencies.len(), 3);
+    assert_eq!(report.topic_precision(), 1.0);
+    assert_eq!(report.topic_recall(), 0.5);
+
+    // The prompt is dated by the case, not by when the eval runs
+    let requests = server.requests();
+    let system = requests[0].1["messages"][0]["content"].as_str().unwrap();
+    assert!(system.contains("Today's date is 2024-06-15."), "{}", system);
+    assert!(!system.contains("{corrections}"));
+}
+
+// ==========================================================================
+// AI-LOG END TO END
+// ==========================================================================
+
+#[test]
+fn ai_log_reviews_saves_and_stores_corrections() {
+    let server = stand_in();
+    let (dir, db) = new_network(&server);
+    let user = {
+        let conn = rusqlite::Connection::open(&db).unwrap();
+        schema::initialize(&conn).unwrap();
+        let user = network_repo::find_first_user(&conn).unwrap().unwrap();
+        person_ops::add_person(&conn, user.id, "Alice", None, None, None, None, None).unwrap();
+        user
+    };
+
+    // Edit the topics before saving: the change is kept as a correction
+    let out = run_shell(
+        &db,
+        &server,
+        "ai-log Had coffee with Alice at Blue Bottle, talked about her new job\ne\n5\ncoffee, new job\ns\nexit\n",
+    );
+    assert!(out.contains("Logged interaction with Alice"), "{}", out);
+
+    // Saved as parsed, adding Bob along the way: no correction
+    let out = run_shell(
+        &db,
+        &server,
+        "ai-log Video call with Alice and Bob from home yesterday about the trip\ns\ny\nexit\n",
+    );
+    assert!(out.contains("Added Bob to your network"), "{}", out);
+    assert!(out.contains("Logged interaction with Bob"), "{}", out);
+
+    let conn = rusqlite::Connection::open(&db).unwrap();
+    let alice = person_repo::find_by_name(&conn, user.id, "Alice").unwrap().remove(0);
+    let history = interaction_repo::find_by_person(&conn, alice.id).unwrap();
+    assert_eq!(history.len(), 2);
+    let coffee = history.iter().find(|i| i.my_location == "Blue Bottle").unwrap();
+    assert_eq!(coffee.topics, vec!["coffee", "new job"]);
+    let call = history.iter().find(|i| i.medium == prm::model::InteractionMedium::VideoCall).unwrap();
+    assert_eq!(call.date, chrono::NaiveDate::from_ymd_opt(2024, 6, 14).unwrap());
+
+    let corrections = correction_repo::recent(&conn, user.id, 5).unwrap();
+    assert_eq!(corrections.len(), 1);
+    assert!(corrections[0].original_text.starts_with("Had coffee with Alice"));
+    assert!(!corrections[0].ai_output.contains("new job"));
+    assert!(corrections[0].user_output.contains("new job"));
+
+    drop(conn);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+// ==========================================================================
+// AI-ADD END TO END
+// ==========================================================================
+
+#[test]
+fn ai_add_reviews_and_saves_a_person_with_contacts_labels_and_circles() {
+    let server = stand_in();
+    let (dir, db) = new_network(&server);
+    let user = {
+        let conn = rusqlite::Connection::open(&db).unwrap();
+        schema::initialize(&conn).unwrap();
+        let user = network_repo::find_first_user(&conn).unwrap().unwrap();
+        circle_ops::create_circle(&conn, user.id, "Rust folks", None, vec![]).unwrap();
+        contact_ops::create_custom_contact_type(&conn, user.id, "GitHub").unwrap();
+        user
+    };
+    let add = "ai-add Met Priya Shah at RustConf, she's a staff eng at Fastly, lives in Portland, birthday June 3, priya@x.com";
+
+    // Add a GitHub handle to the proposed contacts, then save, giving the
+    // year the description left out
+    let out = run_shell(&db, &server, &format!("{}\ne\n7\ngithub priyashah\n\ns\n1990\nexit\n", add));
+    assert!(out.contains("June 3 (no year; you'll be asked for it when saving)"), "{}", out);
+    assert!(out.contains("The birthday June 3 has no year."), "{}", out);
+    assert!(out.contains("8. Labels:     coworker\n"), "{}", out);
+    assert!(out.contains("Added Priya Shah."), "{}", out);
+
+    let conn = rusqlite::Connection::open(&db).unwrap();
+    let priya = person_repo::find_by_name(&conn, user.id, "Priya Shah").unwrap().remove(0);
+    assert_eq!(priya.how_we_met.as_deref(), Some("RustConf"));
+    assert_eq!(priya.location.as_deref(), Some("Portland"));
+    assert_eq!(priya.birthday, chrono::NaiveDate::from_ymd_opt(1990, 6, 3));
+    assert_eq!(priya.notes.as_deref(), Some("Staff engineer at Fastly"));
+    let contacts = contact_repo::find_by_person(&conn, priya.id).unwrap();
+    let values: Vec<String> = contacts
+        .iter()
+        .map(|c| match &c.value {
+            prm::model::ContactValue::StringValue { value } => value.clone(),
+            other => panic!("unexpected contact {:?}", other),
+        })
+        .collect();
+    assert_eq!(values, vec!["priya@x.com", "priyashah"]);
+    let labels = prm::queries::relationship_queries::labels_for(&conn, priya.id).unwrap();
+    assert_eq!(labels.iter().map(|l| l.name.as_str()).collect::<Vec<_>>(), vec!["coworker"]);
+    let circles = prm::queries::circle_queries::circles_for_person(&conn, user.id, priya.id).unwrap();
+    assert_eq!(circles.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), vec!["Rust folks"]);
+    drop(conn);
+
+    // The same description again: asked before adding a second Priya Shah
+    let out = run_shell(&db, &server, &format!("{}\ns\n-\nn\nexit\n", add));
+    assert!(out.contains("You already have Priya Shah."), "{}", out);
+    let conn = rusqlite::Connection::open(&db).unwrap();
+    assert_eq!(person_repo::find_by_name(&conn, user.id, "Priya Shah").unwrap().len(), 1);
+
+    drop(conn);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+// ==========================================================================
+// AI-EDIT END TO END
+// ==========================================================================
+
+#[test]
+fn ai_edit_applies_the_reviewed_changes_and_keeps_the_amendment() {
+    let server = stand_in();
+    let (dir, db) = new_network(&server);
+    let (user, alice) = {
+        let conn = rusqlite::Connection::open(&db).unwrap();
+        schema::initialize(&conn).unwrap();
+        let user = network_repo::find_first_user(&conn).unwrap().unwrap();
+        let alice = person_ops::add_person(&conn, user.id, "Alice", None, None, None, None, Some("Lisbon")).unwrap();
+        person_ops::add_person(&conn, user.id, "Bob", None, None, None, None, None).unwrap();
+        contact_ops::add_phone(&conn, alice.id, "555-0000", None).unwrap();
+        (user, alice)
+    };
+
+    // The model misses that the old number goes; mark it for removal, then apply
+    let out = run_shell(
+        &db,
+        &server,
+        "ai-edit Alice moved to Berlin and got a new number 555-1234\ne\n8\n1\n\na\nexit\n",
+    );
+    assert!(out.contains("Proposed changes to Alice:"), "{}", out);
+    assert!(out.contains(" 5. Location:         Lisbon -> Berlin\n"), "{}", out);
+    assert!(out.contains(" 8. Remove contacts:  (none)\n"), "{}", out);
+    assert!(out.contains(" 8. Remove contacts:  phone 555-0000\n"), "{}", out);
+    assert!(out.contains("Updated Alice."), "{}", out);
+
+    let conn = rusqlite::Connection::open(&db).unwrap();
+    let updated = person_repo::find_by_id(&conn, alice.id).unwrap().unwrap();
+    assert_eq!(updated.location.as_deref(), Some("Berlin"));
+    let contacts = contact_repo::find_by_person(&conn, alice.id).unwrap();
+    assert_eq!(contacts.len(), 1);
+    assert_eq!(contacts[0].value, prm::model::ContactValue::StringValue { value: "555-1234".into() });
+
+    // The amendment is kept for ai-edit, and not offered to ai-log
+    let edits = correction_repo::all_of_kind(&conn, user.id, correction_repo::CorrectionKind::PersonEdit).unwrap();
+    assert_eq!(edits.len(), 1);
+    assert!(!edits[0].ai_output.contains("555-0000"));
+    assert!(edits[0].user_output.contains("\"removeContacts\":[\"555-0000\"]"));
+    let interactions =
+        correction_repo::all_of_kind(&conn, user.id, correction_repo::CorrectionKind::Interaction).unwrap();
+    assert!(interactions.is_empty());
+
+    drop(conn);
+    let _ = std::fs::remove_dir_all(&dir);
+}
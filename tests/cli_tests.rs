@@ -0,0 +1,123 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+use prm::cli::subcommand::{EXIT_ERROR, EXIT_NOT_FOUND, EXIT_OK, EXIT_USAGE};
+use prm::db::*;
+use prm::ops::*;
+
+/// A new database with a network for Petros and the people in `names`.
+fn network(names: &[&str]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("prm-cli-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let db = dir.join("prm.db");
+    assert_eq!(prm(&db, &["init", "Petros"], "").status.code(), Some(EXIT_OK));
+    let conn = rusqlite::Connection::open(&db).unwrap();
+    let user = network_repo::find_first_user(&conn).unwrap().unwrap();
+    for name in names {
+        person_ops::add_person(&conn, user.id, name, None, None, None, None, None).unwrap();
+    }
+    db
+}
+
+/// Runs `prm -f <db> <args>` with `stdin` as its input.
+fn prm(db: &Path, args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_prm"))
+        .arg("-f")
+        .arg(db)
+        .args(args)
+        .env("PRM_LLM_CONFIG", db.with_file_name("no-llm.json"))
+        .env("PRM_LLM_URL", "http://127.0.0.1:9")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(stdin.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn code(db: &Path, args: &[&str]) -> Option<i32> {
+    prm(db, args, "").status.code()
+}
+
+// ==========================================================================
+// EXIT CODES
+// ==========================================================================
+
+#[test]
+fn subcommands_exit_with_documented_codes() {
+    let db = network(&["Alice", "Bob"]);
+
+    assert_eq!(code(&db, &["people", "--json"]), Some(EXIT_OK));
+    assert_eq!(code(&db, &["show", "Alice"]), Some(EXIT_OK));
+    assert_eq!(code(&db, &["log", "Bob", "--topics", "chess", "--location", "Park"]), Some(EXIT_OK));
+
+    assert_eq!(code(&db, &["show"]), Some(EXIT_USAGE));
+    assert_eq!(code(&db, &["people", "sort:age"]), Some(EXIT_USAGE));
+    assert_eq!(code(&db, &["log", "Bob", "--topics", "chess", "--medium", "fax"]), Some(EXIT_USAGE));
+
+    assert_eq!(code(&db, &["show", "Zed"]), Some(EXIT_NOT_FOUND));
+    assert_eq!(code(&db, &["history", "Zed"]), Some(EXIT_NOT_FOUND));
+
+    let empty = std::env::temp_dir().join(format!("prm-cli-{}.db", uuid::Uuid::new_v4()));
+    assert_eq!(code(&empty, &["people"]), Some(EXIT_ERROR));
+    assert_eq!(code(&db, &["init", "Petros"]), Some(EXIT_ERROR));
+}
+
+#[test]
+fn ambiguous_names_fail_instead_of_prompting() {
+    let db = network(&["Alice Smith", "Alice Jones"]);
+
+    let out = prm(&db, &["show", "Alice"], "1\n");
+    assert_eq!(out.status.code(), Some(EXIT_NOT_FOUND));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("Multiple matches for 'Alice'"));
+    assert!(out.stdout.is_empty());
+}
+
+#[test]
+fn listings_fail_when_the_data_cannot_be_read() {
+    let db = network(&["Alice"]);
+    {
+        let conn = rusqlite::Connection::open(&db).unwrap();
+        conn.execute_batch(
+            "ALTER TABLE interactions RENAME COLUMN date TO day;
+             ALTER TABLE relationships RENAME COLUMN reminder_days TO every;
+             ALTER TABLE circles RENAME COLUMN archived TO hidden;
+             ALTER TABLE relationship_labels RENAME COLUMN archived TO hidden;",
+        )
+        .unwrap();
+    }
+
+    for command in ["people", "remind", "stats", "circles", "labels"] {
+        let out = prm(&db, &[command, "--json"], "");
+        assert_eq!(out.status.code(), Some(EXIT_ERROR), "{}", command);
+        assert!(out.stdout.is_empty(), "{}", command);
+        assert!(String::from_utf8_lossy(&out.stderr).starts_with("Error: "), "{}", command);
+    }
+}
+
+// ==========================================================================
+// REVIEW
+// ==========================================================================
+
+#[test]
+fn review_keeps_the_note_when_input_ends_at_the_note_prompt() {
+    let db = network(&["Alice"]);
+    let alice = {
+        let conn = rusqlite::Connection::open(&db).unwrap();
+        let user = network_repo::find_first_user(&conn).unwrap().unwrap();
+        let alice = person_repo::find_active_by_owner(&conn, user.id).unwrap().into_iter().find(|p| p.name == "Alice").unwrap();
+        let today = chrono::Local::now().date_naive();
+        interaction_ops::log_in_person(&conn, user.id, alice.id, "Park", vec!["walk".into()], Some("Bring the map"), today)
+            .unwrap();
+        alice
+    };
+
+    let out = prm(&db, &[], "review\nn\n");
+    assert!(String::from_utf8_lossy(&out.stdout).contains("Review paused"));
+
+    let conn = rusqlite::Connection::open(&db).unwrap();
+    let interactions = prm::queries::interaction_queries::interactions_with(&conn, alice.id).unwrap();
+    assert_eq!(interactions[0].note.as_deref(), Some("Bring the map"));
+}
@@ -383,3 +383,142 @@ fn find_first_user() {
     let found = network_repo::find_first_user(&conn).unwrap().unwrap();
     assert_eq!(found.id, user.id);
 }
+
+// ==========================================================================
+// REVIEW REPO TESTS
+// ==========================================================================
+
+#[test]
+fn review_progress_roundtrip() {
+    let (conn, user, _) = setup();
+    assert!(review_repo::find_by_owner(&conn, user.id).unwrap().is_none());
+
+    let mut progress = review_repo::ReviewProgress {
+        started_on: NaiveDate::from_ymd_opt(2024, 6, 9).unwrap(),
+        section: 1,
+        done: vec!["a".into(), "b".into()],
+    };
+    review_repo::upsert(&conn, user.id, &progress).unwrap();
+    assert_eq!(review_repo::find_by_owner(&conn, user.id).unwrap(), Some(progress.clone()));
+
+    progress.section = 2;
+    progress.done.clear();
+    review_repo::upsert(&conn, user.id, &progress).unwrap();
+    assert_eq!(review_repo::find_by_owner(&conn, user.id).unwrap(), Some(progress));
+
+    review_repo::delete(&conn, user.id).unwrap();
+    assert!(review_repo::find_by_owner(&conn, user.id).unwrap().is_none());
+}
+
+// ==========================================================================
+// CORRECTION REPO TESTS
+// ==========================================================================
+
+#[test]
+fn corrections_can_be_pinned_found_by_prefix_and_deleted() {
+    let (conn, user, _) = setup();
+    correction_repo::insert(&conn, user.id, "Coffee with Ann", r#"{"a":1}"#, r#"{"a":2}"#).unwrap();
+    correction_repo::insert(&conn, user.id, "Call with Bo", r#"{"a":1}"#, r#"{"a":3}"#).unwrap();
+
+    let all = correction_repo::all(&conn, user.id).unwrap();
+    assert_eq!(all.len(), 2);
+    assert!(all.iter().all(|r| !r.pinned));
+    let ann = all.iter().find(|r| r.original_text == "Coffee with Ann").unwrap();
+
+    let prefix = &ann.id.to_string()[..8];
+    let found = correction_repo::find_by_id_prefix(&conn, user.id, prefix).unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].id, ann.id);
+
+    correction_repo::set_pinned(&conn, ann.id, true).unwrap();
+    correction_repo::set_pinned(&conn, ann.id, true).unwrap();
+    assert!(correction_repo::find_by_id(&conn, ann.id).unwrap().unwrap().pinned);
+    correction_repo::set_pinned(&conn, ann.id, false).unwrap();
+    assert!(!correction_repo::find_by_id(&conn, ann.id).unwrap().unwrap().pinned);
+
+    // Deleting a pinned correction removes its pin too
+    correction_repo::set_pinned(&conn, ann.id, true).unwrap();
+    correction_repo::delete(&conn, ann.id).unwrap();
+    assert!(correction_repo::find_by_id(&conn, ann.id).unwrap().is_none());
+    assert!(correction_repo::delete(&conn, ann.id).is_err());
+    assert!(correction_repo::set_pinned(&conn, ann.id, true).is_err());
+    assert_eq!(correction_repo::all(&conn, user.id).unwrap().len(), 1);
+}
+
+#[test]
+fn corrections_are_kept_apart_by_kind() {
+    let (conn, user, _) = setup();
+    correction_repo::insert(&conn, user.id, "Coffee with Ann", r#"{"a":1}"#, r#"{"a":2}"#).unwrap();
+    correction_repo::insert_kind(&conn, user.id, correction_repo::CorrectionKind::PersonEdit, "Ann moved", r#"{"b":1}"#, r#"{"b":2}"#)
+        .unwrap();
+
+    let all = correction_repo::all(&conn, user.id).unwrap();
+    assert_eq!(all.len(), 2);
+    let edits = correction_repo::all_of_kind(&conn, user.id, correction_repo::CorrectionKind::PersonEdit).unwrap();
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].original_text, "Ann moved");
+    assert_eq!(edits[0].kind, correction_repo::CorrectionKind::PersonEdit);
+    let parses = correction_repo::all_of_kind(&conn, user.id, correction_repo::CorrectionKind::Interaction).unwrap();
+    assert_eq!(parses.len(), 1);
+    assert_eq!(parses[0].kind, correction_repo::CorrectionKind::Interaction);
+
+    // The same text and outputs under the other kind are a different correction
+    let mut copy = correction_repo::find_by_id(&conn, parses[0].id).unwrap().unwrap();
+    assert!(!correction_repo::insert_record(&conn, user.id, &copy).unwrap());
+    copy.kind = correction_repo::CorrectionKind::PersonEdit;
+    assert!(correction_repo::insert_record(&conn, user.id, &copy).unwrap());
+    assert!(!correction_repo::insert_record(&conn, user.id, &copy).unwrap());
+
+    correction_repo::delete(&conn, edits[0].id).unwrap();
+    assert_eq!(correction_repo::all_of_kind(&conn, user.id, correction_repo::CorrectionKind::PersonEdit).unwrap().len(), 1);
+}
+
+// ==========================================================================
+// EMBEDDING REPO TESTS
+// ==========================================================================
+
+#[test]
+fn embeddings_roundtrip_as_blobs() {
+    let (conn, user, person) = setup();
+    let mut embedding = embedding_repo::StoredEmbedding {
+        source_type: embedding_repo::SourceType::PersonNotes,
+        source_id: person.id.value,
+        text_hash: "a1".into(),
+        vector: vec![0.25, -1.5, 3.0e-7],
+    };
+    embedding_repo::upsert(&conn, user.id, &embedding).unwrap();
+    assert_eq!(embedding_repo::find_by_owner(&conn, user.id).unwrap(), vec![embedding.clone()]);
+
+    embedding.text_hash = "b2".into();
+    embedding.vector = vec![1.0];
+    embedding_repo::upsert(&conn, user.id, &embedding).unwrap();
+    assert_eq!(embedding_repo::find_by_owner(&conn, user.id).unwrap(), vec![embedding.clone()]);
+
+    embedding_repo::delete(&conn, embedding.source_type, embedding.source_id).unwrap();
+    assert!(embedding_repo::find_by_owner(&conn, user.id).unwrap().is_empty());
+}
+
+// ==========================================================================
+// SUMMARY REPO TESTS
+// ==========================================================================
+
+#[test]
+fn person_summary_is_replaced_on_upsert() {
+    let (conn, user, _) = setup();
+    let alice = Person::create("Alice".into());
+    person_repo::insert(&conn, user.id, &alice).unwrap();
+    assert_eq!(summary_repo::find_by_person(&conn, alice.id).unwrap(), None);
+
+    let mut summary = summary_repo::StoredSummary {
+        source_hash: "a1".into(),
+        summary: r#"{"summary": "An old friend."}"#.into(),
+        created_at: "2024-06-15 18:02:11".into(),
+    };
+    summary_repo::upsert(&conn, alice.id, &summary).unwrap();
+    assert_eq!(summary_repo::find_by_person(&conn, alice.id).unwrap(), Some(summary.clone()));
+
+    summary.source_hash = "b2".into();
+    summary.created_at = "2024-06-16 09:00:00".into();
+    summary_repo::upsert(&conn, alice.id, &summary).unwrap();
+    assert_eq!(summary_repo::find_by_person(&conn, alice.id).unwrap(), Some(summary));
+}
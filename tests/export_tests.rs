@@ -0,0 +1,88 @@
+use chrono::NaiveDate;
+use prm::db::*;
+use prm::export::dossier::{self, DossierFormat};
+use prm::model::*;
+use prm::ops::*;
+
+fn setup() -> (rusqlite::Connection, User, Person) {
+    let conn = schema::test_connection();
+    let user = User::create("Petros".into(), "petros@example.com".into());
+    network_repo::insert_user(&conn, &user).unwrap();
+
+    let self_person = Person::create_self("Petros".into());
+    person_repo::insert(&conn, user.id, &self_person).unwrap();
+    network_repo::set_network_metadata(&conn, user.id, self_person.id).unwrap();
+
+    for label in RelationshipLabel::defaults() {
+        relationship_repo::insert_label(&conn, user.id, &label).unwrap();
+    }
+
+    (conn, user, self_person)
+}
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+// ==========================================================================
+// DOSSIER
+// ==========================================================================
+
+#[test]
+fn dossier_groups_interactions_by_year_with_topic_counts() {
+    let (conn, user, _) = setup();
+    let alice = person_ops::add_person(&conn, user.id, "Alice Smith", None, None, None, None, None).unwrap();
+
+    interaction_ops::log_in_person(&conn, user.id, alice.id, "Cafe", vec!["work".into(), "travel".into()], None, date(2023, 3, 1)).unwrap();
+    interaction_ops::log_in_person(&conn, user.id, alice.id, "Park", vec!["Work".into()], None, date(2024, 2, 1)).unwrap();
+    interaction_ops::log_in_person(&conn, user.id, alice.id, "Bar", vec!["work".into(), "music".into()], None, date(2024, 5, 1)).unwrap();
+
+    let d = dossier::build(&conn, user.id, alice.id, date(2024, 5, 11)).unwrap();
+    assert_eq!(d.total_interactions, 3);
+    assert_eq!(d.days_since_last_contact, Some(10));
+    assert_eq!(d.years.len(), 2);
+    assert_eq!(d.years[0].year, 2024);
+    assert_eq!(d.years[0].interactions.len(), 2);
+    assert_eq!(d.years[0].topic_counts[0], ("work".to_string(), 2));
+    assert_eq!(d.years[1].year, 2023);
+}
+
+#[test]
+fn dossier_includes_labels_circles_contacts_and_reminder() {
+    let (conn, user, _) = setup();
+    let alice = person_ops::add_person(&conn, user.id, "Alice", None, None, None, None, None).unwrap();
+    contact_ops::add_email(&conn, alice.id, "alice@example.com", Some("work")).unwrap();
+    let friend = relationship_repo::find_label_by_name(&conn, user.id, "friend").unwrap().unwrap();
+    relationship_ops::set_relationship(&conn, user.id, alice.id, vec![friend.id], Some(30)).unwrap();
+    circle_ops::create_circle(&conn, user.id, "Book Club", None, vec![alice.id]).unwrap();
+
+    let d = dossier::build(&conn, user.id, alice.id, date(2024, 5, 1)).unwrap();
+    assert_eq!(d.labels, vec!["friend".to_string()]);
+    assert_eq!(d.circles, vec!["Book Club".to_string()]);
+    assert_eq!(d.reminder_days, Some(30));
+    assert_eq!(d.contacts.len(), 1);
+    assert_eq!(d.contacts[0].kind, "Email");
+
+    let md = dossier::render(&d, DossierFormat::Markdown);
+    assert!(md.starts_with("# Alice\n"));
+    assert!(md.contains("alice@example.com (work)"));
+    assert!(md.contains("every 30 days (never contacted)"));
+}
+
+#[test]
+fn dossier_html_escapes_content() {
+    let (conn, user, _) = setup();
+    let alice = person_ops::add_person(&conn, user.id, "Alice <A&B>", None, None, None, None, None).unwrap();
+
+    let d = dossier::build(&conn, user.id, alice.id, date(2024, 5, 1)).unwrap();
+    let html = dossier::render(&d, DossierFormat::Html);
+    assert!(html.contains("<h1>Alice &lt;A&amp;B&gt;</h1>"));
+    assert_eq!(dossier::file_stem(&d.person), "alice-a-b");
+}
+
+#[test]
+fn dossier_format_parse() {
+    assert_eq!(DossierFormat::parse("md"), Some(DossierFormat::Markdown));
+    assert_eq!(DossierFormat::parse("HTML"), Some(DossierFormat::Html));
+    assert_eq!(DossierFormat::parse("pdf"), None);
+}
@@ -0,0 +1,274 @@
+use prm::db::*;
+use prm::import::{android, ics, mbox, whatsapp};
+use prm::model::*;
+use prm::ops::*;
+use prm::queries::*;
+
+fn setup() -> (rusqlite::Connection, User, Person) {
+    let conn = schema::test_connection();
+    let user = User::create("Petros".into(), "petros@example.com".into());
+    network_repo::insert_user(&conn, &user).unwrap();
+
+    let self_person = Person::create_self("Petros".into());
+    person_repo::insert(&conn, user.id, &self_person).unwrap();
+    network_repo::set_network_metadata(&conn, user.id, self_person.id).unwrap();
+
+    for label in RelationshipLabel::defaults() {
+        relationship_repo::insert_label(&conn, user.id, &label).unwrap();
+    }
+
+    (conn, user, self_person)
+}
+
+// ==========================================================================
+// WHATSAPP
+// ==========================================================================
+
+const CHAT: &str = "\
+01/02/2024, 10:00 - Messages and calls are end-to-end encrypted.
+01/02/2024, 10:00 - Alice: are we still on for friday?
+01/02/2024, 10:05 - Petros: yes!
+14/02/2024, 20:00 - Alice: happy valentine's
+";
+
+#[test]
+fn whatsapp_import_logs_one_text_per_day() {
+    let (conn, user, _) = setup();
+    let alice = person_ops::add_person(&conn, user.id, "Alice", None, None, None, None, None).unwrap();
+
+    let days = whatsapp::group_by_day(&whatsapp::parse_chat(CHAT, None));
+    let (days, skipped) = whatsapp::new_days(&conn, alice.id, days).unwrap();
+    assert_eq!(skipped, 0);
+
+    let created = whatsapp::import_days(
+        &conn, user.id, alice.id, &days, "Athens", None, true, |_| vec!["chat".into()],
+    )
+    .unwrap();
+    assert_eq!(created, 2);
+
+    let interactions = interaction_queries::interactions_with(&conn, alice.id).unwrap();
+    assert_eq!(interactions.len(), 2);
+    assert!(interactions.iter().all(|i| i.medium == InteractionMedium::Text));
+    let first = interactions
+        .iter()
+        .find(|i| i.date == chrono::NaiveDate::from_ymd_opt(2024, 2, 1).unwrap())
+        .unwrap();
+    assert_eq!(first.note.as_deref(), Some("WhatsApp: 2 messages (Alice 1, Petros 1)"));
+}
+
+#[test]
+fn whatsapp_reimport_skips_logged_days() {
+    let (conn, user, _) = setup();
+    let alice = person_ops::add_person(&conn, user.id, "Alice", None, None, None, None, None).unwrap();
+
+    let days = whatsapp::group_by_day(&whatsapp::parse_chat(CHAT, None));
+    whatsapp::import_days(&conn, user.id, alice.id, &days, "Athens", None, false, |_| vec!["chat".into()])
+        .unwrap();
+
+    let again = whatsapp::group_by_day(&whatsapp::parse_chat(CHAT, None));
+    let (fresh, skipped) = whatsapp::new_days(&conn, alice.id, again).unwrap();
+    assert!(fresh.is_empty());
+    assert_eq!(skipped, 2);
+}
+
+// ==========================================================================
+// ANDROID BACKUP
+// ==========================================================================
+
+// 2024-06-01 12:00 UTC
+const CALLS_XML: &str = r#"<calls count="3">
+  <call number="+1 555-123-4567" duration="725" date="1717243200000" type="2" contact_name="Alice" />
+  <call number="+15551234567" duration="0" date="1717243260000" type="3" contact_name="Alice" />
+  <call number="+15559990000" duration="30" date="1717243320000" type="1" contact_name="Stranger" />
+</calls>"#;
+
+const SMS_XML: &str = r#"<smses count="3">
+  <sms address="5551234567" date="1717243200000" type="1" body="hi" />
+  <sms address="5551234567" date="1717243260000" type="2" body="hey" />
+  <sms address="+15559990000" date="1717243320000" type="1" body="who dis" />
+</smses>"#;
+
+fn android_backup() -> android::Backup {
+    let mut backup = android::parse_backup(CALLS_XML).unwrap();
+    backup.extend(android::parse_backup(SMS_XML).unwrap());
+    backup
+}
+
+#[test]
+fn android_import_matches_normalized_numbers() {
+    let (conn, user, _) = setup();
+    let alice = person_ops::add_person(&conn, user.id, "Alice", None, None, None, None, None).unwrap();
+    contact_ops::add_phone(&conn, alice.id, "(555) 123-4567", None).unwrap();
+
+    let report = android::import(&conn, user.id, &android_backup(), "Boston", true).unwrap();
+    assert_eq!(report.calls, 1);
+    assert_eq!(report.text_days, 1);
+
+    let interactions = interaction_queries::interactions_with(&conn, alice.id).unwrap();
+    let call = interactions.iter().find(|i| i.medium == InteractionMedium::PhoneCall).unwrap();
+    assert_eq!(call.note.as_deref(), Some("Outgoing call, 12m 5s"));
+    let text = interactions.iter().find(|i| i.medium == InteractionMedium::Text).unwrap();
+    assert_eq!(text.note.as_deref(), Some("SMS: 1 sent, 1 received"));
+
+    assert_eq!(report.unmatched.len(), 1);
+    assert_eq!(report.unmatched[0].contact_name.as_deref(), Some("Stranger"));
+    assert_eq!(report.unmatched[0].calls, 1);
+    assert_eq!(report.unmatched[0].messages, 1);
+}
+
+#[test]
+fn android_reimport_skips_logged() {
+    let (conn, user, _) = setup();
+    let alice = person_ops::add_person(&conn, user.id, "Alice", None, None, None, None, None).unwrap();
+    contact_ops::add_phone(&conn, alice.id, "+1 555 123 4567", None).unwrap();
+
+    android::import(&conn, user.id, &android_backup(), "Boston", false).unwrap();
+    let again = android::import(&conn, user.id, &android_backup(), "Boston", false).unwrap();
+    assert_eq!(again.calls, 0);
+    assert_eq!(again.text_days, 0);
+    assert_eq!(again.skipped, 2);
+    assert_eq!(interaction_queries::interactions_with(&conn, alice.id).unwrap().len(), 2);
+}
+
+#[test]
+fn android_import_keeps_same_day_calls_of_the_same_length() {
+    let (conn, user, _) = setup();
+    let alice = person_ops::add_person(&conn, user.id, "Alice", None, None, None, None, None).unwrap();
+    contact_ops::add_phone(&conn, alice.id, "+1 555 123 4567", None).unwrap();
+
+    // Two one-minute calls an hour apart, the first exported twice
+    let xml = r#"<calls count="3">
+  <call number="+15551234567" duration="60" date="1717243200000" type="1" />
+  <call number="+15551234567" duration="60" date="1717243200000" type="1" />
+  <call number="+15551234567" duration="60" date="1717246800000" type="1" />
+</calls>"#;
+    let report = android::import(&conn, user.id, &android::parse_backup(xml).unwrap(), "Boston", false).unwrap();
+    assert_eq!(report.calls, 2);
+    assert_eq!(report.skipped, 1);
+
+    let again = android::import(&conn, user.id, &android::parse_backup(xml).unwrap(), "Boston", false).unwrap();
+    assert_eq!(again.calls, 0);
+    assert_eq!(again.skipped, 3);
+    assert_eq!(interaction_queries::interactions_with(&conn, alice.id).unwrap().len(), 2);
+}
+
+// ==========================================================================
+// ICS
+// ==========================================================================
+
+const CALENDAR: &str = "BEGIN:VCALENDAR
+BEGIN:VEVENT
+DTSTART:20240601T190000
+SUMMARY:Dinner
+LOCATION:Taverna
+ATTENDEE;CN=Someone:mailto:ALICE@example.com
+END:VEVENT
+BEGIN:VEVENT
+DTSTART:20240605T090000
+SUMMARY:Coffee with Bob Jones
+END:VEVENT
+BEGIN:VEVENT
+DTSTART:20240610T090000
+SUMMARY:Dentist
+END:VEVENT
+BEGIN:VEVENT
+DTSTART:20240801T090000
+SUMMARY:Dinner again
+ATTENDEE:mailto:alice@example.com
+END:VEVENT
+END:VCALENDAR
+";
+
+#[test]
+fn ics_proposes_matched_events_in_range() {
+    let (conn, user, _) = setup();
+    let alice = person_ops::add_person(&conn, user.id, "Alice", None, None, None, None, None).unwrap();
+    contact_ops::add_email(&conn, alice.id, "alice@example.com", None).unwrap();
+    person_ops::add_person(&conn, user.id, "Bob Jones", None, None, None, None, None).unwrap();
+
+    let from = chrono::NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+    let to = chrono::NaiveDate::from_ymd_opt(2024, 6, 30).unwrap();
+    let proposals = ics::propose(&conn, user.id, ics::parse_calendar(CALENDAR), from, to).unwrap();
+
+    assert_eq!(proposals.len(), 2);
+    assert_eq!(proposals[0].people[0].name, "Alice");
+    assert_eq!(proposals[0].event.physical_location(), Some("Taverna"));
+    assert_eq!(proposals[1].people[0].name, "Bob Jones");
+}
+
+#[test]
+fn ics_skips_days_already_logged() {
+    let (conn, user, _) = setup();
+    let alice = person_ops::add_person(&conn, user.id, "Alice", None, None, None, None, None).unwrap();
+    contact_ops::add_email(&conn, alice.id, "alice@example.com", None).unwrap();
+
+    let day = chrono::NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+    interaction_ops::log_in_person(&conn, user.id, alice.id, "Taverna", vec!["dinner".into()], None, day).unwrap();
+
+    let to = chrono::NaiveDate::from_ymd_opt(2024, 6, 30).unwrap();
+    let proposals = ics::propose(&conn, user.id, ics::parse_calendar(CALENDAR), day, to).unwrap();
+    assert!(proposals.is_empty());
+}
+
+// ==========================================================================
+// MBOX
+// ==========================================================================
+
+const MBOX: &str = "From alice@example.com Sat Jun  1 10:00:00 2024
+From: Alice <alice@example.com>
+To: Petros <petros@example.com>
+Subject: Dinner on Friday?
+Message-ID: <root@example.com>
+Date: Sat, 1 Jun 2024 12:00:00 +0000
+
+Are you free?
+
+From petros@example.com Sat Jun  1 11:00:00 2024
+From: petros@example.com
+To: alice@example.com, Unknown <stranger@example.com>
+Subject: Re: Dinner on Friday?
+References: <root@example.com>
+Date: Sat, 1 Jun 2024 13:00:00 +0000
+
+Yes!
+
+From news@example.com Sat Jun  1 11:00:00 2024
+From: news@example.com
+To: petros@example.com
+Subject: Weekly digest
+Precedence: bulk
+Date: Sat, 1 Jun 2024 14:00:00 +0000
+
+News.
+
+From petros@example.com Sat Jun  1 11:00:00 2024
+From: petros@example.com
+To: petros@example.com
+Subject: reminder
+Date: Sat, 1 Jun 2024 14:00:00 +0000
+
+Self.
+";
+
+#[test]
+fn mbox_logs_one_email_per_thread_day() {
+    let (conn, user, _) = setup();
+    let alice = person_ops::add_person(&conn, user.id, "Alice", None, None, None, None, None).unwrap();
+    contact_ops::add_email(&conn, alice.id, "Alice@Example.com", None).unwrap();
+
+    let filter = mbox::MailFilter::new(vec![user.email.clone()]);
+    let report = mbox::import(&conn, user.id, &mbox::parse_mbox(MBOX), &filter, "Athens", true).unwrap();
+    assert_eq!(report.created, 1);
+    assert_eq!(report.filtered, 2);
+    assert_eq!(report.unmatched, vec![("stranger@example.com".to_string(), 1)]);
+
+    let interactions = interaction_queries::interactions_with(&conn, alice.id).unwrap();
+    assert_eq!(interactions.len(), 1);
+    assert_eq!(interactions[0].medium, InteractionMedium::Email);
+    assert_eq!(interactions[0].topics, vec!["Dinner on Friday?".to_string()]);
+    assert_eq!(interactions[0].note.as_deref(), Some("Email: 1 sent, 1 received"));
+
+    let again = mbox::import(&conn, user.id, &mbox::parse_mbox(MBOX), &filter, "Athens", true).unwrap();
+    assert_eq!(again.created, 0);
+    assert_eq!(again.skipped, 1);
+}
@@ -120,7 +120,8 @@ fn interaction_medium_all_contains_all_mediums() {
     assert!(all.contains(&InteractionMedium::PhoneCall));
     assert!(all.contains(&InteractionMedium::VideoCall));
     assert!(all.contains(&InteractionMedium::SocialMedia));
-    assert_eq!(all.len(), 5);
+    assert!(all.contains(&InteractionMedium::Email));
+    assert_eq!(all.len(), 6);
 }
 
 #[test]
@@ -130,6 +131,7 @@ fn interaction_medium_display_names() {
     assert_eq!(InteractionMedium::VideoCall.display_name(), "Video Call");
     assert_eq!(InteractionMedium::SocialMedia.display_name(), "Social Media");
     assert_eq!(InteractionMedium::Text.display_name(), "Text");
+    assert_eq!(InteractionMedium::Email.display_name(), "Email");
 }
 
 #[test]
@@ -141,6 +143,18 @@ fn interaction_medium_db_roundtrip() {
     }
 }
 
+#[test]
+fn interaction_medium_parses_user_input() {
+    assert_eq!(InteractionMedium::parse("text"), Some(InteractionMedium::Text));
+    assert_eq!(InteractionMedium::parse("In-Person"), Some(InteractionMedium::InPerson));
+    assert_eq!(InteractionMedium::parse("video call"), Some(InteractionMedium::VideoCall));
+    assert_eq!(InteractionMedium::parse("EMAIL"), Some(InteractionMedium::Email));
+    assert_eq!(InteractionMedium::parse("pigeon"), None);
+    for medium in InteractionMedium::ALL {
+        assert_eq!(InteractionMedium::parse(medium.to_db_str()), Some(*medium));
+    }
+}
+
 // ==========================================================================
 // INTERACTION TESTS
 // ==========================================================================
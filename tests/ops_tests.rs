@@ -251,6 +251,27 @@ fn log_interaction_rejects_empty_topics() {
     assert!(result.is_err());
 }
 
+#[test]
+fn update_interaction_details_replaces_topics_and_note() {
+    let (conn, user, _) = setup();
+    let person = person_ops::add_person(&conn, user.id, "Alice", None, None, None, None, None).unwrap();
+    let date = NaiveDate::from_ymd_opt(2024, 6, 15).unwrap();
+    let logged = interaction_ops::log_in_person(&conn, user.id, person.id, "Cafe", vec!["coffee".into()], None, date)
+        .unwrap();
+
+    interaction_ops::update_details(&conn, logged.id, vec!["coffee".into(), " travel ".into()], Some(" Going to Crete "))
+        .unwrap();
+    let saved = &interaction_repo::find_by_person(&conn, person.id).unwrap()[0];
+    assert_eq!(saved.topics, vec!["coffee", "travel"]);
+    assert_eq!(saved.note.as_deref(), Some("Going to Crete"));
+
+    interaction_ops::update_details(&conn, logged.id, vec!["coffee".into()], Some("  ")).unwrap();
+    assert_eq!(interaction_repo::find_by_person(&conn, person.id).unwrap()[0].note, None);
+
+    assert!(interaction_ops::update_details(&conn, logged.id, vec![], None).is_err());
+    assert!(interaction_ops::update_details(&conn, Id::generate(), vec!["x".into()], None).is_err());
+}
+
 // ==========================================================================
 // CIRCLE OPS TESTS
 // ==========================================================================
@@ -335,3 +356,71 @@ fn update_label_name() {
     let updated = label_ops::update_label(&conn, user.id, label.id, Some("farm friend")).unwrap();
     assert_eq!(updated.name, "farm friend");
 }
+
+// ==========================================================================
+// CORRECTION OPS TESTS
+// ==========================================================================
+
+#[test]
+fn corrections_export_and_import_between_installs() {
+    let (conn, user, _) = setup();
+    let ai = r#"{"person_names":["Ann"],"medium":"InPerson","location":"","their_location":null,"topics":["coffee"],"note":null,"date":null}"#;
+    let fixed = r#"{"person_names":["Ann"],"medium":"PhoneCall","location":"","their_location":null,"topics":["coffee","job"],"note":null,"date":null}"#;
+    correction_repo::insert(&conn, user.id, "Called Ann about coffee", ai, fixed).unwrap();
+    let id = correction_repo::all(&conn, user.id).unwrap()[0].id;
+    correction_repo::set_pinned(&conn, id, true).unwrap();
+
+    let record = correction_repo::find_by_id(&conn, id).unwrap().unwrap();
+    let changed: Vec<String> = correction_ops::changed_fields(&record).into_iter().map(|(f, _, _)| f).collect();
+    assert_eq!(changed, vec!["medium", "topics"]);
+
+    // Parses are exported as objects, so they can be edited by hand
+    let json = correction_ops::export(&conn, user.id).unwrap();
+    let exported: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(exported[0]["user_output"]["medium"], "PhoneCall");
+    assert_eq!(exported[0]["pinned"], true);
+
+    // Re-importing into the same install finds the original
+    let summary = correction_ops::import(&conn, user.id, &json).unwrap();
+    assert_eq!((summary.added, summary.skipped), (0, 1));
+
+    let (other, other_user, _) = setup();
+    let summary = correction_ops::import(&other, other_user.id, &json).unwrap();
+    assert_eq!((summary.added, summary.skipped), (1, 0));
+    let imported = correction_repo::all(&other, other_user.id).unwrap();
+    assert_eq!(imported[0].original_text, "Called Ann about coffee");
+    let as_json = |s: &str| serde_json::from_str::<serde_json::Value>(s).unwrap();
+    assert_eq!(as_json(&imported[0].user_output), as_json(fixed));
+    assert_eq!(imported[0].created_at, record.created_at);
+
+    // Key order doesn't make a correction new
+    let reordered = correction_ops::export(&other, other_user.id).unwrap();
+    assert_eq!(correction_ops::import(&conn, user.id, &reordered).unwrap().skipped, 1);
+    assert!(imported[0].pinned);
+
+    // A bad entry leaves nothing half-imported
+    let bad = r#"[{"original_text": "Lunch with Bo", "ai_output": {}, "user_output": {}},
+                  {"original_text": " ", "ai_output": {}, "user_output": {}}]"#;
+    assert!(correction_ops::import(&other, other_user.id, bad).is_err());
+    assert_eq!(correction_repo::all(&other, other_user.id).unwrap().len(), 1);
+}
+
+#[test]
+fn edit_corrections_keep_their_kind_when_shared() {
+    let (conn, user, _) = setup();
+    let ai = r#"{"fields":{"name":null,"nickname":null,"howWeMet":null,"birthday":null,"location":"Berlin","notes":null},"addContacts":[],"removeContacts":[],"addLabels":[],"removeLabels":[]}"#;
+    let fixed = ai.replace(r#""removeContacts":[]"#, r#""removeContacts":["555-0000"]"#);
+    correction_repo::insert_kind(&conn, user.id, correction_repo::CorrectionKind::PersonEdit, "Ann moved to Berlin", ai, &fixed).unwrap();
+
+    let json = correction_ops::export(&conn, user.id).unwrap();
+    let exported: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(exported[0]["kind"], "person_edit");
+    assert_eq!(correction_ops::import(&conn, user.id, &json).unwrap().skipped, 1);
+
+    let (other, other_user, _) = setup();
+    correction_ops::import(&other, other_user.id, &json).unwrap();
+    let imported = correction_repo::all_of_kind(&other, other_user.id, correction_repo::CorrectionKind::PersonEdit).unwrap();
+    let as_json = |s: &str| serde_json::from_str::<serde_json::Value>(s).unwrap();
+    assert_eq!(as_json(&imported[0].user_output), as_json(&fixed));
+    assert!(correction_repo::all_of_kind(&other, other_user.id, correction_repo::CorrectionKind::Interaction).unwrap().is_empty());
+}
@@ -59,6 +59,100 @@ fn find_person_by_name_query() {
     assert_eq!(results[0].name, "Alice Smith");
 }
 
+#[test]
+fn find_person_by_name_is_fuzzy_and_ranked() {
+    let (conn, user, _) = setup();
+    person_ops::add_person(&conn, user.id, "José García", None, None, None, None, None).unwrap();
+    person_ops::add_person(&conn, user.id, "William Gates", None, None, None, None, None).unwrap();
+    person_ops::add_person(&conn, user.id, "Alice Smith", None, None, None, None, None).unwrap();
+    person_ops::add_person(&conn, user.id, "Malice Jones", None, None, None, None, None).unwrap();
+
+    let results = person_queries::find_by_name(&conn, user.id, "jose").unwrap();
+    assert_eq!(results[0].name, "José García");
+
+    let results = person_queries::find_by_name(&conn, user.id, "bill").unwrap();
+    assert_eq!(results[0].name, "William Gates");
+
+    let results = person_queries::find_by_name(&conn, user.id, "alcie").unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].name, "Alice Smith");
+
+    // Prefix matches rank above matches inside the name
+    let results = person_queries::find_by_name(&conn, user.id, "alice").unwrap();
+    let names: Vec<&str> = results.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, vec!["Alice Smith", "Malice Jones"]);
+}
+
+#[test]
+fn person_filter_compiles_to_sql() {
+    use prm::queries::person_filter::{filter_people, PersonFilter};
+
+    let (conn, user, _) = setup();
+    let bday = NaiveDate::from_ymd_opt(1990, 6, 20).unwrap();
+    let alice = person_ops::add_person(&conn, user.id, "Alice", None, None, Some(bday), None, Some("Boston")).unwrap();
+    let bob = person_ops::add_person(&conn, user.id, "Bob", None, None, None, None, Some("Boston")).unwrap();
+    let carol = person_ops::add_person(&conn, user.id, "Carol", None, None, None, None, Some("Athens")).unwrap();
+
+    let friend = label_ops::add_label(&conn, user.id, "climbing").unwrap();
+    relationship_ops::add_labels(&conn, user.id, alice.id, vec![friend.id]).unwrap();
+    relationship_ops::add_labels(&conn, user.id, bob.id, vec![friend.id]).unwrap();
+    circle_ops::create_circle(&conn, user.id, "Book Club", None, vec![alice.id, carol.id]).unwrap();
+    contact_ops::add_email(&conn, bob.id, "bob@example.com", None).unwrap();
+
+    let today = NaiveDate::from_ymd_opt(2024, 6, 15).unwrap();
+    let d = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+    interaction_ops::log_in_person(&conn, user.id, alice.id, "Park", vec!["walk".into()], None, d).unwrap();
+    let d = NaiveDate::from_ymd_opt(2024, 1, 10).unwrap();
+    interaction_ops::log_in_person(&conn, user.id, bob.id, "Cafe", vec!["chat".into()], None, d).unwrap();
+
+    let names = |query: &str| -> Vec<String> {
+        let filter = PersonFilter::parse(query).unwrap();
+        filter_people(&conn, user.id, &filter, today).unwrap().into_iter().map(|p| p.name).collect()
+    };
+
+    // Self is never listed
+    assert_eq!(names(""), vec!["Alice", "Bob", "Carol"]);
+    assert_eq!(names("label:CLIMBING"), vec!["Alice", "Bob"]);
+    assert_eq!(names(r#"circle:"book club" label:climbing"#), vec!["Alice"]);
+    assert_eq!(names("last>60d"), vec!["Bob", "Carol"]);
+    assert_eq!(names("last<1w"), vec!["Alice"]);
+    assert_eq!(names("last:never"), vec!["Carol"]);
+    assert_eq!(names("location:boston -has:email"), vec!["Alice"]);
+    assert_eq!(names("birthday:next7d"), vec!["Alice"]);
+    assert_eq!(names("medium:inperson sort:last-contact"), vec!["Bob", "Alice"]);
+    assert_eq!(names("sort:last-contact"), vec!["Carol", "Bob", "Alice"]);
+}
+
+#[test]
+fn person_filter_names_fold_accents_and_match_wildcards_literally() {
+    use prm::queries::person_filter::{filter_people, PersonFilter};
+
+    let (conn, user, _) = setup();
+    person_ops::add_person(&conn, user.id, "José Núñez", Some("Pepe"), None, None, None, None).unwrap();
+    let ann = person_ops::add_person(&conn, user.id, "Ann_Lee", None, None, None, None, None).unwrap();
+    let annie = person_ops::add_person(&conn, user.id, "Annie Hall", None, None, None, None, None).unwrap();
+
+    let today = NaiveDate::from_ymd_opt(2024, 6, 15).unwrap();
+    let names = |query: &str| -> Vec<String> {
+        let filter = PersonFilter::parse(query).unwrap();
+        filter_people(&conn, user.id, &filter, today).unwrap().into_iter().map(|p| p.name).collect()
+    };
+
+    assert_eq!(names("jose"), vec!["José Núñez"]);
+    assert_eq!(names("NUNEZ"), vec!["José Núñez"]);
+    assert_eq!(names("pépe"), vec!["José Núñez"]);
+    assert_eq!(names("ann_"), vec!["Ann_Lee"]);
+    assert!(names("%").is_empty());
+    assert_eq!(names("-jose"), vec!["Ann_Lee", "Annie Hall"]);
+
+    person_ops::update_person(&conn, ann.id, None, None, None, None, None, Some(Some("Zürich"))).unwrap();
+    person_ops::update_person(&conn, annie.id, None, None, None, None, None, Some(Some("100% Main St"))).unwrap();
+    assert_eq!(names("location:zurich"), vec!["Ann_Lee"]);
+    assert_eq!(names("location:%"), vec!["Annie Hall"]);
+    assert!(names("location:_").is_empty());
+    assert_eq!(names("-location:zurich"), vec!["Annie Hall", "José Núñez"]);
+}
+
 // ==========================================================================
 // RELATIONSHIP QUERIES
 // ==========================================================================
@@ -146,6 +240,77 @@ fn interactions_in_date_range() {
     assert_eq!(results[0].1.my_location, "Cafe");
 }
 
+#[test]
+fn timeline_collapses_group_events_by_period() {
+    use prm::queries::timeline_queries::{timeline, Period, TimelineFilter};
+
+    let (conn, user, _) = setup();
+    let alice = person_ops::add_person(&conn, user.id, "Alice", None, None, None, None, None).unwrap();
+    let bob = person_ops::add_person(&conn, user.id, "Bob", None, None, None, None, None).unwrap();
+    let carol = person_ops::add_person(&conn, user.id, "Carol", None, None, None, None, None).unwrap();
+    let club = circle_ops::create_circle(&conn, user.id, "Club", None, vec![carol.id]).unwrap();
+
+    // Dinner with Alice and Bob, logged once per person
+    let d1 = NaiveDate::from_ymd_opt(2024, 3, 5).unwrap();
+    for id in [alice.id, bob.id] {
+        interaction_ops::log_in_person(&conn, user.id, id, "Taverna", vec!["dinner".into()], None, d1).unwrap();
+    }
+    let d2 = NaiveDate::from_ymd_opt(2024, 3, 20).unwrap();
+    interaction_ops::log_remote(&conn, user.id, carol.id, InteractionMedium::PhoneCall, "Home", None, vec!["catch-up".into()], None, d2).unwrap();
+    let d3 = NaiveDate::from_ymd_opt(2024, 4, 2).unwrap();
+    interaction_ops::log_in_person(&conn, user.id, alice.id, "Park", vec!["walk".into()], None, d3).unwrap();
+
+    let from = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+    let to = NaiveDate::from_ymd_opt(2024, 4, 30).unwrap();
+    let periods = timeline(&conn, user.id, from, to, &TimelineFilter::default(), Period::Month).unwrap();
+    assert_eq!(periods.len(), 2);
+    assert_eq!(periods[0].events.len(), 2);
+    assert_eq!(periods[0].events[0].people.len(), 2);
+    assert_eq!(periods[0].interaction_count(), 3);
+    assert_eq!(periods[0].people_count(), 3);
+    assert_eq!(periods[1].start, NaiveDate::from_ymd_opt(2024, 4, 1).unwrap());
+
+    let weeks = timeline(&conn, user.id, from, to, &TimelineFilter::default(), Period::Week).unwrap();
+    assert_eq!(weeks.len(), 3);
+    assert_eq!(weeks[0].start, NaiveDate::from_ymd_opt(2024, 3, 4).unwrap());
+
+    let filter = TimelineFilter { circle: Some(club.id), ..Default::default() };
+    let periods = timeline(&conn, user.id, from, to, &filter, Period::Month).unwrap();
+    assert_eq!(periods.len(), 1);
+    assert_eq!(periods[0].events[0].people[0].name, "Carol");
+
+    let filter = TimelineFilter { medium: Some(InteractionMedium::InPerson), ..Default::default() };
+    let periods = timeline(&conn, user.id, from, to, &filter, Period::Month).unwrap();
+    assert_eq!(periods.iter().map(|p| p.events.len()).sum::<usize>(), 2);
+}
+
+#[test]
+fn timeline_keeps_separate_same_day_interactions_apart() {
+    use prm::queries::timeline_queries::{timeline, Period, TimelineFilter};
+
+    let (conn, user, _) = setup();
+    let alice = person_ops::add_person(&conn, user.id, "Alice", None, None, None, None, None).unwrap();
+    let bob = person_ops::add_person(&conn, user.id, "Bob", None, None, None, None, None).unwrap();
+
+    // A morning call with Alice and an evening call with Bob, logged separately
+    let day = NaiveDate::from_ymd_opt(2024, 3, 5).unwrap();
+    for (id, logged_at) in [(alice.id, "2024-03-05 09:12:00"), (bob.id, "2024-03-05 21:40:00")] {
+        let call = interaction_ops::log_remote(
+            &conn, user.id, id, InteractionMedium::PhoneCall, "Home", None, vec!["catch up".into()], None, day,
+        )
+        .unwrap();
+        conn.execute(
+            "UPDATE interactions SET created_at = ?1 WHERE id = ?2",
+            rusqlite::params![logged_at, call.id.value.to_string()],
+        )
+        .unwrap();
+    }
+
+    let periods = timeline(&conn, user.id, day, day, &TimelineFilter::default(), Period::Week).unwrap();
+    assert_eq!(periods[0].events.len(), 2);
+    assert!(periods[0].events.iter().all(|e| e.people.len() == 1 && e.interaction_ids.len() == 1));
+}
+
 // ==========================================================================
 // CIRCLE QUERIES
 // ==========================================================================
@@ -219,6 +384,65 @@ fn reminder_never_contacted() {
     }
 }
 
+#[test]
+fn overdue_status_json_shape() {
+    let overdue = serde_json::to_value(reminder_queries::OverdueStatus::DaysOverdue(3)).unwrap();
+    assert_eq!(overdue, serde_json::json!({"status": "days_overdue", "days": 3}));
+
+    let never = serde_json::to_value(reminder_queries::OverdueStatus::NeverContacted).unwrap();
+    assert_eq!(never, serde_json::json!({"status": "never_contacted"}));
+}
+
+#[test]
+fn suggested_cadence_follows_past_contact() {
+    let d = |m, day| NaiveDate::from_ymd_opt(2024, m, day).unwrap();
+    assert_eq!(reminder_queries::cadence_from_dates(&[]), 30);
+    assert_eq!(reminder_queries::cadence_from_dates(&[d(1, 1), d(1, 1)]), 30);
+    // Weekly-ish, with one long gap
+    assert_eq!(reminder_queries::cadence_from_dates(&[d(1, 1), d(1, 8), d(1, 16), d(3, 1)]), 7);
+    assert_eq!(reminder_queries::cadence_from_dates(&[d(1, 1), d(3, 5), d(5, 1)]), 60);
+
+    let (conn, user, _) = setup();
+    let alice = person_ops::add_person(&conn, user.id, "Alice", None, None, None, None, None).unwrap();
+    for date in [d(1, 1), d(1, 15), d(1, 29)] {
+        interaction_ops::log_in_person(&conn, user.id, alice.id, "Park", vec!["walk".into()], None, date).unwrap();
+    }
+    assert_eq!(reminder_queries::suggested_cadence(&conn, alice.id).unwrap(), 14);
+}
+
+#[test]
+fn snoozed_reminders_are_hidden_until_the_snooze_ends() {
+    let (conn, user, _) = setup();
+    let alice = person_ops::add_person(&conn, user.id, "Alice", None, None, None, None, None).unwrap();
+    relationship_ops::set_reminder(&conn, alice.id, Some(7)).unwrap();
+    let d = |day| NaiveDate::from_ymd_opt(2024, 6, day).unwrap();
+    interaction_ops::log_in_person(&conn, user.id, alice.id, "Park", vec!["walk".into()], None, d(1)).unwrap();
+
+    relationship_ops::snooze_reminder(&conn, alice.id, d(15)).unwrap();
+    assert!(reminder_queries::people_needing_reminder(&conn, user.id, d(11)).unwrap().is_empty());
+    assert!(reminder_queries::all_reminders(&conn, user.id, d(11)).unwrap().is_empty());
+    assert_eq!(reminder_queries::people_needing_reminder(&conn, user.id, d(15)).unwrap().len(), 1);
+}
+
+#[test]
+fn added_since_lists_new_people_without_self() {
+    let (conn, user, _) = setup();
+    let alice = person_ops::add_person(&conn, user.id, "Alice", None, None, None, None, None).unwrap();
+    let bob = person_ops::add_person(&conn, user.id, "Bob", None, None, None, None, None).unwrap();
+    person_ops::archive_person(&conn, bob.id).unwrap();
+    conn.execute(
+        "UPDATE people SET created_at = '2024-01-01 12:00:00' WHERE name = 'Alice'",
+        [],
+    )
+    .unwrap();
+
+    let today = chrono::Local::now().date_naive();
+    assert!(person_queries::added_since(&conn, user.id, today).unwrap().is_empty());
+    let all = person_queries::added_since(&conn, user.id, NaiveDate::from_ymd_opt(2023, 12, 25).unwrap()).unwrap();
+    assert_eq!(all.len(), 1);
+    assert_eq!(all[0].id, alice.id);
+}
+
 #[test]
 fn people_needing_reminder_query() {
     let (conn, user, _) = setup();
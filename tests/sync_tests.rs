@@ -0,0 +1,219 @@
+use std::path::PathBuf;
+
+use prm::db::*;
+use prm::model::*;
+use prm::ops::*;
+use prm::queries::*;
+use prm::sync::vault;
+
+fn setup() -> (rusqlite::Connection, User, Person) {
+    let conn = schema::test_connection();
+    let user = User::create("Petros".into(), "petros@example.com".into());
+    network_repo::insert_user(&conn, &user).unwrap();
+
+    let self_person = Person::create_self("Petros".into());
+    person_repo::insert(&conn, user.id, &self_person).unwrap();
+    network_repo::set_network_metadata(&conn, user.id, self_person.id).unwrap();
+
+    for label in RelationshipLabel::defaults() {
+        relationship_repo::insert_label(&conn, user.id, &label).unwrap();
+    }
+
+    (conn, user, self_person)
+}
+
+fn temp_vault() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("prm-vault-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn replace_in_file(path: &std::path::Path, from: &str, to: &str) {
+    let content = std::fs::read_to_string(path).unwrap();
+    assert!(content.contains(from), "{} not found in:\n{}", from, content);
+    std::fs::write(path, content.replace(from, to)).unwrap();
+}
+
+// ==========================================================================
+// VAULT SYNC
+// ==========================================================================
+
+#[test]
+fn vault_sync_exports_people_and_is_idempotent() {
+    let (conn, user, me) = setup();
+    let alice = person_ops::add_person(&conn, user.id, "Alice", Some("Al"), None, None, Some("Likes jazz"), Some("Boston")).unwrap();
+    contact_ops::add_phone(&conn, alice.id, "555-1234", Some("mobile")).unwrap();
+    let dir = temp_vault();
+
+    let report = vault::sync(&conn, user.id, me.id, &dir).unwrap();
+    assert_eq!(report.written, vec!["Alice".to_string()]);
+
+    let content = std::fs::read_to_string(dir.join("Alice.md")).unwrap();
+    assert!(content.contains(&format!("id: {}", alice.id)));
+    assert!(content.contains("location: Boston"));
+    assert!(content.contains("value: \"555-1234\""));
+    assert!(content.contains("Likes jazz"));
+    assert!(!dir.join("Petros.md").exists());
+
+    let again = vault::sync(&conn, user.id, me.id, &dir).unwrap();
+    assert!(again.written.is_empty());
+    assert!(again.applied.is_empty());
+    assert!(again.conflicts.is_empty());
+}
+
+#[test]
+fn vault_sync_applies_file_edits() {
+    let (conn, user, me) = setup();
+    let alice = person_ops::add_person(&conn, user.id, "Alice", None, None, None, None, Some("Boston")).unwrap();
+    let dir = temp_vault();
+    vault::sync(&conn, user.id, me.id, &dir).unwrap();
+
+    let path = dir.join("Alice.md");
+    replace_in_file(&path, "location: Boston", "location: Berlin");
+    replace_in_file(&path, "labels: []", "labels: [friend, climbing buddy]");
+    replace_in_file(&path, "circles: []", "circles: [Book Club]");
+    replace_in_file(&path, "reminder_days:\n", "reminder_days: 14\n");
+
+    let report = vault::sync(&conn, user.id, me.id, &dir).unwrap();
+    assert_eq!(report.applied, vec!["Alice".to_string()]);
+    assert!(report.errors.is_empty(), "{:?}", report.errors);
+
+    let updated = person_repo::find_by_id(&conn, alice.id).unwrap().unwrap();
+    assert_eq!(updated.location.as_deref(), Some("Berlin"));
+    let mut labels: Vec<String> = relationship_queries::labels_for(&conn, alice.id).unwrap().into_iter().map(|l| l.name).collect();
+    labels.sort();
+    assert_eq!(labels, vec!["climbing buddy", "friend"]);
+    let circles = circle_queries::circles_for_person(&conn, user.id, alice.id).unwrap();
+    assert_eq!(circles[0].name, "Book Club");
+    let rel = relationship_queries::get_relationship(&conn, alice.id).unwrap().unwrap();
+    assert_eq!(rel.reminder_days, Some(14));
+}
+
+#[test]
+fn vault_sync_merges_edits_to_different_fields() {
+    let (conn, user, me) = setup();
+    let alice = person_ops::add_person(&conn, user.id, "Alice", None, None, None, None, Some("Boston")).unwrap();
+    let dir = temp_vault();
+    vault::sync(&conn, user.id, me.id, &dir).unwrap();
+
+    replace_in_file(&dir.join("Alice.md"), "location: Boston", "location: Berlin");
+    person_ops::update_person(&conn, alice.id, None, Some(Some("Al")), None, None, None, None).unwrap();
+
+    let report = vault::sync(&conn, user.id, me.id, &dir).unwrap();
+    assert!(report.conflicts.is_empty());
+
+    let updated = person_repo::find_by_id(&conn, alice.id).unwrap().unwrap();
+    assert_eq!(updated.location.as_deref(), Some("Berlin"));
+    assert_eq!(updated.nickname.as_deref(), Some("Al"));
+    let content = std::fs::read_to_string(dir.join("Alice.md")).unwrap();
+    assert!(content.contains("nickname: Al"));
+}
+
+#[test]
+fn vault_sync_reports_conflicts_without_overwriting() {
+    let (conn, user, me) = setup();
+    let alice = person_ops::add_person(&conn, user.id, "Alice", None, None, None, None, Some("Boston")).unwrap();
+    let dir = temp_vault();
+    vault::sync(&conn, user.id, me.id, &dir).unwrap();
+
+    let path = dir.join("Alice.md");
+    replace_in_file(&path, "location: Boston", "location: Berlin");
+    person_ops::update_person(&conn, alice.id, None, None, None, None, None, Some(Some("Paris"))).unwrap();
+
+    let report = vault::sync(&conn, user.id, me.id, &dir).unwrap();
+    assert_eq!(report.conflicts.len(), 1);
+    assert_eq!(report.conflicts[0].fields, vec!["location".to_string()]);
+
+    let db = person_repo::find_by_id(&conn, alice.id).unwrap().unwrap();
+    assert_eq!(db.location.as_deref(), Some("Paris"));
+    assert!(std::fs::read_to_string(&path).unwrap().contains("location: Berlin"));
+}
+
+#[test]
+fn vault_sync_applies_file_edits_with_an_old_modification_time() {
+    let (conn, user, me) = setup();
+    let alice = person_ops::add_person(&conn, user.id, "Alice", None, None, None, None, Some("Boston")).unwrap();
+    let dir = temp_vault();
+    vault::sync(&conn, user.id, me.id, &dir).unwrap();
+
+    // As if checked out from git: edited, but dated before the last sync
+    let path = dir.join("Alice.md");
+    replace_in_file(&path, "location: Boston", "location: Berlin");
+    let old = std::time::SystemTime::now() - std::time::Duration::from_secs(30 * 24 * 3600);
+    std::fs::File::options().write(true).open(&path).unwrap().set_modified(old).unwrap();
+
+    let report = vault::sync(&conn, user.id, me.id, &dir).unwrap();
+    assert_eq!(report.applied, vec!["Alice".to_string()]);
+    let updated = person_repo::find_by_id(&conn, alice.id).unwrap().unwrap();
+    assert_eq!(updated.location.as_deref(), Some("Berlin"));
+    assert!(std::fs::read_to_string(&path).unwrap().contains("location: Berlin"));
+
+    // Edited on both sides: a conflict, and the file is left alone
+    replace_in_file(&path, "location: Berlin", "location: Lisbon");
+    std::fs::File::options().write(true).open(&path).unwrap().set_modified(old).unwrap();
+    person_ops::update_person(&conn, alice.id, None, None, None, None, None, Some(Some("Paris"))).unwrap();
+    let report = vault::sync(&conn, user.id, me.id, &dir).unwrap();
+    assert_eq!(report.conflicts.len(), 1);
+    assert!(std::fs::read_to_string(&path).unwrap().contains("location: Lisbon"));
+}
+
+#[test]
+fn vault_sync_creates_people_from_new_files() {
+    let (conn, user, me) = setup();
+    let dir = temp_vault();
+    std::fs::write(
+        dir.join("Priya Shah.md"),
+        "---\nname: Priya Shah\nlocation: Portland\ncontacts:\n  - type: email\n    value: priya@example.com\n---\n\nMet at RustConf.\n",
+    )
+    .unwrap();
+
+    let report = vault::sync(&conn, user.id, me.id, &dir).unwrap();
+    assert_eq!(report.created, vec!["Priya Shah".to_string()]);
+
+    let people = person_queries::find_by_name(&conn, user.id, "Priya").unwrap();
+    assert_eq!(people.len(), 1);
+    assert_eq!(people[0].notes.as_deref(), Some("Met at RustConf."));
+    let emails = contact_queries::emails_for(&conn, people[0].id).unwrap();
+    assert_eq!(emails.len(), 1);
+
+    let content = std::fs::read_to_string(dir.join("Priya Shah.md")).unwrap();
+    assert!(content.contains(&format!("id: {}", people[0].id)));
+}
+
+#[test]
+fn vault_sync_never_overwrites_files_it_cannot_read() {
+    let (conn, user, me) = setup();
+    person_ops::add_person(&conn, user.id, "Alice", None, None, None, None, None).unwrap();
+    let dir = temp_vault();
+    std::fs::write(dir.join("Alice.md"), "My own notes about Alice.\n").unwrap();
+
+    let report = vault::sync(&conn, user.id, me.id, &dir).unwrap();
+    assert_eq!(report.errors, vec!["Alice.md: Missing YAML frontmatter".to_string()]);
+    assert_eq!(report.written, vec!["Alice".to_string()]);
+
+    assert_eq!(std::fs::read_to_string(dir.join("Alice.md")).unwrap(), "My own notes about Alice.\n");
+    assert!(std::fs::read_to_string(dir.join("Alice (2).md")).unwrap().contains("name: Alice"));
+}
+
+#[test]
+fn vault_sync_matches_files_without_an_id_to_people_by_name() {
+    let (conn, user, me) = setup();
+    let bob = person_ops::add_person(&conn, user.id, "Bob", None, None, None, None, None).unwrap();
+    let dir = temp_vault();
+    std::fs::write(dir.join("Bob.md"), "---\nname: Bob\n---\n").unwrap();
+
+    let report = vault::sync(&conn, user.id, me.id, &dir).unwrap();
+    assert!(report.created.is_empty());
+    assert!(report.errors.is_empty(), "{:?}", report.errors);
+    assert_eq!(person_queries::find_by_name(&conn, user.id, "Bob").unwrap().len(), 1);
+    assert!(std::fs::read_to_string(dir.join("Bob.md")).unwrap().contains(&format!("id: {}", bob.id)));
+
+    // A second file naming Bob doesn't create another Bob
+    std::fs::write(dir.join("Bobby.md"), "---\nname: bob\n---\n").unwrap();
+    let report = vault::sync(&conn, user.id, me.id, &dir).unwrap();
+    assert!(report.created.is_empty());
+    assert_eq!(report.errors.len(), 1);
+    assert!(report.errors[0].starts_with("Bobby.md: Bob is already in your network"));
+    assert_eq!(person_queries::find_by_name(&conn, user.id, "Bob").unwrap().len(), 1);
+    assert_eq!(std::fs::read_to_string(dir.join("Bobby.md")).unwrap(), "---\nname: bob\n---\n");
+}